@@ -0,0 +1,1576 @@
+//! In-process NDI backend.
+//!
+//! Senders created with this backend are discoverable by finders and receivable by receivers
+//! of the same process. Frames are copied on send and handed out unchanged to all connected
+//! receivers, i.e. no color format conversion is happening.
+
+use crate::ndisys;
+use crate::ndisys::*;
+
+use super::Backend;
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi;
+use std::mem;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time;
+
+// Maximum number of frames queued per receiver before the oldest ones are dropped
+const MAX_QUEUED_FRAMES: usize = 64;
+
+const DEFAULT_GROUPS: &str = "public";
+
+#[derive(Default)]
+pub struct Loopback {
+    registry: Mutex<Registry>,
+    sources_changed: Condvar,
+}
+
+#[derive(Default)]
+struct Registry {
+    senders: Vec<Arc<SenderInfo>>,
+    receivers: Vec<Weak<ReceiverShared>>,
+    // Increased whenever a sender appears or disappears
+    generation: u64,
+    next_id: u64,
+}
+
+impl Registry {
+    // Senders the receiver is connected to directly or via a routing instance
+    fn senders_for(&self, receiver: &ReceiverShared) -> Vec<Arc<SenderInfo>> {
+        self.senders
+            .iter()
+            .filter(|s| {
+                !s.is_routing
+                    && (receiver.is_connected_to(s)
+                        || self.senders.iter().any(|routing| {
+                            routing.routes_to(s) && receiver.is_connected_to(routing)
+                        }))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+struct SenderInfo {
+    ndi_name: String,
+    url_address: String,
+    groups: Vec<String>,
+    // Metadata sent by receivers to this sender
+    metadata_queue: Mutex<VecDeque<QueuedFrame>>,
+    metadata_cond: Condvar,
+    // Routing instances forward the source given as NDI name and/or URL/address
+    is_routing: bool,
+    route: Mutex<Option<(Option<String>, Option<String>)>>,
+    // Source the receivers of this sender switch to when it goes away
+    failover: Mutex<Option<(Option<String>, Option<String>)>>,
+    // Metadata sent to every receiver when it connects
+    connection_metadata: Mutex<Vec<ffi::CString>>,
+}
+
+impl SenderInfo {
+    fn push_metadata(&self, data: ffi::CString, timecode: i64) {
+        let metadata_frame = NDIlib_metadata_frame_t {
+            length: data.as_bytes_with_nul().len() as i32,
+            timecode,
+            p_data: data.as_ptr(),
+        };
+
+        let mut queue = self.metadata_queue.lock().unwrap();
+        while queue.len() >= MAX_QUEUED_FRAMES {
+            queue.pop_front();
+        }
+        queue.push_back(QueuedFrame::Metadata(metadata_frame, data));
+        self.metadata_cond.notify_one();
+    }
+
+    fn routes_to(&self, sender: &SenderInfo) -> bool {
+        match *self.route.lock().unwrap() {
+            Some((ref ndi_name, ref url_address)) => {
+                source_matches(ndi_name.as_deref(), url_address.as_deref(), sender)
+            }
+            None => false,
+        }
+    }
+}
+
+struct LoopbackRouting {
+    info: Arc<SenderInfo>,
+}
+
+struct LoopbackFind {
+    show_local_sources: bool,
+    groups: Vec<String>,
+    generation: u64,
+    // Storage for the sources returned by the last find_get_current_sources() call
+    sources: Vec<NDIlib_source_t>,
+    names: Vec<(ffi::CString, ffi::CString)>,
+}
+
+struct LoopbackRecv {
+    shared: Arc<ReceiverShared>,
+    // Frames currently handed out to the caller, keyed by their data pointer
+    in_flight: Mutex<HashMap<usize, QueuedFrame>>,
+}
+
+struct ReceiverShared {
+    // NDI name and URL/address of the source, changed by recv_connect()
+    source: Mutex<(Option<String>, Option<String>)>,
+    bandwidth: NDIlib_recv_bandwidth_e,
+    queue: Mutex<VecDeque<QueuedFrame>>,
+    queue_cond: Condvar,
+    tally: Mutex<NDIlib_tally_t>,
+    performance: Mutex<Performance>,
+    // Set when the connected sender appeared or disappeared, reported once by recv_capture_v3()
+    status_changed: AtomicBool,
+    // Metadata sent to every sender when connecting to it
+    connection_metadata: Mutex<Vec<ffi::CString>>,
+}
+
+struct LoopbackFramesync {
+    shared: Arc<ReceiverShared>,
+    state: Mutex<FramesyncState>,
+    // Frames currently handed out to the caller, keyed by their data pointer
+    in_flight: Mutex<HashMap<usize, QueuedFrame>>,
+}
+
+#[derive(Default)]
+struct FramesyncState {
+    // Last received video frame, repeated until a new one arrives
+    video: Option<(NDIlib_video_frame_v2_t, Vec<u8>)>,
+    sample_rate: i32,
+    // Received audio samples per channel that were not captured yet
+    audio: Vec<VecDeque<f32>>,
+}
+
+#[derive(Default)]
+struct Performance {
+    total: [i64; 3],
+    dropped: [i64; 3],
+}
+
+struct LoopbackSend {
+    info: Arc<SenderInfo>,
+    clock_video: bool,
+    clock_audio: bool,
+    next_video_time: Mutex<Option<time::Instant>>,
+    next_audio_time: Mutex<Option<time::Instant>>,
+    // Metadata frames currently handed out to the caller, keyed by their data pointer
+    in_flight: Mutex<HashMap<usize, QueuedFrame>>,
+    // Tally last returned by send_get_tally()
+    tally: Mutex<NDIlib_tally_t>,
+}
+
+// The raw frame structs point into the owned data next to them, which is only kept alive here
+#[allow(dead_code)]
+enum QueuedFrame {
+    Video(NDIlib_video_frame_v2_t, Vec<u8>, Option<ffi::CString>),
+    Audio(NDIlib_audio_frame_v3_t, Vec<f32>, Option<ffi::CString>),
+    Metadata(NDIlib_metadata_frame_t, ffi::CString),
+}
+
+unsafe impl Send for QueuedFrame {}
+
+impl QueuedFrame {
+    fn key(&self) -> usize {
+        match self {
+            QueuedFrame::Video(ref frame, ..) => frame.p_data as usize,
+            QueuedFrame::Audio(ref frame, ..) => frame.p_data as usize,
+            QueuedFrame::Metadata(ref frame, ..) => frame.p_data as usize,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            QueuedFrame::Video(..) => 0,
+            QueuedFrame::Audio(..) => 1,
+            QueuedFrame::Metadata(..) => 2,
+        }
+    }
+}
+
+impl ReceiverShared {
+    fn is_connected_to(&self, sender: &SenderInfo) -> bool {
+        let source = self.source.lock().unwrap();
+        source_matches(source.0.as_deref(), source.1.as_deref(), sender)
+    }
+
+    fn notify_status_change(&self) {
+        let _queue = self.queue.lock().unwrap();
+        self.status_changed.store(true, Ordering::SeqCst);
+        self.queue_cond.notify_all();
+    }
+
+    fn push_metadata(&self, data: ffi::CString, timecode: i64) {
+        let metadata_frame = NDIlib_metadata_frame_t {
+            length: data.as_bytes_with_nul().len() as i32,
+            timecode,
+            p_data: data.as_ptr(),
+        };
+
+        self.push(QueuedFrame::Metadata(metadata_frame, data));
+    }
+
+    fn push(&self, frame: QueuedFrame) {
+        let mut queue = self.queue.lock().unwrap();
+        let mut performance = self.performance.lock().unwrap();
+        while queue.len() >= MAX_QUEUED_FRAMES {
+            if let Some(dropped) = queue.pop_front() {
+                performance.dropped[dropped.index()] += 1;
+            }
+        }
+        performance.total[frame.index()] += 1;
+        queue.push_back(frame);
+        self.queue_cond.notify_one();
+    }
+}
+
+impl LoopbackFramesync {
+    // Takes all audio and video frames out of the receiver queue, leaving metadata frames there
+    fn pull_frames(&self, state: &mut FramesyncState) {
+        let mut queue = self.shared.queue.lock().unwrap();
+
+        let mut remaining = VecDeque::with_capacity(queue.len());
+        for frame in queue.drain(..) {
+            match frame {
+                QueuedFrame::Video(frame, data, _) => state.video = Some((frame, data)),
+                QueuedFrame::Audio(ref frame, ref data, _) => state.push_audio(frame, data),
+                frame => remaining.push_back(frame),
+            }
+        }
+        *queue = remaining;
+    }
+}
+
+impl FramesyncState {
+    fn push_audio(&mut self, frame: &NDIlib_audio_frame_v3_t, data: &[f32]) {
+        if frame.FourCC != NDIlib_FourCC_audio_type_FLTp || frame.sample_rate <= 0 {
+            return;
+        }
+
+        let no_channels = frame.no_channels.max(0) as usize;
+        if frame.sample_rate != self.sample_rate || no_channels != self.audio.len() {
+            self.sample_rate = frame.sample_rate;
+            self.audio = vec![VecDeque::new(); no_channels];
+        }
+
+        let stride =
+            frame.channel_stride_or_data_size_in_bytes.max(0) as usize / mem::size_of::<f32>();
+        let no_samples = (frame.no_samples.max(0) as usize).min(stride);
+        // Like the SDK, don't buffer more than one second of audio
+        let max_samples = self.sample_rate as usize;
+
+        for (channel, queue) in self.audio.iter_mut().enumerate() {
+            if let Some(samples) = data.get(channel * stride..channel * stride + no_samples) {
+                queue.extend(samples);
+            }
+            if queue.len() > max_samples {
+                let excess = queue.len() - max_samples;
+                queue.drain(..excess);
+            }
+        }
+    }
+}
+
+fn source_matches(ndi_name: Option<&str>, url_address: Option<&str>, sender: &SenderInfo) -> bool {
+    match (url_address, ndi_name) {
+        (Some(url_address), _) => url_address == sender.url_address,
+        (None, Some(ndi_name)) => ndi_name == sender.ndi_name,
+        (None, None) => false,
+    }
+}
+
+fn parse_groups(groups: *const c_char) -> Vec<String> {
+    let groups = if groups.is_null() {
+        DEFAULT_GROUPS
+    } else {
+        unsafe { ffi::CStr::from_ptr(groups) }
+            .to_str()
+            .unwrap_or(DEFAULT_GROUPS)
+    };
+
+    groups
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn opt_string(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        unsafe { ffi::CStr::from_ptr(s) }
+            .to_str()
+            .ok()
+            .map(String::from)
+    }
+}
+
+fn now_100ns() -> i64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| (d.as_nanos() / 100) as i64)
+        .unwrap_or(0)
+}
+
+fn video_data_size(frame: &NDIlib_video_frame_v2_t) -> usize {
+    let xres = frame.xres.max(0) as usize;
+    let yres = frame.yres.max(0) as usize;
+
+    let stride = if frame.line_stride_or_data_size_in_bytes > 0 {
+        frame.line_stride_or_data_size_in_bytes as usize
+    } else {
+        match frame.FourCC {
+            ndisys::NDIlib_FourCC_video_type_UYVY | ndisys::NDIlib_FourCC_video_type_UYVA => {
+                2 * xres
+            }
+            ndisys::NDIlib_FourCC_video_type_P216 | ndisys::NDIlib_FourCC_video_type_PA16 => {
+                2 * xres
+            }
+            ndisys::NDIlib_FourCC_video_type_BGRA
+            | ndisys::NDIlib_FourCC_video_type_BGRX
+            | ndisys::NDIlib_FourCC_video_type_RGBA
+            | ndisys::NDIlib_FourCC_video_type_RGBX => 4 * xres,
+            _ => xres,
+        }
+    };
+
+    let size = match frame.FourCC {
+        ndisys::NDIlib_FourCC_video_type_UYVY
+        | ndisys::NDIlib_FourCC_video_type_BGRA
+        | ndisys::NDIlib_FourCC_video_type_BGRX
+        | ndisys::NDIlib_FourCC_video_type_RGBA
+        | ndisys::NDIlib_FourCC_video_type_RGBX => stride * yres,
+        // Alpha plane with one byte per pixel after the UYVY data
+        ndisys::NDIlib_FourCC_video_type_UYVA => stride * yres + xres * yres,
+        ndisys::NDIlib_FourCC_video_type_P216 => 2 * stride * yres,
+        ndisys::NDIlib_FourCC_video_type_PA16 => 3 * stride * yres,
+        ndisys::NDIlib_FourCC_video_type_I420 | ndisys::NDIlib_FourCC_video_type_YV12 => {
+            stride * yres + 2 * (stride / 2) * ((yres + 1) / 2)
+        }
+        ndisys::NDIlib_FourCC_video_type_NV12 => stride * yres + stride * ((yres + 1) / 2),
+        // Compressed formats store the data size instead of the stride
+        _ => frame.line_stride_or_data_size_in_bytes.max(0) as usize,
+    };
+
+    match frame.frame_format_type {
+        NDIlib_frame_format_type_e::NDIlib_frame_format_type_field_0
+        | NDIlib_frame_format_type_e::NDIlib_frame_format_type_field_1 => size / 2,
+        _ => size,
+    }
+}
+
+unsafe fn copy_metadata(p_metadata: *const c_char) -> Option<ffi::CString> {
+    if p_metadata.is_null() {
+        None
+    } else {
+        Some(ffi::CStr::from_ptr(p_metadata).to_owned())
+    }
+}
+
+// Waits until the given time has passed and schedules the next frame
+fn clock(next_time: &Mutex<Option<time::Instant>>, duration: time::Duration) {
+    let mut next_time = next_time.lock().unwrap();
+    let now = time::Instant::now();
+
+    let start = match *next_time {
+        Some(next_time) if next_time > now => {
+            thread::sleep(next_time - now);
+            next_time
+        }
+        _ => now,
+    };
+
+    *next_time = Some(start + duration);
+}
+
+impl Loopback {
+    // PTZ commands are sent to the sender as metadata, like the SDK does
+    unsafe fn recv_ptz_command(&self, p_instance: NDIlib_recv_instance_t, command: &str) -> bool {
+        let command = ffi::CString::new(command).unwrap();
+        let frame = NDIlib_metadata_frame_t {
+            length: command.as_bytes_with_nul().len() as i32,
+            timecode: NDIlib_send_timecode_synthesize,
+            p_data: command.as_ptr(),
+        };
+
+        self.recv_send_metadata(p_instance, &frame)
+    }
+
+    // Combined tally of all receivers connected to the sender
+    fn tally_for(&self, sender: &SenderInfo) -> NDIlib_tally_t {
+        let mut tally = NDIlib_tally_t {
+            on_program: false,
+            on_preview: false,
+        };
+
+        for receiver in self.receivers_for(sender) {
+            let receiver_tally = receiver.tally.lock().unwrap();
+            tally.on_program |= receiver_tally.on_program;
+            tally.on_preview |= receiver_tally.on_preview;
+        }
+
+        tally
+    }
+
+    // Senders echo the combined tally of all their receivers to them, like the SDK does
+    fn echo_tally(&self, receiver: &ReceiverShared) {
+        let senders = self.registry.lock().unwrap().senders_for(receiver);
+        for sender in senders {
+            let tally = self.tally_for(&sender);
+            let echo = ffi::CString::new(format!(
+                "<ndi_tally_echo on_program=\"{}\" on_preview=\"{}\"/>",
+                tally.on_program, tally.on_preview
+            ))
+            .unwrap();
+
+            for receiver in self.receivers_for(&sender) {
+                receiver.push_metadata(echo.clone(), now_100ns());
+            }
+        }
+    }
+
+    // Exchanges the connection metadata of the receiver and the senders it is connected to
+    fn push_connection_metadata(&self, receiver: &ReceiverShared) {
+        let senders = self.registry.lock().unwrap().senders_for(receiver);
+        for sender in senders {
+            for data in sender.connection_metadata.lock().unwrap().iter() {
+                receiver.push_metadata(data.clone(), now_100ns());
+            }
+            for data in receiver.connection_metadata.lock().unwrap().iter() {
+                sender.push_metadata(data.clone(), now_100ns());
+            }
+        }
+    }
+
+    // Receivers connected to the sender directly or via a routing instance
+    fn receivers_for(&self, sender: &SenderInfo) -> Vec<Arc<ReceiverShared>> {
+        let mut registry = self.registry.lock().unwrap();
+        registry.receivers.retain(|r| r.strong_count() > 0);
+
+        let routings = registry
+            .senders
+            .iter()
+            .filter(|s| s.routes_to(sender))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        registry
+            .receivers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|r| {
+                r.is_connected_to(sender)
+                    || routings.iter().any(|routing| r.is_connected_to(routing))
+            })
+            .collect()
+    }
+}
+
+impl Backend for Loopback {
+    fn initialize(&self) -> Result<(), glib::BoolError> {
+        Ok(())
+    }
+
+    unsafe fn find_create_v2(
+        &self,
+        p_create_settings: *const NDIlib_find_create_t,
+    ) -> NDIlib_find_instance_t {
+        let (show_local_sources, groups) = if p_create_settings.is_null() {
+            (true, parse_groups(ptr::null()))
+        } else {
+            let settings = &*p_create_settings;
+            (settings.show_local_sources, parse_groups(settings.p_groups))
+        };
+
+        Box::into_raw(Box::new(LoopbackFind {
+            show_local_sources,
+            groups,
+            generation: 0,
+            sources: vec![],
+            names: vec![],
+        })) as NDIlib_find_instance_t
+    }
+
+    unsafe fn find_destroy(&self, p_instance: NDIlib_find_instance_t) {
+        drop(Box::from_raw(p_instance as *mut LoopbackFind));
+    }
+
+    unsafe fn find_wait_for_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        timeout_in_ms: u32,
+    ) -> bool {
+        let find = &mut *(p_instance as *mut LoopbackFind);
+        let timeout = time::Duration::from_millis(timeout_in_ms as u64);
+
+        let registry = self.registry.lock().unwrap();
+        let (registry, _) = self
+            .sources_changed
+            .wait_timeout_while(registry, timeout, |registry| {
+                registry.generation == find.generation
+            })
+            .unwrap();
+
+        if registry.generation == find.generation {
+            false
+        } else {
+            find.generation = registry.generation;
+            true
+        }
+    }
+
+    unsafe fn find_get_current_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        p_no_sources: *mut u32,
+    ) -> *const NDIlib_source_t {
+        let find = &mut *(p_instance as *mut LoopbackFind);
+        let registry = self.registry.lock().unwrap();
+
+        // All loopback sources are local to this machine
+        find.names = if find.show_local_sources {
+            registry
+                .senders
+                .iter()
+                .filter(|s| s.groups.iter().any(|g| find.groups.contains(g)))
+                .map(|s| {
+                    (
+                        ffi::CString::new(s.ndi_name.as_str()).unwrap(),
+                        ffi::CString::new(s.url_address.as_str()).unwrap(),
+                    )
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        find.sources = find
+            .names
+            .iter()
+            .map(|(ndi_name, url_address)| NDIlib_source_t {
+                p_ndi_name: ndi_name.as_ptr(),
+                p_url_address: url_address.as_ptr(),
+            })
+            .collect();
+
+        if !p_no_sources.is_null() {
+            *p_no_sources = find.sources.len() as u32;
+        }
+
+        find.sources.as_ptr()
+    }
+
+    unsafe fn recv_create_v3(
+        &self,
+        p_create_settings: *const NDIlib_recv_create_v3_t,
+    ) -> NDIlib_recv_instance_t {
+        if p_create_settings.is_null() {
+            return ptr::null_mut();
+        }
+        let settings = &*p_create_settings;
+
+        let shared = Arc::new(ReceiverShared {
+            source: Mutex::new((
+                opt_string(settings.source_to_connect_to.p_ndi_name),
+                opt_string(settings.source_to_connect_to.p_url_address),
+            )),
+            bandwidth: settings.bandwidth,
+            queue: Mutex::new(VecDeque::new()),
+            queue_cond: Condvar::new(),
+            tally: Mutex::new(NDIlib_tally_t {
+                on_program: false,
+                on_preview: false,
+            }),
+            performance: Mutex::new(Performance::default()),
+            status_changed: AtomicBool::new(false),
+            connection_metadata: Mutex::new(Vec::new()),
+        });
+
+        self.registry
+            .lock()
+            .unwrap()
+            .receivers
+            .push(Arc::downgrade(&shared));
+        self.push_connection_metadata(&shared);
+
+        Box::into_raw(Box::new(LoopbackRecv {
+            shared,
+            in_flight: Mutex::new(HashMap::new()),
+        })) as NDIlib_recv_instance_t
+    }
+
+    unsafe fn recv_destroy(&self, p_instance: NDIlib_recv_instance_t) {
+        drop(Box::from_raw(p_instance as *mut LoopbackRecv));
+    }
+
+    unsafe fn recv_connect(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_src: *const NDIlib_source_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let source = if p_src.is_null() {
+            (None, None)
+        } else {
+            (
+                opt_string((*p_src).p_ndi_name),
+                opt_string((*p_src).p_url_address),
+            )
+        };
+
+        // Frames of the previous source that were not captured yet are discarded
+        *recv.shared.source.lock().unwrap() = source;
+        recv.shared.queue.lock().unwrap().clear();
+        self.push_connection_metadata(&recv.shared);
+        recv.shared.notify_status_change();
+    }
+
+    unsafe fn recv_set_tally(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_tally: *const NDIlib_tally_t,
+    ) -> bool {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        if p_tally.is_null() {
+            return false;
+        }
+
+        *recv.shared.tally.lock().unwrap() = *p_tally;
+        self.echo_tally(&recv.shared);
+        true
+    }
+
+    unsafe fn recv_send_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) -> bool {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let frame = &*p_metadata;
+        let registry = self.registry.lock().unwrap();
+
+        let data = copy_metadata(frame.p_data);
+        let timecode = if frame.timecode == NDIlib_send_timecode_synthesize {
+            now_100ns()
+        } else {
+            frame.timecode
+        };
+
+        // Metadata sent to routing instances goes to the routed sender
+        let mut connected = false;
+        for sender in registry.senders_for(&recv.shared) {
+            connected = true;
+
+            if let Some(ref data) = data {
+                sender.push_metadata(data.clone(), timecode);
+            }
+        }
+
+        connected
+    }
+
+    unsafe fn recv_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let data = match copy_metadata((*p_metadata).p_data) {
+            None => return,
+            Some(data) => data,
+        };
+
+        // Also sent to the senders the receiver is already connected to
+        for sender in self.registry.lock().unwrap().senders_for(&recv.shared) {
+            sender.push_metadata(data.clone(), now_100ns());
+        }
+        recv.shared.connection_metadata.lock().unwrap().push(data);
+    }
+
+    unsafe fn recv_capture_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let timeout = time::Duration::from_millis(timeout_in_ms as u64);
+
+        let queue = recv.shared.queue.lock().unwrap();
+        let (mut queue, _) = recv
+            .shared
+            .queue_cond
+            .wait_timeout_while(queue, timeout, |queue| {
+                // Only wait for frames the caller can actually receive
+                !recv.shared.status_changed.load(Ordering::SeqCst)
+                    && !queue.iter().any(|frame| match frame {
+                        QueuedFrame::Video(..) => !p_video_data.is_null(),
+                        QueuedFrame::Audio(..) => !p_audio_data.is_null(),
+                        QueuedFrame::Metadata(..) => !p_metadata.is_null(),
+                    })
+            })
+            .unwrap();
+
+        if recv.shared.status_changed.swap(false, Ordering::SeqCst) {
+            return NDIlib_frame_type_e::NDIlib_frame_type_status_change;
+        }
+
+        let pos = queue.iter().position(|frame| match frame {
+            QueuedFrame::Video(..) => !p_video_data.is_null(),
+            QueuedFrame::Audio(..) => !p_audio_data.is_null(),
+            QueuedFrame::Metadata(..) => !p_metadata.is_null(),
+        });
+        let frame = match pos.and_then(|pos| queue.remove(pos)) {
+            None => return NDIlib_frame_type_e::NDIlib_frame_type_none,
+            Some(frame) => frame,
+        };
+        drop(queue);
+
+        let res = match frame {
+            QueuedFrame::Video(ref frame, ..) => {
+                *p_video_data = *frame;
+                NDIlib_frame_type_e::NDIlib_frame_type_video
+            }
+            QueuedFrame::Audio(ref frame, ..) => {
+                *p_audio_data = *frame;
+                NDIlib_frame_type_e::NDIlib_frame_type_audio
+            }
+            QueuedFrame::Metadata(ref frame, ..) => {
+                *p_metadata = *frame;
+                NDIlib_frame_type_e::NDIlib_frame_type_metadata
+            }
+        };
+
+        let key = frame.key();
+        if key != 0 {
+            recv.in_flight.lock().unwrap().insert(key, frame);
+        }
+
+        res
+    }
+
+    unsafe fn recv_free_video_v2(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let key = (*p_video_data).p_data as usize;
+        recv.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn recv_free_audio_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let key = (*p_audio_data).p_data as usize;
+        recv.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn recv_free_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let key = (*p_metadata).p_data as usize;
+        recv.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn recv_get_queue(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_queue_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let queue = recv.shared.queue.lock().unwrap();
+
+        let mut total = NDIlib_recv_queue_t {
+            video_frames: 0,
+            audio_frames: 0,
+            metadata_frames: 0,
+        };
+        for frame in &*queue {
+            match frame {
+                QueuedFrame::Video(..) => total.video_frames += 1,
+                QueuedFrame::Audio(..) => total.audio_frames += 1,
+                QueuedFrame::Metadata(..) => total.metadata_frames += 1,
+            }
+        }
+
+        *p_total = total;
+    }
+
+    unsafe fn recv_get_no_connections(&self, p_instance: NDIlib_recv_instance_t) -> i32 {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let registry = self.registry.lock().unwrap();
+
+        if registry.senders_for(&recv.shared).is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+
+    unsafe fn recv_get_web_control(&self, _p_instance: NDIlib_recv_instance_t) -> *const c_char {
+        ptr::null()
+    }
+
+    unsafe fn recv_free_string(
+        &self,
+        _p_instance: NDIlib_recv_instance_t,
+        _p_string: *const c_char,
+    ) {
+    }
+
+    unsafe fn recv_get_performance(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_performance_t,
+        p_dropped: *mut NDIlib_recv_performance_t,
+    ) {
+        let recv = &*(p_instance as *const LoopbackRecv);
+        let performance = recv.shared.performance.lock().unwrap();
+
+        let to_ffi = |counts: &[i64; 3]| NDIlib_recv_performance_t {
+            video_frames: counts[0],
+            audio_frames: counts[1],
+            metadata_frames: counts[2],
+        };
+
+        if !p_total.is_null() {
+            *p_total = to_ffi(&performance.total);
+        }
+        if !p_dropped.is_null() {
+            *p_dropped = to_ffi(&performance.dropped);
+        }
+    }
+
+    unsafe fn recv_ptz_is_supported(&self, _p_instance: NDIlib_recv_instance_t) -> bool {
+        false
+    }
+
+    unsafe fn recv_ptz_store_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!("<ntk_ptz_store_preset index=\"{}\"/>", preset_no),
+        )
+    }
+
+    unsafe fn recv_ptz_recall_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+        speed: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_recall_preset index=\"{}\" speed=\"{}\"/>",
+                preset_no, speed
+            ),
+        )
+    }
+
+    unsafe fn recv_ptz_zoom(&self, p_instance: NDIlib_recv_instance_t, zoom_value: f32) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!("<ntk_ptz_zoom zoom=\"{}\"/>", zoom_value),
+        )
+    }
+
+    unsafe fn recv_ptz_zoom_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        zoom_speed: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!("<ntk_ptz_zoom_speed zoom_speed=\"{}\"/>", zoom_speed),
+        )
+    }
+
+    unsafe fn recv_ptz_pan_tilt(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_value: f32,
+        tilt_value: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_pan_tilt pan=\"{}\" tilt=\"{}\"/>",
+                pan_value, tilt_value
+            ),
+        )
+    }
+
+    unsafe fn recv_ptz_pan_tilt_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_speed: f32,
+        tilt_speed: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_pan_tilt_speed pan_speed=\"{}\" tilt_speed=\"{}\"/>",
+                pan_speed, tilt_speed
+            ),
+        )
+    }
+
+    unsafe fn recv_ptz_auto_focus(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_focus mode=\"auto\"/>")
+    }
+
+    unsafe fn recv_ptz_focus(&self, p_instance: NDIlib_recv_instance_t, focus_value: f32) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_focus mode=\"manual\" distance=\"{}\"/>",
+                focus_value
+            ),
+        )
+    }
+
+    unsafe fn recv_ptz_focus_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        focus_speed: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!("<ntk_ptz_focus_speed distance=\"{}\"/>", focus_speed),
+        )
+    }
+
+    unsafe fn recv_ptz_white_balance_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_white_balance mode=\"auto\"/>")
+    }
+
+    unsafe fn recv_ptz_white_balance_indoor(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_white_balance mode=\"indoor\"/>")
+    }
+
+    unsafe fn recv_ptz_white_balance_outdoor(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_white_balance mode=\"outdoor\"/>")
+    }
+
+    unsafe fn recv_ptz_white_balance_oneshot(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_white_balance mode=\"one_shot\"/>")
+    }
+
+    unsafe fn recv_ptz_white_balance_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        red: f32,
+        blue: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_white_balance mode=\"manual\" red=\"{}\" blue=\"{}\"/>",
+                red, blue
+            ),
+        )
+    }
+
+    unsafe fn recv_ptz_exposure_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        self.recv_ptz_command(p_instance, "<ntk_ptz_exposure mode=\"auto\"/>")
+    }
+
+    unsafe fn recv_ptz_exposure_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        exposure_level: f32,
+    ) -> bool {
+        self.recv_ptz_command(
+            p_instance,
+            &format!(
+                "<ntk_ptz_exposure mode=\"manual\" value=\"{}\"/>",
+                exposure_level
+            ),
+        )
+    }
+
+    unsafe fn send_create(
+        &self,
+        p_create_settings: *const NDIlib_send_create_t,
+    ) -> NDIlib_send_instance_t {
+        if p_create_settings.is_null() {
+            return ptr::null_mut();
+        }
+        let settings = &*p_create_settings;
+        let ndi_name = match opt_string(settings.p_ndi_name) {
+            None => return ptr::null_mut(),
+            Some(ndi_name) => ndi_name,
+        };
+
+        let mut registry = self.registry.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+
+        // Same naming scheme as the SDK: machine name followed by the sender name
+        let info = Arc::new(SenderInfo {
+            ndi_name: format!("{} ({})", glib::host_name().to_uppercase(), ndi_name),
+            url_address: format!("loopback:{}", id),
+            groups: parse_groups(settings.p_groups),
+            metadata_queue: Mutex::new(VecDeque::new()),
+            metadata_cond: Condvar::new(),
+            is_routing: false,
+            route: Mutex::new(None),
+            failover: Mutex::new(None),
+            connection_metadata: Mutex::new(Vec::new()),
+        });
+
+        registry.senders.push(info.clone());
+        registry.generation += 1;
+        self.sources_changed.notify_all();
+        drop(registry);
+
+        for receiver in self.receivers_for(&info) {
+            for data in receiver.connection_metadata.lock().unwrap().iter() {
+                info.push_metadata(data.clone(), now_100ns());
+            }
+            receiver.notify_status_change();
+        }
+
+        Box::into_raw(Box::new(LoopbackSend {
+            info,
+            clock_video: settings.clock_video,
+            clock_audio: settings.clock_audio,
+            next_video_time: Mutex::new(None),
+            next_audio_time: Mutex::new(None),
+            in_flight: Mutex::new(HashMap::new()),
+            tally: Mutex::new(NDIlib_tally_t {
+                on_program: false,
+                on_preview: false,
+            }),
+        })) as NDIlib_send_instance_t
+    }
+
+    unsafe fn send_destroy(&self, p_instance: NDIlib_send_instance_t) {
+        let send = Box::from_raw(p_instance as *mut LoopbackSend);
+
+        let failover = send.info.failover.lock().unwrap().take();
+        for receiver in self.receivers_for(&send.info) {
+            if let Some(ref failover) = failover {
+                *receiver.source.lock().unwrap() = failover.clone();
+                receiver.queue.lock().unwrap().clear();
+            }
+            receiver.notify_status_change();
+        }
+
+        let mut registry = self.registry.lock().unwrap();
+        registry.senders.retain(|s| !Arc::ptr_eq(s, &send.info));
+        registry.generation += 1;
+        self.sources_changed.notify_all();
+    }
+
+    unsafe fn send_send_video_v2(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_video_data: *const NDIlib_video_frame_v2_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let frame = &*p_video_data;
+
+        if send.clock_video && frame.frame_rate_N > 0 && frame.frame_rate_D > 0 {
+            clock(
+                &send.next_video_time,
+                time::Duration::from_nanos(
+                    1_000_000_000 * frame.frame_rate_D as u64 / frame.frame_rate_N as u64,
+                ),
+            );
+        }
+
+        let size = video_data_size(frame);
+        let timestamp = now_100ns();
+
+        for receiver in self.receivers_for(&send.info) {
+            if receiver.bandwidth == NDIlib_recv_bandwidth_metadata_only
+                || receiver.bandwidth == NDIlib_recv_bandwidth_audio_only
+            {
+                continue;
+            }
+
+            let data = if frame.p_data.is_null() {
+                vec![]
+            } else {
+                Vec::from(slice::from_raw_parts(frame.p_data as *const u8, size))
+            };
+            let metadata = copy_metadata(frame.p_metadata);
+
+            let video_frame = NDIlib_video_frame_v2_t {
+                timecode: if frame.timecode == NDIlib_send_timecode_synthesize {
+                    timestamp
+                } else {
+                    frame.timecode
+                },
+                p_data: if data.is_empty() {
+                    ptr::null()
+                } else {
+                    data.as_ptr() as *const c_char
+                },
+                p_metadata: metadata.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null()),
+                timestamp,
+                ..*frame
+            };
+
+            receiver.push(QueuedFrame::Video(video_frame, data, metadata));
+        }
+    }
+
+    unsafe fn send_send_audio_v3(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_audio_data: *const NDIlib_audio_frame_v3_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let frame = &*p_audio_data;
+
+        if send.clock_audio && frame.sample_rate > 0 {
+            clock(
+                &send.next_audio_time,
+                time::Duration::from_nanos(
+                    1_000_000_000 * frame.no_samples.max(0) as u64 / frame.sample_rate as u64,
+                ),
+            );
+        }
+
+        let size = if frame.FourCC == NDIlib_FourCC_audio_type_FLTp {
+            frame.no_channels.max(0) as usize
+                * frame.channel_stride_or_data_size_in_bytes.max(0) as usize
+        } else {
+            frame.channel_stride_or_data_size_in_bytes.max(0) as usize
+        };
+        let timestamp = now_100ns();
+
+        for receiver in self.receivers_for(&send.info) {
+            if receiver.bandwidth == NDIlib_recv_bandwidth_metadata_only {
+                continue;
+            }
+
+            let mut data = vec![0.0f32; (size + mem::size_of::<f32>() - 1) / mem::size_of::<f32>()];
+            if !frame.p_data.is_null() {
+                ptr::copy_nonoverlapping(
+                    frame.p_data as *const u8,
+                    data.as_mut_ptr() as *mut u8,
+                    size,
+                );
+            }
+            let metadata = copy_metadata(frame.p_metadata);
+
+            let audio_frame = NDIlib_audio_frame_v3_t {
+                timecode: if frame.timecode == NDIlib_send_timecode_synthesize {
+                    timestamp
+                } else {
+                    frame.timecode
+                },
+                p_data: if data.is_empty() {
+                    ptr::null()
+                } else {
+                    data.as_ptr()
+                },
+                p_metadata: metadata.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null()),
+                timestamp,
+                ..*frame
+            };
+
+            receiver.push(QueuedFrame::Audio(audio_frame, data, metadata));
+        }
+    }
+
+    unsafe fn send_send_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let frame = &*p_metadata;
+
+        let data = match copy_metadata(frame.p_data) {
+            Some(data) => data,
+            None => return,
+        };
+        let timecode = if frame.timecode == NDIlib_send_timecode_synthesize {
+            now_100ns()
+        } else {
+            frame.timecode
+        };
+
+        for receiver in self.receivers_for(&send.info) {
+            receiver.push_metadata(data.clone(), timecode);
+        }
+    }
+
+    unsafe fn send_capture(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e {
+        let send = &*(p_instance as *const LoopbackSend);
+        let timeout = time::Duration::from_millis(timeout_in_ms as u64);
+
+        let queue = send.info.metadata_queue.lock().unwrap();
+        let (mut queue, _) = send
+            .info
+            .metadata_cond
+            .wait_timeout_while(queue, timeout, |queue| queue.is_empty())
+            .unwrap();
+
+        let frame = match queue.pop_front() {
+            None => return NDIlib_frame_type_e::NDIlib_frame_type_none,
+            Some(frame) => frame,
+        };
+        drop(queue);
+
+        if let QueuedFrame::Metadata(ref frame, ..) = frame {
+            *p_metadata = *frame;
+        }
+        send.in_flight.lock().unwrap().insert(frame.key(), frame);
+
+        NDIlib_frame_type_e::NDIlib_frame_type_metadata
+    }
+
+    unsafe fn send_free_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let key = (*p_metadata).p_data as usize;
+        send.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn send_get_tally(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_tally: *mut NDIlib_tally_t,
+        timeout_in_ms: u32,
+    ) -> bool {
+        let send = &*(p_instance as *const LoopbackSend);
+        let deadline = time::Instant::now() + time::Duration::from_millis(timeout_in_ms as u64);
+
+        // Tally changes are not signalled, so poll until the timeout
+        loop {
+            let tally = self.tally_for(&send.info);
+            let mut last_tally = send.tally.lock().unwrap();
+            let changed = tally.on_program != last_tally.on_program
+                || tally.on_preview != last_tally.on_preview;
+            *last_tally = tally;
+            drop(last_tally);
+
+            if changed || time::Instant::now() >= deadline {
+                if !p_tally.is_null() {
+                    *p_tally = tally;
+                }
+                return changed;
+            }
+
+            thread::sleep(time::Duration::from_millis(10));
+        }
+    }
+
+    unsafe fn send_get_no_connections(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        timeout_in_ms: u32,
+    ) -> i32 {
+        let send = &*(p_instance as *const LoopbackSend);
+        let deadline = time::Instant::now() + time::Duration::from_millis(timeout_in_ms as u64);
+
+        // Waits until there is at least one connection or the timeout is reached
+        loop {
+            let connections = self.receivers_for(&send.info).len() as i32;
+            if connections > 0 || time::Instant::now() >= deadline {
+                return connections;
+            }
+
+            thread::sleep(time::Duration::from_millis(10));
+        }
+    }
+
+    unsafe fn send_set_failover(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_failover_source: *const NDIlib_source_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let failover = if p_failover_source.is_null() {
+            None
+        } else {
+            Some((
+                opt_string((*p_failover_source).p_ndi_name),
+                opt_string((*p_failover_source).p_url_address),
+            ))
+        };
+
+        *send.info.failover.lock().unwrap() = failover;
+    }
+
+    unsafe fn send_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        let send = &*(p_instance as *const LoopbackSend);
+        let data = match copy_metadata((*p_metadata).p_data) {
+            Some(data) => data,
+            None => return,
+        };
+
+        // Receivers that are already connected get the metadata immediately
+        for receiver in self.receivers_for(&send.info) {
+            receiver.push_metadata(data.clone(), now_100ns());
+        }
+        send.info.connection_metadata.lock().unwrap().push(data);
+    }
+
+    unsafe fn send_clear_connection_metadata(&self, p_instance: NDIlib_send_instance_t) {
+        let send = &*(p_instance as *const LoopbackSend);
+        send.info.connection_metadata.lock().unwrap().clear();
+    }
+
+    unsafe fn framesync_create(
+        &self,
+        p_receiver: NDIlib_recv_instance_t,
+    ) -> NDIlib_framesync_instance_t {
+        if p_receiver.is_null() {
+            return ptr::null_mut();
+        }
+        let recv = &*(p_receiver as *const LoopbackRecv);
+
+        Box::into_raw(Box::new(LoopbackFramesync {
+            shared: recv.shared.clone(),
+            state: Mutex::new(FramesyncState::default()),
+            in_flight: Mutex::new(HashMap::new()),
+        })) as NDIlib_framesync_instance_t
+    }
+
+    unsafe fn framesync_destroy(&self, p_instance: NDIlib_framesync_instance_t) {
+        drop(Box::from_raw(p_instance as *mut LoopbackFramesync));
+    }
+
+    unsafe fn framesync_capture_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        _field_type: NDIlib_frame_format_type_e,
+    ) {
+        let framesync = &*(p_instance as *const LoopbackFramesync);
+        let mut state = framesync.state.lock().unwrap();
+        framesync.pull_frames(&mut state);
+
+        let (frame, data) = match state.video {
+            None => {
+                *p_video_data = mem::zeroed();
+                return;
+            }
+            Some((ref frame, ref data)) => (*frame, data.clone()),
+        };
+
+        let frame = NDIlib_video_frame_v2_t {
+            p_data: if data.is_empty() {
+                ptr::null()
+            } else {
+                data.as_ptr() as *const c_char
+            },
+            p_metadata: ptr::null(),
+            ..frame
+        };
+        *p_video_data = frame;
+
+        if !frame.p_data.is_null() {
+            framesync
+                .in_flight
+                .lock()
+                .unwrap()
+                .insert(frame.p_data as usize, QueuedFrame::Video(frame, data, None));
+        }
+    }
+
+    unsafe fn framesync_free_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    ) {
+        let framesync = &*(p_instance as *const LoopbackFramesync);
+        let key = (*p_video_data).p_data as usize;
+        framesync.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn framesync_capture_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        sample_rate: i32,
+        no_channels: i32,
+        no_samples: i32,
+    ) {
+        let framesync = &*(p_instance as *const LoopbackFramesync);
+        let mut state = framesync.state.lock().unwrap();
+        framesync.pull_frames(&mut state);
+
+        let mut frame: NDIlib_audio_frame_v3_t = mem::zeroed();
+        frame.FourCC = NDIlib_FourCC_audio_type_FLTp;
+        frame.timecode = now_100ns();
+        frame.timestamp = frame.timecode;
+
+        // Only querying the current format
+        if sample_rate == 0 && no_channels == 0 && no_samples == 0 {
+            frame.sample_rate = state.sample_rate;
+            frame.no_channels = state.audio.len() as i32;
+            *p_audio_data = frame;
+            return;
+        }
+
+        // No resampling or channel conversion is happening here: missing samples and channels
+        // are filled with silence
+        frame.sample_rate = match sample_rate {
+            0 if state.sample_rate > 0 => state.sample_rate,
+            0 => 48_000,
+            sample_rate => sample_rate,
+        };
+        frame.no_channels = match no_channels {
+            0 if !state.audio.is_empty() => state.audio.len() as i32,
+            0 => 2,
+            no_channels => no_channels,
+        };
+        frame.no_samples = no_samples.max(0);
+        frame.channel_stride_or_data_size_in_bytes =
+            frame.no_samples * mem::size_of::<f32>() as i32;
+
+        let no_samples = frame.no_samples as usize;
+        let mut data = vec![0.0f32; frame.no_channels as usize * no_samples];
+        let available = state
+            .audio
+            .first()
+            .map_or(0, |queue| queue.len().min(no_samples));
+        for (channel, queue) in state.audio.iter_mut().enumerate() {
+            for (i, sample) in queue.drain(..available).enumerate() {
+                if let Some(dest) = data.get_mut(channel * no_samples + i) {
+                    *dest = sample;
+                }
+            }
+        }
+
+        frame.p_data = if data.is_empty() {
+            ptr::null()
+        } else {
+            data.as_ptr()
+        };
+        *p_audio_data = frame;
+
+        if !frame.p_data.is_null() {
+            framesync
+                .in_flight
+                .lock()
+                .unwrap()
+                .insert(frame.p_data as usize, QueuedFrame::Audio(frame, data, None));
+        }
+    }
+
+    unsafe fn framesync_free_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    ) {
+        let framesync = &*(p_instance as *const LoopbackFramesync);
+        let key = (*p_audio_data).p_data as usize;
+        framesync.in_flight.lock().unwrap().remove(&key);
+    }
+
+    unsafe fn routing_create(
+        &self,
+        p_create_settings: *const NDIlib_routing_create_t,
+    ) -> NDIlib_routing_instance_t {
+        if p_create_settings.is_null() {
+            return ptr::null_mut();
+        }
+        let settings = &*p_create_settings;
+        let ndi_name = match opt_string(settings.p_ndi_name) {
+            None => return ptr::null_mut(),
+            Some(ndi_name) => ndi_name,
+        };
+
+        let mut registry = self.registry.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+
+        let info = Arc::new(SenderInfo {
+            ndi_name: format!("{} ({})", glib::host_name().to_uppercase(), ndi_name),
+            url_address: format!("loopback:{}", id),
+            groups: parse_groups(settings.p_groups),
+            metadata_queue: Mutex::new(VecDeque::new()),
+            metadata_cond: Condvar::new(),
+            is_routing: true,
+            route: Mutex::new(None),
+            failover: Mutex::new(None),
+            connection_metadata: Mutex::new(Vec::new()),
+        });
+
+        registry.senders.push(info.clone());
+        registry.generation += 1;
+        self.sources_changed.notify_all();
+
+        Box::into_raw(Box::new(LoopbackRouting { info })) as NDIlib_routing_instance_t
+    }
+
+    unsafe fn routing_destroy(&self, p_instance: NDIlib_routing_instance_t) {
+        let routing = Box::from_raw(p_instance as *mut LoopbackRouting);
+
+        let mut registry = self.registry.lock().unwrap();
+        registry.senders.retain(|s| !Arc::ptr_eq(s, &routing.info));
+        registry.generation += 1;
+        self.sources_changed.notify_all();
+    }
+
+    unsafe fn routing_change(
+        &self,
+        p_instance: NDIlib_routing_instance_t,
+        p_source: *const NDIlib_source_t,
+    ) -> bool {
+        let routing = &*(p_instance as *const LoopbackRouting);
+        if p_source.is_null() {
+            return false;
+        }
+        let source = &*p_source;
+
+        *routing.info.route.lock().unwrap() = Some((
+            opt_string(source.p_ndi_name),
+            opt_string(source.p_url_address),
+        ));
+
+        for receiver in self.receivers_for(&routing.info) {
+            receiver.notify_status_change();
+        }
+        true
+    }
+
+    unsafe fn routing_clear(&self, p_instance: NDIlib_routing_instance_t) -> bool {
+        let routing = &*(p_instance as *const LoopbackRouting);
+        *routing.info.route.lock().unwrap() = None;
+
+        for receiver in self.receivers_for(&routing.info) {
+            receiver.notify_status_change();
+        }
+        true
+    }
+}
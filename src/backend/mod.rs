@@ -0,0 +1,718 @@
+//! Backends implementing the NDI API used by the safe wrappers in `ndi`.
+//!
+//! The methods mirror the `NDIlib_*` C functions one to one, working on the raw structs and
+//! opaque instance handles from `ndisys`. The `Sdk` backend calls into the NDI SDK, while the
+//! `Loopback` backend implements senders, receivers and discovery purely in-process, which
+//! allows exercising the elements without the SDK or a network.
+//!
+//! The backend is selected once per process: either explicitly via `set()` before any NDI
+//! object is created, or via the `GST_NDI_BACKEND` environment variable (`sdk` or `loopback`).
+
+use crate::ndisys;
+use crate::ndisys::*;
+
+use once_cell::sync::OnceCell;
+
+use std::env;
+use std::os::raw::c_char;
+
+pub mod loopback;
+
+pub use loopback::Loopback;
+
+#[allow(clippy::missing_safety_doc)]
+pub trait Backend: Send + Sync {
+    fn initialize(&self) -> Result<(), glib::BoolError>;
+
+    unsafe fn find_create_v2(
+        &self,
+        p_create_settings: *const NDIlib_find_create_t,
+    ) -> NDIlib_find_instance_t;
+    unsafe fn find_destroy(&self, p_instance: NDIlib_find_instance_t);
+    unsafe fn find_wait_for_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        timeout_in_ms: u32,
+    ) -> bool;
+    unsafe fn find_get_current_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        p_no_sources: *mut u32,
+    ) -> *const NDIlib_source_t;
+
+    unsafe fn recv_create_v3(
+        &self,
+        p_create_settings: *const NDIlib_recv_create_v3_t,
+    ) -> NDIlib_recv_instance_t;
+    unsafe fn recv_destroy(&self, p_instance: NDIlib_recv_instance_t);
+    unsafe fn recv_connect(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_src: *const NDIlib_source_t,
+    );
+    unsafe fn recv_set_tally(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_tally: *const NDIlib_tally_t,
+    ) -> bool;
+    unsafe fn recv_send_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) -> bool;
+    unsafe fn recv_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    );
+    unsafe fn recv_capture_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e;
+    unsafe fn recv_free_video_v2(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    );
+    unsafe fn recv_free_audio_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    );
+    unsafe fn recv_free_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+    );
+    unsafe fn recv_get_queue(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_queue_t,
+    );
+    unsafe fn recv_get_no_connections(&self, p_instance: NDIlib_recv_instance_t) -> i32;
+    unsafe fn recv_get_web_control(&self, p_instance: NDIlib_recv_instance_t) -> *const c_char;
+    unsafe fn recv_free_string(&self, p_instance: NDIlib_recv_instance_t, p_string: *const c_char);
+    unsafe fn recv_get_performance(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_performance_t,
+        p_dropped: *mut NDIlib_recv_performance_t,
+    );
+    unsafe fn recv_ptz_is_supported(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_store_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+    ) -> bool;
+    unsafe fn recv_ptz_recall_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+        speed: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_zoom(&self, p_instance: NDIlib_recv_instance_t, zoom_value: f32) -> bool;
+    unsafe fn recv_ptz_zoom_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        zoom_speed: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_pan_tilt(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_value: f32,
+        tilt_value: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_pan_tilt_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_speed: f32,
+        tilt_speed: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_auto_focus(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_focus(&self, p_instance: NDIlib_recv_instance_t, focus_value: f32) -> bool;
+    unsafe fn recv_ptz_focus_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        focus_speed: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_white_balance_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_white_balance_indoor(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_white_balance_outdoor(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_white_balance_oneshot(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_white_balance_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        red: f32,
+        blue: f32,
+    ) -> bool;
+    unsafe fn recv_ptz_exposure_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool;
+    unsafe fn recv_ptz_exposure_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        exposure_level: f32,
+    ) -> bool;
+
+    unsafe fn send_create(
+        &self,
+        p_create_settings: *const NDIlib_send_create_t,
+    ) -> NDIlib_send_instance_t;
+    unsafe fn send_destroy(&self, p_instance: NDIlib_send_instance_t);
+    unsafe fn send_send_video_v2(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_video_data: *const NDIlib_video_frame_v2_t,
+    );
+    unsafe fn send_send_audio_v3(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_audio_data: *const NDIlib_audio_frame_v3_t,
+    );
+    unsafe fn send_send_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    );
+    unsafe fn send_capture(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e;
+    unsafe fn send_free_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    );
+    unsafe fn send_get_tally(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_tally: *mut NDIlib_tally_t,
+        timeout_in_ms: u32,
+    ) -> bool;
+    unsafe fn send_get_no_connections(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        timeout_in_ms: u32,
+    ) -> i32;
+    unsafe fn send_set_failover(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_failover_source: *const NDIlib_source_t,
+    );
+    unsafe fn send_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    );
+    unsafe fn send_clear_connection_metadata(&self, p_instance: NDIlib_send_instance_t);
+
+    unsafe fn routing_create(
+        &self,
+        p_create_settings: *const NDIlib_routing_create_t,
+    ) -> NDIlib_routing_instance_t;
+    unsafe fn routing_destroy(&self, p_instance: NDIlib_routing_instance_t);
+    unsafe fn routing_change(
+        &self,
+        p_instance: NDIlib_routing_instance_t,
+        p_source: *const NDIlib_source_t,
+    ) -> bool;
+    unsafe fn routing_clear(&self, p_instance: NDIlib_routing_instance_t) -> bool;
+
+    unsafe fn framesync_create(
+        &self,
+        p_receiver: NDIlib_recv_instance_t,
+    ) -> NDIlib_framesync_instance_t;
+    unsafe fn framesync_destroy(&self, p_instance: NDIlib_framesync_instance_t);
+    unsafe fn framesync_capture_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        field_type: NDIlib_frame_format_type_e,
+    );
+    unsafe fn framesync_free_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    );
+    unsafe fn framesync_capture_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        sample_rate: i32,
+        no_channels: i32,
+        no_samples: i32,
+    );
+    unsafe fn framesync_free_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    );
+}
+
+/// Backend calling into the NDI SDK, which is loaded at runtime by `initialize()`.
+#[derive(Debug, Default)]
+pub struct Sdk;
+
+impl Backend for Sdk {
+    fn initialize(&self) -> Result<(), glib::BoolError> {
+        static INITIALIZED: OnceCell<Result<(), glib::BoolError>> = OnceCell::new();
+
+        INITIALIZED
+            .get_or_init(|| {
+                let lib = ndisys::load()?;
+                if unsafe { (lib.NDIlib_initialize)() } {
+                    Ok(())
+                } else {
+                    Err(glib::bool_error!("Cannot initialize NDI"))
+                }
+            })
+            .clone()
+    }
+
+    unsafe fn find_create_v2(
+        &self,
+        p_create_settings: *const NDIlib_find_create_t,
+    ) -> NDIlib_find_instance_t {
+        (lib().NDIlib_find_create_v2)(p_create_settings)
+    }
+
+    unsafe fn find_destroy(&self, p_instance: NDIlib_find_instance_t) {
+        (lib().NDIlib_find_destroy)(p_instance)
+    }
+
+    unsafe fn find_wait_for_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        timeout_in_ms: u32,
+    ) -> bool {
+        (lib().NDIlib_find_wait_for_sources)(p_instance, timeout_in_ms)
+    }
+
+    unsafe fn find_get_current_sources(
+        &self,
+        p_instance: NDIlib_find_instance_t,
+        p_no_sources: *mut u32,
+    ) -> *const NDIlib_source_t {
+        (lib().NDIlib_find_get_current_sources)(p_instance, p_no_sources)
+    }
+
+    unsafe fn recv_create_v3(
+        &self,
+        p_create_settings: *const NDIlib_recv_create_v3_t,
+    ) -> NDIlib_recv_instance_t {
+        (lib().NDIlib_recv_create_v3)(p_create_settings)
+    }
+
+    unsafe fn recv_destroy(&self, p_instance: NDIlib_recv_instance_t) {
+        (lib().NDIlib_recv_destroy)(p_instance)
+    }
+
+    unsafe fn recv_connect(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_src: *const NDIlib_source_t,
+    ) {
+        (lib().NDIlib_recv_connect)(p_instance, p_src)
+    }
+
+    unsafe fn recv_set_tally(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_tally: *const NDIlib_tally_t,
+    ) -> bool {
+        (lib().NDIlib_recv_set_tally)(p_instance, p_tally)
+    }
+
+    unsafe fn recv_send_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) -> bool {
+        (lib().NDIlib_recv_send_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn recv_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        (lib().NDIlib_recv_add_connection_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn recv_capture_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e {
+        (lib().NDIlib_recv_capture_v3)(
+            p_instance,
+            p_video_data,
+            p_audio_data,
+            p_metadata,
+            timeout_in_ms,
+        )
+    }
+
+    unsafe fn recv_free_video_v2(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    ) {
+        (lib().NDIlib_recv_free_video_v2)(p_instance, p_video_data)
+    }
+
+    unsafe fn recv_free_audio_v3(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    ) {
+        (lib().NDIlib_recv_free_audio_v3)(p_instance, p_audio_data)
+    }
+
+    unsafe fn recv_free_metadata(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+    ) {
+        (lib().NDIlib_recv_free_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn recv_get_queue(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_queue_t,
+    ) {
+        (lib().NDIlib_recv_get_queue)(p_instance, p_total)
+    }
+
+    unsafe fn recv_get_no_connections(&self, p_instance: NDIlib_recv_instance_t) -> i32 {
+        (lib().NDIlib_recv_get_no_connections)(p_instance)
+    }
+
+    unsafe fn recv_get_web_control(&self, p_instance: NDIlib_recv_instance_t) -> *const c_char {
+        (lib().NDIlib_recv_get_web_control)(p_instance)
+    }
+
+    unsafe fn recv_free_string(&self, p_instance: NDIlib_recv_instance_t, p_string: *const c_char) {
+        (lib().NDIlib_recv_free_string)(p_instance, p_string)
+    }
+
+    unsafe fn recv_get_performance(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_performance_t,
+        p_dropped: *mut NDIlib_recv_performance_t,
+    ) {
+        (lib().NDIlib_recv_get_performance)(p_instance, p_total, p_dropped)
+    }
+
+    unsafe fn recv_ptz_is_supported(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_is_supported)(p_instance)
+    }
+
+    unsafe fn recv_ptz_store_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_store_preset)(p_instance, preset_no)
+    }
+
+    unsafe fn recv_ptz_recall_preset(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        preset_no: i32,
+        speed: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_recall_preset)(p_instance, preset_no, speed)
+    }
+
+    unsafe fn recv_ptz_zoom(&self, p_instance: NDIlib_recv_instance_t, zoom_value: f32) -> bool {
+        (lib().NDIlib_recv_ptz_zoom)(p_instance, zoom_value)
+    }
+
+    unsafe fn recv_ptz_zoom_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        zoom_speed: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_zoom_speed)(p_instance, zoom_speed)
+    }
+
+    unsafe fn recv_ptz_pan_tilt(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_value: f32,
+        tilt_value: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_pan_tilt)(p_instance, pan_value, tilt_value)
+    }
+
+    unsafe fn recv_ptz_pan_tilt_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        pan_speed: f32,
+        tilt_speed: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_pan_tilt_speed)(p_instance, pan_speed, tilt_speed)
+    }
+
+    unsafe fn recv_ptz_auto_focus(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_auto_focus)(p_instance)
+    }
+
+    unsafe fn recv_ptz_focus(&self, p_instance: NDIlib_recv_instance_t, focus_value: f32) -> bool {
+        (lib().NDIlib_recv_ptz_focus)(p_instance, focus_value)
+    }
+
+    unsafe fn recv_ptz_focus_speed(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        focus_speed: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_focus_speed)(p_instance, focus_speed)
+    }
+
+    unsafe fn recv_ptz_white_balance_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_white_balance_auto)(p_instance)
+    }
+
+    unsafe fn recv_ptz_white_balance_indoor(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_white_balance_indoor)(p_instance)
+    }
+
+    unsafe fn recv_ptz_white_balance_outdoor(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_white_balance_outdoor)(p_instance)
+    }
+
+    unsafe fn recv_ptz_white_balance_oneshot(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_white_balance_oneshot)(p_instance)
+    }
+
+    unsafe fn recv_ptz_white_balance_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        red: f32,
+        blue: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_white_balance_manual)(p_instance, red, blue)
+    }
+
+    unsafe fn recv_ptz_exposure_auto(&self, p_instance: NDIlib_recv_instance_t) -> bool {
+        (lib().NDIlib_recv_ptz_exposure_auto)(p_instance)
+    }
+
+    unsafe fn recv_ptz_exposure_manual(
+        &self,
+        p_instance: NDIlib_recv_instance_t,
+        exposure_level: f32,
+    ) -> bool {
+        (lib().NDIlib_recv_ptz_exposure_manual)(p_instance, exposure_level)
+    }
+
+    unsafe fn send_create(
+        &self,
+        p_create_settings: *const NDIlib_send_create_t,
+    ) -> NDIlib_send_instance_t {
+        (lib().NDIlib_send_create)(p_create_settings)
+    }
+
+    unsafe fn send_destroy(&self, p_instance: NDIlib_send_instance_t) {
+        (lib().NDIlib_send_destroy)(p_instance)
+    }
+
+    unsafe fn send_send_video_v2(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_video_data: *const NDIlib_video_frame_v2_t,
+    ) {
+        (lib().NDIlib_send_send_video_v2)(p_instance, p_video_data)
+    }
+
+    unsafe fn send_send_audio_v3(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_audio_data: *const NDIlib_audio_frame_v3_t,
+    ) {
+        (lib().NDIlib_send_send_audio_v3)(p_instance, p_audio_data)
+    }
+
+    unsafe fn send_send_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        (lib().NDIlib_send_send_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn send_capture(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e {
+        (lib().NDIlib_send_capture)(p_instance, p_metadata, timeout_in_ms)
+    }
+
+    unsafe fn send_free_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        (lib().NDIlib_send_free_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn send_get_tally(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_tally: *mut NDIlib_tally_t,
+        timeout_in_ms: u32,
+    ) -> bool {
+        (lib().NDIlib_send_get_tally)(p_instance, p_tally, timeout_in_ms)
+    }
+
+    unsafe fn send_get_no_connections(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        timeout_in_ms: u32,
+    ) -> i32 {
+        (lib().NDIlib_send_get_no_connections)(p_instance, timeout_in_ms)
+    }
+
+    unsafe fn send_set_failover(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_failover_source: *const NDIlib_source_t,
+    ) {
+        (lib().NDIlib_send_set_failover)(p_instance, p_failover_source)
+    }
+
+    unsafe fn send_add_connection_metadata(
+        &self,
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ) {
+        (lib().NDIlib_send_add_connection_metadata)(p_instance, p_metadata)
+    }
+
+    unsafe fn send_clear_connection_metadata(&self, p_instance: NDIlib_send_instance_t) {
+        (lib().NDIlib_send_clear_connection_metadata)(p_instance)
+    }
+
+    unsafe fn routing_create(
+        &self,
+        p_create_settings: *const NDIlib_routing_create_t,
+    ) -> NDIlib_routing_instance_t {
+        (lib().NDIlib_routing_create)(p_create_settings)
+    }
+
+    unsafe fn routing_destroy(&self, p_instance: NDIlib_routing_instance_t) {
+        (lib().NDIlib_routing_destroy)(p_instance)
+    }
+
+    unsafe fn routing_change(
+        &self,
+        p_instance: NDIlib_routing_instance_t,
+        p_source: *const NDIlib_source_t,
+    ) -> bool {
+        (lib().NDIlib_routing_change)(p_instance, p_source)
+    }
+
+    unsafe fn routing_clear(&self, p_instance: NDIlib_routing_instance_t) -> bool {
+        (lib().NDIlib_routing_clear)(p_instance)
+    }
+
+    unsafe fn framesync_create(
+        &self,
+        p_receiver: NDIlib_recv_instance_t,
+    ) -> NDIlib_framesync_instance_t {
+        let lib = lib();
+
+        // All other frame synchronizer functions are only called on a successfully created
+        // instance, so it's enough to check for all of them here
+        match (
+            lib.NDIlib_framesync_create,
+            lib.NDIlib_framesync_destroy,
+            lib.NDIlib_framesync_capture_video,
+            lib.NDIlib_framesync_free_video,
+            lib.NDIlib_framesync_capture_audio_v2,
+            lib.NDIlib_framesync_free_audio_v2,
+        ) {
+            (Some(create), Some(_), Some(_), Some(_), Some(_), Some(_)) => create(p_receiver),
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    unsafe fn framesync_destroy(&self, p_instance: NDIlib_framesync_instance_t) {
+        (lib().NDIlib_framesync_destroy.unwrap())(p_instance)
+    }
+
+    unsafe fn framesync_capture_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+        field_type: NDIlib_frame_format_type_e,
+    ) {
+        (lib().NDIlib_framesync_capture_video.unwrap())(p_instance, p_video_data, field_type)
+    }
+
+    unsafe fn framesync_free_video(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_video_data: *mut NDIlib_video_frame_v2_t,
+    ) {
+        (lib().NDIlib_framesync_free_video.unwrap())(p_instance, p_video_data)
+    }
+
+    unsafe fn framesync_capture_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        sample_rate: i32,
+        no_channels: i32,
+        no_samples: i32,
+    ) {
+        (lib().NDIlib_framesync_capture_audio_v2.unwrap())(
+            p_instance,
+            p_audio_data,
+            sample_rate,
+            no_channels,
+            no_samples,
+        )
+    }
+
+    unsafe fn framesync_free_audio_v2(
+        &self,
+        p_instance: NDIlib_framesync_instance_t,
+        p_audio_data: *mut NDIlib_audio_frame_v3_t,
+    ) {
+        (lib().NDIlib_framesync_free_audio_v2.unwrap())(p_instance, p_audio_data)
+    }
+}
+
+static BACKEND: OnceCell<Box<dyn Backend>> = OnceCell::new();
+
+/// Selects the backend for this process.
+///
+/// Must be called before any NDI object is created, otherwise the backend selected via the
+/// environment is already in use and the passed backend is returned again.
+pub fn set(backend: Box<dyn Backend>) -> Result<(), Box<dyn Backend>> {
+    BACKEND.set(backend)
+}
+
+/// Returns the backend for this process, selecting it from the environment if needed.
+pub fn get() -> &'static dyn Backend {
+    BACKEND
+        .get_or_init(|| match env::var("GST_NDI_BACKEND").as_deref() {
+            Ok("loopback") => Box::new(Loopback::default()),
+            _ => Box::new(Sdk),
+        })
+        .as_ref()
+}
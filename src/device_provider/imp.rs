@@ -4,6 +4,7 @@ use gst::{gst_error, gst_log, gst_trace};
 
 use once_cell::sync::OnceCell;
 
+use std::env;
 use std::sync::atomic;
 use std::sync::Mutex;
 use std::thread;
@@ -20,8 +21,30 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
     )
 });
 
+// Defaults can be overridden from the environment as gst-device-monitor and similar tools have
+// no way of setting device provider properties
+#[derive(Debug, Clone)]
+struct Settings {
+    groups: Option<String>,
+    extra_ips: Option<String>,
+    show_local_sources: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            groups: env::var("GST_NDI_GROUPS").ok(),
+            extra_ips: env::var("GST_NDI_EXTRA_IPS").ok(),
+            show_local_sources: env::var("GST_NDI_SHOW_LOCAL_SOURCES")
+                .map(|v| !matches!(v.as_str(), "0" | "false" | "no"))
+                .unwrap_or(true),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DeviceProvider {
+    settings: Mutex<Settings>,
     thread: Mutex<Option<thread::JoinHandle<()>>>,
     current_devices: Mutex<Vec<super::Device>>,
     find: Mutex<Option<ndi::FindInstance>>,
@@ -36,6 +59,7 @@ impl ObjectSubclass for DeviceProvider {
 
     fn new() -> Self {
         Self {
+            settings: Mutex::new(Default::default()),
             thread: Mutex::new(None),
             current_devices: Mutex::new(vec![]),
             find: Mutex::new(None),
@@ -44,7 +68,69 @@ impl ObjectSubclass for DeviceProvider {
     }
 }
 
-impl ObjectImpl for DeviceProvider {}
+impl ObjectImpl for DeviceProvider {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpec::new_string(
+                    "groups",
+                    "Groups",
+                    "Comma separated list of NDI groups to discover sources in (default: $GST_NDI_GROUPS or public)",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "extra-ips",
+                    "Extra IPs",
+                    "Comma separated list of additional IP addresses to query for sources (default: $GST_NDI_EXTRA_IPS)",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "show-local-sources",
+                    "Show Local Sources",
+                    "Whether to discover sources running on this machine (default: $GST_NDI_SHOW_LOCAL_SOURCES or true)",
+                    true,
+                    glib::ParamFlags::READWRITE,
+                ),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(
+        &self,
+        _obj: &Self::Type,
+        _id: usize,
+        value: &glib::Value,
+        pspec: &glib::ParamSpec,
+    ) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "groups" => {
+                settings.groups = value.get().unwrap();
+            }
+            "extra-ips" => {
+                settings.extra_ips = value.get().unwrap();
+            }
+            "show-local-sources" => {
+                settings.show_local_sources = value.get().unwrap();
+            }
+            _ => unimplemented!(),
+        };
+    }
+
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "groups" => settings.groups.to_value(),
+            "extra-ips" => settings.extra_ips.to_value(),
+            "show-local-sources" => settings.show_local_sources.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
 
 impl DeviceProviderImpl for DeviceProvider {
     fn metadata() -> Option<&'static gst::subclass::DeviceProviderMetadata> {
@@ -74,6 +160,9 @@ impl DeviceProviderImpl for DeviceProvider {
             return Ok(());
         }
 
+        ndi::initialize()
+            .map_err(|err| gst::loggable_error!(CAT, "Failed to initialize NDI SDK: {}", err))?;
+
         self.is_running.store(true, atomic::Ordering::SeqCst);
 
         let device_provider_weak = device_provider.downgrade();
@@ -92,7 +181,18 @@ impl DeviceProviderImpl for DeviceProvider {
                     return;
                 }
 
-                let find = match ndi::FindInstance::builder().build() {
+                // The settings are only read when the Find instance is created on the first start
+                let settings = imp.settings.lock().unwrap().clone();
+                let mut builder =
+                    ndi::FindInstance::builder().show_local_sources(settings.show_local_sources);
+                if let Some(ref groups) = settings.groups {
+                    builder = builder.groups(groups);
+                }
+                if let Some(ref extra_ips) = settings.extra_ips {
+                    builder = builder.extra_ips(extra_ips);
+                }
+
+                let find = match builder.build() {
                     None => {
                         gst_error!(CAT, obj: &device_provider, "Failed to create Find instance");
                         return;
@@ -1,5 +1,7 @@
+pub mod backend;
 mod device_provider;
 pub mod ndi;
+mod ndirouter;
 #[cfg(feature = "sink")]
 mod ndisink;
 #[cfg(feature = "sink")]
@@ -36,6 +38,43 @@ pub enum TimestampMode {
     ReceiveTime = 4,
 }
 
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::GEnum)]
+#[repr(u32)]
+#[genum(type_name = "GstNdiReceiveMode")]
+pub enum ReceiveMode {
+    #[genum(name = "Capture frames as they arrive", nick = "capture")]
+    Capture = 0,
+    #[genum(name = "Frame synchronizer", nick = "framesync")]
+    Framesync = 1,
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::GEnum)]
+#[repr(u32)]
+#[genum(type_name = "GstNdiNameMatch")]
+pub enum NdiNameMatch {
+    #[genum(name = "Exact NDI name", nick = "exact")]
+    Exact = 0,
+    #[genum(name = "Shell-style wildcard pattern", nick = "glob")]
+    Glob = 1,
+    #[genum(
+        name = "Perl-compatible regular expression matching the whole NDI name",
+        nick = "regex"
+    )]
+    Regex = 2,
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::GEnum)]
+#[repr(u32)]
+#[genum(type_name = "GstNdiTimeoutAction")]
+pub enum TimeoutAction {
+    #[genum(name = "End of stream", nick = "eos")]
+    Eos = 0,
+    #[genum(name = "Reconnect to the source", nick = "reconnect")]
+    Reconnect = 1,
+    #[genum(name = "Post an error", nick = "error")]
+    Error = 2,
+}
+
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::GEnum)]
 #[repr(u32)]
 #[genum(type_name = "GstNdiRecvColorFormat")]
@@ -114,23 +153,49 @@ impl From<RecvColorFormat> for NDIlib_recv_color_format_e {
 }
 
 fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
-    if !ndi::initialize() {
-        return Err(glib::bool_error!("Cannot initialize NDI"));
-    }
-
+    // The NDI SDK is only loaded once an element is started so that the elements are available
+    // for inspection even if the SDK is not installed
     device_provider::register(plugin)?;
 
     ndisrc::register(plugin)?;
     ndisrcdemux::register(plugin)?;
+    ndirouter::register(plugin)?;
 
     #[cfg(feature = "sink")]
     {
+        ndisinkmeta::register();
         ndisinkcombiner::register(plugin)?;
         ndisink::register(plugin)?;
     }
     Ok(())
 }
 
+// Metadata properties take either XML or a GstStructure that is converted to an XML element
+// with the fields as attributes, e.g. `ndi_product,long_name=Product` to
+// `<ndi_product long_name="Product"/>`
+fn metadata_to_xml(metadata: &str) -> Option<String> {
+    use gst::prelude::*;
+    use std::fmt::Write;
+    use std::str::FromStr;
+
+    if metadata.trim_start().starts_with('<') {
+        return Some(String::from(metadata));
+    }
+
+    let s = gst::Structure::from_str(metadata).ok()?;
+    let mut xml = format!("<{}", s.name());
+    for (name, value) in s.iter() {
+        let value = match value.get::<String>() {
+            Ok(value) => value,
+            Err(_) => value.serialize().ok()?.to_string(),
+        };
+        let _ = write!(xml, " {}=\"{}\"", name, glib::markup_escape_text(&value));
+    }
+    xml.push_str("/>");
+
+    Some(xml)
+}
+
 static DEFAULT_RECEIVER_NDI_NAME: Lazy<String> = Lazy::new(|| {
     format!(
         "GStreamer NDI Source {}-{}",
@@ -139,6 +204,9 @@ static DEFAULT_RECEIVER_NDI_NAME: Lazy<String> = Lazy::new(|| {
     )
 });
 
+static METADATA_CAPS: Lazy<gst::Caps> =
+    Lazy::new(|| gst::Caps::new_simple("application/x-ndi-metadata", &[]));
+
 #[cfg(feature = "reference-timestamps")]
 static TIMECODE_CAPS: Lazy<gst::Caps> =
     Lazy::new(|| gst::Caps::new_simple("timestamp/x-ndi-timecode", &[]));
@@ -1,13 +1,17 @@
+use crate::backend::Backend;
 use crate::ndisys;
 use crate::ndisys::*;
 use std::ffi;
 use std::mem;
 use std::ptr;
+use std::sync::Arc;
 
-use byte_slice_cast::*;
+fn backend() -> &'static dyn Backend {
+    crate::backend::get()
+}
 
-pub fn initialize() -> bool {
-    unsafe { NDIlib_initialize() }
+pub fn initialize() -> Result<(), glib::BoolError> {
+    backend().initialize()
 }
 
 #[derive(Debug)]
@@ -54,7 +58,7 @@ impl<'a> FindBuilder<'a> {
         let extra_ips = self.extra_ips.map(|s| ffi::CString::new(s).unwrap());
 
         unsafe {
-            let ptr = NDIlib_find_create_v2(&NDIlib_find_create_t {
+            let ptr = backend().find_create_v2(&NDIlib_find_create_t {
                 show_local_sources: self.show_local_sources,
                 p_groups: groups.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null()),
                 p_extra_ips: extra_ips
@@ -81,14 +85,14 @@ impl FindInstance {
     }
 
     pub fn wait_for_sources(&mut self, timeout_in_ms: u32) -> bool {
-        unsafe { NDIlib_find_wait_for_sources(self.0.as_ptr(), timeout_in_ms) }
+        unsafe { backend().find_wait_for_sources(self.0.as_ptr(), timeout_in_ms) }
     }
 
     pub fn get_current_sources(&mut self) -> Vec<Source> {
         unsafe {
             let mut no_sources = mem::MaybeUninit::uninit();
             let sources_ptr =
-                NDIlib_find_get_current_sources(self.0.as_ptr(), no_sources.as_mut_ptr());
+                backend().find_get_current_sources(self.0.as_ptr(), no_sources.as_mut_ptr());
             let no_sources = no_sources.assume_init();
 
             if sources_ptr.is_null() || no_sources == 0 {
@@ -111,7 +115,7 @@ impl FindInstance {
 impl Drop for FindInstance {
     fn drop(&mut self) {
         unsafe {
-            NDIlib_find_destroy(self.0.as_mut());
+            backend().find_destroy(self.0.as_mut());
         }
     }
 }
@@ -119,13 +123,34 @@ impl Drop for FindInstance {
 #[derive(Debug)]
 pub enum Source<'a> {
     Borrowed(ptr::NonNull<NDIlib_source_t>, &'a FindInstance),
-    Owned(NDIlib_source_t, ffi::CString, ffi::CString),
+    Owned(NDIlib_source_t, Option<ffi::CString>, Option<ffi::CString>),
 }
 
 unsafe impl<'a> Send for Source<'a> {}
 unsafe impl<'a> Sync for Source<'a> {}
 
 impl<'a> Source<'a> {
+    // Either of the two can be `None`, the URL/address takes precedence in the SDK
+    pub fn new(ndi_name: Option<&str>, url_address: Option<&str>) -> Source<'static> {
+        let ndi_name = ndi_name.map(|s| ffi::CString::new(s).unwrap());
+        let url_address = url_address.map(|s| ffi::CString::new(s).unwrap());
+
+        Source::Owned(
+            NDIlib_source_t {
+                p_ndi_name: ndi_name
+                    .as_ref()
+                    .map(|s| s.as_ptr())
+                    .unwrap_or_else(ptr::null),
+                p_url_address: url_address
+                    .as_ref()
+                    .map(|s| s.as_ptr())
+                    .unwrap_or_else(ptr::null),
+            },
+            ndi_name,
+            url_address,
+        )
+    }
+
     pub fn ndi_name(&self) -> &str {
         unsafe {
             let ptr = match *self {
@@ -133,7 +158,9 @@ impl<'a> Source<'a> {
                 Source::Owned(ref source, _, _) => source,
             };
 
-            assert!(!ptr.p_ndi_name.is_null());
+            if ptr.p_ndi_name.is_null() {
+                return "";
+            }
             ffi::CStr::from_ptr(ptr.p_ndi_name).to_str().unwrap()
         }
     }
@@ -145,32 +172,39 @@ impl<'a> Source<'a> {
                 Source::Owned(ref source, _, _) => source,
             };
 
-            assert!(!ptr.p_url_address.is_null());
+            if ptr.p_url_address.is_null() {
+                return "";
+            }
             ffi::CStr::from_ptr(ptr.p_url_address).to_str().unwrap()
         }
     }
 
     pub fn to_owned<'b>(&self) -> Source<'b> {
         unsafe {
-            let (ndi_name, url_address) = match *self {
-                Source::Borrowed(ptr, _) => (ptr.as_ref().p_ndi_name, ptr.as_ref().p_url_address),
-                Source::Owned(_, ref ndi_name, ref url_address) => {
-                    (ndi_name.as_ptr(), url_address.as_ptr())
-                }
+            let ptr = match *self {
+                Source::Borrowed(ptr, _) => &*ptr.as_ptr(),
+                Source::Owned(ref source, _, _) => source,
             };
 
-            let ndi_name = ffi::CString::new(ffi::CStr::from_ptr(ndi_name).to_bytes()).unwrap();
-            let url_address =
-                ffi::CString::new(ffi::CStr::from_ptr(url_address).to_bytes()).unwrap();
+            let ndi_name = if ptr.p_ndi_name.is_null() {
+                None
+            } else {
+                Some(ffi::CStr::from_ptr(ptr.p_ndi_name).to_str().unwrap())
+            };
+            let url_address = if ptr.p_url_address.is_null() {
+                None
+            } else {
+                Some(ffi::CStr::from_ptr(ptr.p_url_address).to_str().unwrap())
+            };
 
-            Source::Owned(
-                NDIlib_source_t {
-                    p_ndi_name: ndi_name.as_ptr(),
-                    p_url_address: url_address.as_ptr(),
-                },
-                ndi_name,
-                url_address,
-            )
+            Source::new(ndi_name, url_address)
+        }
+    }
+
+    fn as_ptr(&self) -> *const NDIlib_source_t {
+        match *self {
+            Source::Borrowed(ptr, _) => ptr.as_ptr(),
+            Source::Owned(ref source, _, _) => source,
         }
     }
 }
@@ -221,7 +255,7 @@ impl<'a> RecvBuilder<'a> {
                 .url_address
                 .as_ref()
                 .map(|s| ffi::CString::new(*s).unwrap());
-            let ptr = NDIlib_recv_create_v3(&NDIlib_recv_create_v3_t {
+            let ptr = backend().recv_create_v3(&NDIlib_recv_create_v3_t {
                 source_to_connect_to: NDIlib_source_t {
                     p_ndi_name: ndi_name
                         .as_ref()
@@ -241,16 +275,29 @@ impl<'a> RecvBuilder<'a> {
             if ptr.is_null() {
                 None
             } else {
-                Some(RecvInstance(ptr::NonNull::new_unchecked(ptr)))
+                Some(RecvInstance(Arc::new(RecvInstanceInner(
+                    ptr::NonNull::new_unchecked(ptr),
+                ))))
             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RecvInstance(ptr::NonNull<::std::os::raw::c_void>);
+#[derive(Debug)]
+struct RecvInstanceInner(ptr::NonNull<::std::os::raw::c_void>);
 
-unsafe impl Send for RecvInstance {}
+unsafe impl Send for RecvInstanceInner {}
+// The SDK allows controlling a receive instance while capturing from another thread
+unsafe impl Sync for RecvInstanceInner {}
+
+impl Drop for RecvInstanceInner {
+    fn drop(&mut self) {
+        unsafe { backend().recv_destroy(self.0.as_ptr() as *mut _) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecvInstance(Arc<RecvInstanceInner>);
 
 impl RecvInstance {
     pub fn builder<'a>(
@@ -268,31 +315,162 @@ impl RecvInstance {
         }
     }
 
+    fn as_ptr(&self) -> *mut ::std::os::raw::c_void {
+        (self.0).0.as_ptr()
+    }
+
+    /// Switches to a different source, or disconnects if `None`.
+    pub fn connect(&self, source: Option<&Source>) {
+        unsafe {
+            backend().recv_connect(
+                self.as_ptr(),
+                source.map(|s| s.as_ptr()).unwrap_or_else(ptr::null),
+            )
+        }
+    }
+
     pub fn set_tally(&self, tally: &Tally) -> bool {
-        unsafe { NDIlib_recv_set_tally(self.0.as_ptr(), &tally.0) }
+        unsafe { backend().recv_set_tally(self.as_ptr(), &tally.0) }
     }
 
     pub fn send_metadata(&self, metadata: &MetadataFrame) -> bool {
-        unsafe { NDIlib_recv_send_metadata(self.0.as_ptr(), metadata.as_ptr()) }
+        unsafe { backend().recv_send_metadata(self.as_ptr(), metadata.as_ptr()) }
+    }
+
+    /// Adds metadata that is sent to the source every time the receiver connects to it.
+    pub fn add_connection_metadata(&self, metadata: &MetadataFrame) {
+        unsafe { backend().recv_add_connection_metadata(self.as_ptr(), metadata.as_ptr()) }
     }
 
     pub fn get_queue(&self) -> Queue {
         unsafe {
             let mut queue = mem::MaybeUninit::uninit();
-            NDIlib_recv_get_queue(self.0.as_ptr(), queue.as_mut_ptr());
+            backend().recv_get_queue(self.as_ptr(), queue.as_mut_ptr());
             Queue(queue.assume_init())
         }
     }
 
+    /// Returns the number of senders this receiver is connected to.
+    pub fn get_no_connections(&self) -> i32 {
+        unsafe { backend().recv_get_no_connections(self.as_ptr()) }
+    }
+
+    /// Returns the URL of the web control page of the source, if it has one.
+    pub fn get_web_control(&self) -> Option<String> {
+        unsafe {
+            let ptr = backend().recv_get_web_control(self.as_ptr());
+            if ptr.is_null() {
+                return None;
+            }
+
+            let url = ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned();
+            backend().recv_free_string(self.as_ptr(), ptr);
+            Some(url)
+        }
+    }
+
+    /// Returns the total and dropped frame counts since the receiver was created.
+    pub fn get_performance(&self) -> (Performance, Performance) {
+        unsafe {
+            let mut total = mem::MaybeUninit::uninit();
+            let mut dropped = mem::MaybeUninit::uninit();
+            backend().recv_get_performance(self.as_ptr(), total.as_mut_ptr(), dropped.as_mut_ptr());
+            (
+                Performance(total.assume_init()),
+                Performance(dropped.assume_init()),
+            )
+        }
+    }
+
+    /// Whether the connected source announced PTZ support. This only becomes true some time
+    /// after connecting.
+    pub fn ptz_is_supported(&self) -> bool {
+        unsafe { backend().recv_ptz_is_supported(self.as_ptr()) }
+    }
+
+    /// Presets are numbered from 0 to 99.
+    pub fn ptz_store_preset(&self, preset_no: i32) -> bool {
+        unsafe { backend().recv_ptz_store_preset(self.as_ptr(), preset_no) }
+    }
+
+    /// `speed` is between 0.0 (slowest) and 1.0 (fastest).
+    pub fn ptz_recall_preset(&self, preset_no: i32, speed: f32) -> bool {
+        unsafe { backend().recv_ptz_recall_preset(self.as_ptr(), preset_no, speed) }
+    }
+
+    /// Zoom from 0.0 (zoomed in) to 1.0 (zoomed out).
+    pub fn ptz_zoom(&self, zoom_value: f32) -> bool {
+        unsafe { backend().recv_ptz_zoom(self.as_ptr(), zoom_value) }
+    }
+
+    /// Zoom speed from -1.0 (zoom out) to 1.0 (zoom in), 0.0 stops zooming.
+    pub fn ptz_zoom_speed(&self, zoom_speed: f32) -> bool {
+        unsafe { backend().recv_ptz_zoom_speed(self.as_ptr(), zoom_speed) }
+    }
+
+    /// Absolute pan and tilt from -1.0 to 1.0, with 0.0 being centered.
+    pub fn ptz_pan_tilt(&self, pan_value: f32, tilt_value: f32) -> bool {
+        unsafe { backend().recv_ptz_pan_tilt(self.as_ptr(), pan_value, tilt_value) }
+    }
+
+    /// Pan speed from -1.0 (right) to 1.0 (left) and tilt speed from -1.0 (down) to 1.0 (up).
+    pub fn ptz_pan_tilt_speed(&self, pan_speed: f32, tilt_speed: f32) -> bool {
+        unsafe { backend().recv_ptz_pan_tilt_speed(self.as_ptr(), pan_speed, tilt_speed) }
+    }
+
+    pub fn ptz_auto_focus(&self) -> bool {
+        unsafe { backend().recv_ptz_auto_focus(self.as_ptr()) }
+    }
+
+    /// Manual focus from 0.0 (focused to infinity) to 1.0 (focused as close as possible).
+    pub fn ptz_focus(&self, focus_value: f32) -> bool {
+        unsafe { backend().recv_ptz_focus(self.as_ptr(), focus_value) }
+    }
+
+    /// Focus speed from -1.0 (focus outwards) to 1.0 (focus inwards).
+    pub fn ptz_focus_speed(&self, focus_speed: f32) -> bool {
+        unsafe { backend().recv_ptz_focus_speed(self.as_ptr(), focus_speed) }
+    }
+
+    pub fn ptz_white_balance_auto(&self) -> bool {
+        unsafe { backend().recv_ptz_white_balance_auto(self.as_ptr()) }
+    }
+
+    pub fn ptz_white_balance_indoor(&self) -> bool {
+        unsafe { backend().recv_ptz_white_balance_indoor(self.as_ptr()) }
+    }
+
+    pub fn ptz_white_balance_outdoor(&self) -> bool {
+        unsafe { backend().recv_ptz_white_balance_outdoor(self.as_ptr()) }
+    }
+
+    pub fn ptz_white_balance_oneshot(&self) -> bool {
+        unsafe { backend().recv_ptz_white_balance_oneshot(self.as_ptr()) }
+    }
+
+    /// Red and blue gains from 0.0 to 1.0.
+    pub fn ptz_white_balance_manual(&self, red: f32, blue: f32) -> bool {
+        unsafe { backend().recv_ptz_white_balance_manual(self.as_ptr(), red, blue) }
+    }
+
+    pub fn ptz_exposure_auto(&self) -> bool {
+        unsafe { backend().recv_ptz_exposure_auto(self.as_ptr()) }
+    }
+
+    /// Exposure from 0.0 (dark) to 1.0 (light).
+    pub fn ptz_exposure_manual(&self, exposure_level: f32) -> bool {
+        unsafe { backend().recv_ptz_exposure_manual(self.as_ptr(), exposure_level) }
+    }
+
     pub fn capture(&self, timeout_in_ms: u32) -> Result<Option<Frame>, ()> {
         unsafe {
-            let ptr = self.0.as_ptr();
+            let ptr = self.as_ptr();
 
             let mut video_frame = mem::zeroed();
             let mut audio_frame = mem::zeroed();
             let mut metadata_frame = mem::zeroed();
 
-            let res = NDIlib_recv_capture_v3(
+            let res = backend().recv_capture_v3(
                 ptr,
                 &mut video_frame,
                 &mut audio_frame,
@@ -310,6 +488,36 @@ impl RecvInstance {
                 NDIlib_frame_type_e::NDIlib_frame_type_metadata => Ok(Some(Frame::Metadata(
                     MetadataFrame::Borrowed(metadata_frame, self),
                 ))),
+                NDIlib_frame_type_e::NDIlib_frame_type_status_change => {
+                    Ok(Some(Frame::StatusChange))
+                }
+                NDIlib_frame_type_e::NDIlib_frame_type_error => Err(()),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    /// Captures only metadata frames and status changes, e.g. while audio and video are captured
+    /// by a `FramesyncInstance`.
+    pub fn capture_metadata(&self, timeout_in_ms: u32) -> Result<Option<Frame>, ()> {
+        unsafe {
+            let mut metadata_frame = mem::zeroed();
+
+            let res = backend().recv_capture_v3(
+                self.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut metadata_frame,
+                timeout_in_ms,
+            );
+
+            match res {
+                NDIlib_frame_type_e::NDIlib_frame_type_metadata => Ok(Some(Frame::Metadata(
+                    MetadataFrame::Borrowed(metadata_frame, self),
+                ))),
+                NDIlib_frame_type_e::NDIlib_frame_type_status_change => {
+                    Ok(Some(Frame::StatusChange))
+                }
                 NDIlib_frame_type_e::NDIlib_frame_type_error => Err(()),
                 _ => Ok(None),
             }
@@ -317,9 +525,98 @@ impl RecvInstance {
     }
 }
 
-impl Drop for RecvInstance {
+/// Frame synchronizer on top of a receive instance.
+///
+/// Audio and video are pulled at the rate of the caller, with the SDK repeating or dropping
+/// video frames and resampling audio as needed.
+#[derive(Debug)]
+pub struct FramesyncInstance {
+    ptr: ptr::NonNull<::std::os::raw::c_void>,
+    // The receive instance must outlive the frame synchronizer
+    _recv: RecvInstance,
+}
+
+unsafe impl Send for FramesyncInstance {}
+
+impl FramesyncInstance {
+    /// Returns `None` if the frame synchronizer is not supported by the NDI SDK.
+    pub fn new(recv: &RecvInstance) -> Option<Self> {
+        unsafe {
+            let ptr = backend().framesync_create(recv.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(FramesyncInstance {
+                    ptr: ptr::NonNull::new_unchecked(ptr),
+                    _recv: recv.clone(),
+                })
+            }
+        }
+    }
+
+    fn as_ptr(&self) -> *mut ::std::os::raw::c_void {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the latest video frame, or `None` if no video was received yet.
+    pub fn capture_video(&self, field_type: NDIlib_frame_format_type_e) -> Option<VideoFrame> {
+        unsafe {
+            let mut video_frame: NDIlib_video_frame_v2_t = mem::zeroed();
+            backend().framesync_capture_video(self.as_ptr(), &mut video_frame, field_type);
+
+            if video_frame.p_data.is_null() {
+                None
+            } else {
+                Some(VideoFrame::BorrowedFramesync(video_frame, self))
+            }
+        }
+    }
+
+    /// Returns the sample rate and number of channels of the received audio, or `None` if no
+    /// audio was received yet.
+    pub fn audio_format(&self) -> Option<(i32, i32)> {
+        unsafe {
+            let mut audio_frame: NDIlib_audio_frame_v3_t = mem::zeroed();
+            backend().framesync_capture_audio_v2(self.as_ptr(), &mut audio_frame, 0, 0, 0);
+
+            if audio_frame.sample_rate > 0 && audio_frame.no_channels > 0 {
+                Some((audio_frame.sample_rate, audio_frame.no_channels))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns exactly `no_samples` samples, filled with silence if not enough audio was
+    /// received.
+    pub fn capture_audio(
+        &self,
+        sample_rate: i32,
+        no_channels: i32,
+        no_samples: i32,
+    ) -> Option<AudioFrame> {
+        unsafe {
+            let mut audio_frame: NDIlib_audio_frame_v3_t = mem::zeroed();
+            backend().framesync_capture_audio_v2(
+                self.as_ptr(),
+                &mut audio_frame,
+                sample_rate,
+                no_channels,
+                no_samples,
+            );
+
+            if audio_frame.p_data.is_null() {
+                None
+            } else {
+                Some(AudioFrame::BorrowedFramesync(audio_frame, self))
+            }
+        }
+    }
+}
+
+impl Drop for FramesyncInstance {
     fn drop(&mut self) {
-        unsafe { NDIlib_recv_destroy(self.0.as_ptr() as *mut _) }
+        unsafe { backend().framesync_destroy(self.as_ptr()) }
     }
 }
 
@@ -328,9 +625,17 @@ pub struct SendBuilder<'a> {
     ndi_name: &'a str,
     clock_audio: bool,
     clock_video: bool,
+    groups: Option<&'a str>,
 }
 
 impl<'a> SendBuilder<'a> {
+    pub fn groups(self, groups: &'a str) -> Self {
+        Self {
+            groups: Some(groups),
+            ..self
+        }
+    }
+
     pub fn clock_audio(self) -> Self {
         Self {
             clock_audio: true,
@@ -348,11 +653,12 @@ impl<'a> SendBuilder<'a> {
     pub fn build(self) -> Option<SendInstance> {
         unsafe {
             let ndi_name = ffi::CString::new(self.ndi_name).unwrap();
-            let ptr = NDIlib_send_create(&NDIlib_send_create_t {
+            let groups = self.groups.map(|s| ffi::CString::new(s).unwrap());
+            let ptr = backend().send_create(&NDIlib_send_create_t {
                 p_ndi_name: ndi_name.as_ptr(),
                 clock_video: self.clock_video,
                 clock_audio: self.clock_audio,
-                p_groups: ptr::null(),
+                p_groups: groups.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null()),
             });
 
             if ptr.is_null() {
@@ -368,6 +674,8 @@ impl<'a> SendBuilder<'a> {
 pub struct SendInstance(ptr::NonNull<::std::os::raw::c_void>);
 
 unsafe impl Send for SendInstance {}
+// The SDK allows capturing from a send instance while sending from another thread
+unsafe impl Sync for SendInstance {}
 
 impl SendInstance {
     pub fn builder(ndi_name: &str) -> SendBuilder {
@@ -375,29 +683,128 @@ impl SendInstance {
             ndi_name,
             clock_video: false,
             clock_audio: false,
+            groups: None,
         }
     }
 
-    pub fn send_video(&mut self, frame: &VideoFrame) {
+    pub fn send_video(&self, frame: &VideoFrame) {
         unsafe {
-            NDIlib_send_send_video_v2(self.0.as_ptr(), frame.as_ptr());
+            backend().send_send_video_v2(self.0.as_ptr(), frame.as_ptr());
         }
     }
 
-    pub fn send_audio(&mut self, frame: &AudioFrame) {
+    pub fn send_audio(&self, frame: &AudioFrame) {
         unsafe {
-            NDIlib_send_send_audio_v3(self.0.as_ptr(), frame.as_ptr());
+            backend().send_send_audio_v3(self.0.as_ptr(), frame.as_ptr());
+        }
+    }
+
+    pub fn send_metadata(&self, frame: &MetadataFrame) {
+        unsafe {
+            backend().send_send_metadata(self.0.as_ptr(), frame.as_ptr());
+        }
+    }
+
+    pub fn capture(&self, timeout_in_ms: u32) -> Result<Option<MetadataFrame>, ()> {
+        unsafe {
+            let mut metadata_frame = mem::zeroed();
+
+            let res = backend().send_capture(self.0.as_ptr(), &mut metadata_frame, timeout_in_ms);
+
+            match res {
+                NDIlib_frame_type_e::NDIlib_frame_type_metadata => {
+                    Ok(Some(MetadataFrame::BorrowedSend(metadata_frame, self)))
+                }
+                NDIlib_frame_type_e::NDIlib_frame_type_error => Err(()),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    pub fn get_no_connections(&self, timeout_in_ms: u32) -> i32 {
+        unsafe { backend().send_get_no_connections(self.0.as_ptr(), timeout_in_ms) }
+    }
+
+    /// Adds metadata that is sent to every receiver when it connects.
+    pub fn add_connection_metadata(&self, frame: &MetadataFrame) {
+        unsafe {
+            backend().send_add_connection_metadata(self.0.as_ptr(), frame.as_ptr());
+        }
+    }
+
+    pub fn clear_connection_metadata(&self) {
+        unsafe {
+            backend().send_clear_connection_metadata(self.0.as_ptr());
+        }
+    }
+
+    /// Sets the source that receivers of this sender switch to if it goes away, or clears it.
+    pub fn set_failover(&self, source: Option<&Source>) {
+        unsafe {
+            backend().send_set_failover(
+                self.0.as_ptr(),
+                source.map(|source| source.as_ptr()).unwrap_or(ptr::null()),
+            )
+        }
+    }
+
+    pub fn get_tally(&self, timeout_in_ms: u32) -> Option<Tally> {
+        unsafe {
+            let mut tally = mem::MaybeUninit::uninit();
+            if backend().send_get_tally(self.0.as_ptr(), tally.as_mut_ptr(), timeout_in_ms) {
+                Some(Tally(tally.assume_init()))
+            } else {
+                None
+            }
         }
     }
 }
 
 impl Drop for SendInstance {
     fn drop(&mut self) {
-        unsafe { NDIlib_send_destroy(self.0.as_ptr() as *mut _) }
+        unsafe { backend().send_destroy(self.0.as_ptr() as *mut _) }
     }
 }
 
 #[derive(Debug)]
+pub struct RoutingInstance(ptr::NonNull<::std::os::raw::c_void>);
+
+unsafe impl Send for RoutingInstance {}
+unsafe impl Sync for RoutingInstance {}
+
+impl RoutingInstance {
+    pub fn new(ndi_name: &str) -> Option<Self> {
+        unsafe {
+            let ndi_name = ffi::CString::new(ndi_name).unwrap();
+            let ptr = backend().routing_create(&NDIlib_routing_create_t {
+                p_ndi_name: ndi_name.as_ptr(),
+                p_groups: ptr::null(),
+            });
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(RoutingInstance(ptr::NonNull::new_unchecked(ptr)))
+            }
+        }
+    }
+
+    pub fn change(&self, source: &Source) -> bool {
+        unsafe { backend().routing_change(self.0.as_ptr(), source.as_ptr()) }
+    }
+
+    pub fn clear(&self) -> bool {
+        unsafe { backend().routing_clear(self.0.as_ptr()) }
+    }
+}
+
+impl Drop for RoutingInstance {
+    fn drop(&mut self) {
+        unsafe { backend().routing_destroy(self.0.as_ptr()) }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct Tally(NDIlib_tally_t);
 unsafe impl Send for Tally {}
 
@@ -432,12 +839,16 @@ pub enum Frame<'a> {
     Video(VideoFrame<'a>),
     Audio(AudioFrame<'a>),
     Metadata(MetadataFrame<'a>),
+    // The connection, tally or web control URL of the source changed
+    StatusChange,
 }
 
 #[derive(Debug)]
 pub enum VideoFrame<'a> {
-    //Owned(NDIlib_video_frame_v2_t, Option<ffi::CString>, Option<Vec<u8>>),
+    // Frame converted from a GStreamer format without NDI equivalent
+    Owned(NDIlib_video_frame_v2_t, Vec<u8>),
     BorrowedRecv(NDIlib_video_frame_v2_t, &'a RecvInstance),
+    BorrowedFramesync(NDIlib_video_frame_v2_t, &'a FramesyncInstance),
     BorrowedGst(
         NDIlib_video_frame_v2_t,
         &'a gst_video::VideoFrameRef<&'a gst::BufferRef>,
@@ -447,57 +858,64 @@ pub enum VideoFrame<'a> {
 impl<'a> VideoFrame<'a> {
     pub fn xres(&self) -> i32 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.xres
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.xres,
         }
     }
 
     pub fn yres(&self) -> i32 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.yres
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.yres,
         }
     }
 
     pub fn fourcc(&self) -> NDIlib_FourCC_video_type_e {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.FourCC
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.FourCC,
         }
     }
 
     pub fn frame_rate(&self) -> (i32, i32) {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                (frame.frame_rate_N, frame.frame_rate_D)
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => (frame.frame_rate_N, frame.frame_rate_D),
         }
     }
 
     pub fn picture_aspect_ratio(&self) -> f32 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.picture_aspect_ratio
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.picture_aspect_ratio,
         }
     }
 
     pub fn frame_format_type(&self) -> NDIlib_frame_format_type_e {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.frame_format_type
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.frame_format_type,
         }
     }
 
     pub fn timecode(&self) -> i64 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.timecode
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.timecode,
         }
     }
 
@@ -519,26 +937,57 @@ impl<'a> VideoFrame<'a> {
         ]
         .contains(&fourcc)
         {
+            if self.xres() <= 0 || self.yres() <= 0 || self.line_stride_or_data_size_in_bytes() <= 0
+            {
+                return None;
+            }
+
             // FIXME: Unclear if this is correct. Needs to be validated against an actual
             // interlaced stream
-            let frame_size = if self.frame_format_type()
+            let field = self.frame_format_type()
                 == NDIlib_frame_format_type_e::NDIlib_frame_format_type_field_0
                 || self.frame_format_type()
-                    == NDIlib_frame_format_type_e::NDIlib_frame_format_type_field_1
+                    == NDIlib_frame_format_type_e::NDIlib_frame_format_type_field_1;
+            let frame_size = if field {
+                self.yres() as usize * self.line_stride_or_data_size_in_bytes() as usize / 2
+            } else {
+                self.yres() as usize * self.line_stride_or_data_size_in_bytes() as usize
+            };
+
+            // Planar formats store further planes after the first one
+            let frame_size = if fourcc == NDIlib_FourCC_video_type_UYVA {
+                // Alpha plane with one byte per pixel, i.e. a stride of xres
+                let alpha_size = self.yres() as usize * self.xres() as usize;
+                if field {
+                    frame_size + alpha_size / 2
+                } else {
+                    frame_size + alpha_size
+                }
+            } else if [
+                NDIlib_FourCC_video_type_YV12,
+                NDIlib_FourCC_video_type_I420,
+                NDIlib_FourCC_video_type_NV12,
+            ]
+            .contains(&fourcc)
             {
-                self.yres() * self.line_stride_or_data_size_in_bytes() / 2
+                frame_size * 3 / 2
+            } else if fourcc == NDIlib_FourCC_video_type_P216 {
+                frame_size * 2
+            } else if fourcc == NDIlib_FourCC_video_type_PA16 {
+                frame_size * 3
             } else {
-                self.yres() * self.line_stride_or_data_size_in_bytes()
+                frame_size
             };
 
             return unsafe {
                 use std::slice;
                 match self {
                     VideoFrame::BorrowedRecv(ref frame, _)
-                    | VideoFrame::BorrowedGst(ref frame, _) => Some(slice::from_raw_parts(
-                        frame.p_data as *const u8,
-                        frame_size as usize,
-                    )),
+                    | VideoFrame::BorrowedFramesync(ref frame, _)
+                    | VideoFrame::BorrowedGst(ref frame, _)
+                    | VideoFrame::Owned(ref frame, _) => {
+                        Some(slice::from_raw_parts(frame.p_data as *const u8, frame_size))
+                    }
                 }
             };
         }
@@ -558,7 +1007,9 @@ impl<'a> VideoFrame<'a> {
                 use std::slice;
                 match self {
                     VideoFrame::BorrowedRecv(ref frame, _)
-                    | VideoFrame::BorrowedGst(ref frame, _) => Some(slice::from_raw_parts(
+                    | VideoFrame::BorrowedFramesync(ref frame, _)
+                    | VideoFrame::BorrowedGst(ref frame, _)
+                    | VideoFrame::Owned(ref frame, _) => Some(slice::from_raw_parts(
                         frame.p_data as *const u8,
                         frame.line_stride_or_data_size_in_bytes as usize,
                     )),
@@ -594,12 +1045,13 @@ impl<'a> VideoFrame<'a> {
             }
 
             let data = match self {
-                VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                    slice::from_raw_parts(
-                        frame.p_data as *const u8,
-                        frame.line_stride_or_data_size_in_bytes as usize,
-                    )
-                }
+                VideoFrame::BorrowedRecv(ref frame, _)
+                | VideoFrame::BorrowedFramesync(ref frame, _)
+                | VideoFrame::BorrowedGst(ref frame, _)
+                | VideoFrame::Owned(ref frame, _) => slice::from_raw_parts(
+                    frame.p_data as *const u8,
+                    frame.line_stride_or_data_size_in_bytes as usize,
+                ),
             };
 
             let mut cursor = Cursor::new(data);
@@ -644,7 +1096,10 @@ impl<'a> VideoFrame<'a> {
 
     pub fn line_stride_or_data_size_in_bytes(&self) -> i32 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => {
                 let stride = frame.line_stride_or_data_size_in_bytes;
 
                 if stride != 0 {
@@ -674,7 +1129,10 @@ impl<'a> VideoFrame<'a> {
     pub fn metadata(&self) -> Option<&str> {
         unsafe {
             match self {
-                VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
+                VideoFrame::BorrowedRecv(ref frame, _)
+                | VideoFrame::BorrowedFramesync(ref frame, _)
+                | VideoFrame::BorrowedGst(ref frame, _)
+                | VideoFrame::Owned(ref frame, _) => {
                     if frame.p_metadata.is_null() {
                         None
                     } else {
@@ -687,22 +1145,30 @@ impl<'a> VideoFrame<'a> {
 
     pub fn timestamp(&self) -> i64 {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => {
-                frame.timestamp
-            }
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame.timestamp,
         }
     }
 
     pub fn as_ptr(&self) -> *const NDIlib_video_frame_v2_t {
         match self {
-            VideoFrame::BorrowedRecv(ref frame, _) | VideoFrame::BorrowedGst(ref frame, _) => frame,
+            VideoFrame::BorrowedRecv(ref frame, _)
+            | VideoFrame::BorrowedFramesync(ref frame, _)
+            | VideoFrame::BorrowedGst(ref frame, _)
+            | VideoFrame::Owned(ref frame, _) => frame,
         }
     }
 
     pub fn try_from_video_frame(
         frame: &'a gst_video::VideoFrameRef<&'a gst::BufferRef>,
         timecode: i64,
+        metadata: Option<&'a ffi::CStr>,
     ) -> Result<Self, ()> {
+        // Frame data converted to UYVA, P216 or PA16 together with its line stride
+        let mut converted = None;
+
         // Planar formats must be in contiguous memory
         let format = match frame.format() {
             gst_video::VideoFormat::Uyvy => ndisys::NDIlib_FourCC_video_type_UYVY,
@@ -764,7 +1230,11 @@ impl<'a> VideoFrame<'a> {
             gst_video::VideoFormat::Bgrx => ndisys::NDIlib_FourCC_video_type_BGRX,
             gst_video::VideoFormat::Rgba => ndisys::NDIlib_FourCC_video_type_RGBA,
             gst_video::VideoFormat::Rgbx => ndisys::NDIlib_FourCC_video_type_RGBX,
-            _ => return Err(()),
+            _ => {
+                let (format, data, stride) = convert_video_frame(frame).ok_or(())?;
+                converted = Some((data, stride));
+                format
+            }
         };
 
         let frame_format_type = match frame.info().interlace_mode() {
@@ -804,23 +1274,311 @@ impl<'a> VideoFrame<'a> {
             picture_aspect_ratio,
             frame_format_type,
             timecode,
-            p_data: frame.plane_data(0).unwrap().as_ptr() as *const i8,
-            line_stride_or_data_size_in_bytes: frame.plane_stride()[0],
-            p_metadata: ptr::null(),
+            p_data: match converted {
+                Some((ref data, _)) => data.as_ptr() as *const i8,
+                None => frame.plane_data(0).unwrap().as_ptr() as *const i8,
+            },
+            line_stride_or_data_size_in_bytes: match converted {
+                Some((_, stride)) => stride,
+                None => frame.plane_stride()[0],
+            },
+            p_metadata: metadata.map(|s| s.as_ptr()).unwrap_or(ptr::null()),
             timestamp: 0,
         };
 
-        Ok(VideoFrame::BorrowedGst(ndi_frame, frame))
+        match converted {
+            Some((data, _)) => Ok(VideoFrame::Owned(ndi_frame, data)),
+            None => Ok(VideoFrame::BorrowedGst(ndi_frame, frame)),
+        }
+    }
+}
+
+// Converts 8 bit YUV with alpha to UYVA and higher bit depth YUV to P216, or to PA16 if it has
+// alpha. Returns the FourCC, the data and its line stride.
+fn convert_video_frame(
+    frame: &gst_video::VideoFrameRef<&gst::BufferRef>,
+) -> Option<(NDIlib_FourCC_video_type_e, Vec<u8>, i32)> {
+    use gst_video::VideoFormat;
+
+    let width = frame.width() as usize;
+    let height = frame.height() as usize;
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let planes = (0..frame.n_planes())
+        .map(|plane| frame.plane_data(plane).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let strides = frame.plane_stride();
+    let line = |plane: usize, y: usize| &planes[plane][y * strides[plane] as usize..];
+
+    match frame.format() {
+        VideoFormat::Ayuv => Some(pack_uyva(width, height, |y, row| {
+            let line = line(0, y);
+            read_samples8(&line[1..], 4, &mut row.y);
+            read_samples8(&line[2..], 8, &mut row.u);
+            read_samples8(&line[3..], 8, &mut row.v);
+            read_samples8(line, 4, &mut row.a);
+        })),
+        VideoFormat::A420 => Some(pack_uyva(width, height, |y, row| {
+            read_samples8(line(0, y), 1, &mut row.y);
+            read_samples8(line(1, y / 2), 1, &mut row.u);
+            read_samples8(line(2, y / 2), 1, &mut row.v);
+            read_samples8(line(3, y), 1, &mut row.a);
+        })),
+        VideoFormat::Ayuv64 => Some(pack_p216(width, height, true, |y, row| {
+            let line = line(0, y);
+            let read = |offset: usize, step: usize, out: &mut [u16]| {
+                read_samples16(&line[offset..], step, 16, false, u16::from_ne_bytes, out)
+            };
+            read(2, 4, &mut row.y);
+            read(4, 8, &mut row.u);
+            read(6, 8, &mut row.v);
+            read(0, 4, &mut row.a);
+        })),
+        VideoFormat::V210 => Some(pack_p216(width, height, false, |y, row| {
+            read_v210(line(0, y), row)
+        })),
+        // Only available since GStreamer 1.16, which is required for ndisink anyway
+        #[cfg(feature = "sink")]
+        VideoFormat::Y210 => Some(pack_p216(width, height, false, |y, row| {
+            let line = line(0, y);
+            let read = |offset: usize, step: usize, out: &mut [u16]| {
+                read_samples16(&line[offset..], step, 10, true, u16::from_le_bytes, out)
+            };
+            read(0, 2, &mut row.y);
+            read(2, 4, &mut row.u);
+            read(6, 4, &mut row.v);
+        })),
+        VideoFormat::P01010le => Some(pack_p216(width, height, false, |y, row| {
+            let read = |line: &[u8], step: usize, out: &mut [u16]| {
+                read_samples16(line, step, 10, true, u16::from_le_bytes, out)
+            };
+            read(line(0, y), 1, &mut row.y);
+            read(line(1, y / 2), 2, &mut row.u);
+            read(&line(1, y / 2)[2..], 2, &mut row.v);
+        })),
+        VideoFormat::A44410le
+        | VideoFormat::A42210le
+        | VideoFormat::A42010le
+        | VideoFormat::Y44412le
+        | VideoFormat::I42212le
+        | VideoFormat::I42012le
+        | VideoFormat::Y44410le
+        | VideoFormat::I42210le
+        | VideoFormat::I42010le => {
+            let format_info = frame.format_info();
+            let depth = format_info.depth()[0];
+            let has_alpha = format_info.has_alpha();
+            // Only the chroma of the first pixel of each pair is used for 4:4:4
+            let chroma_step = if format_info.w_sub()[1] == 0 { 2 } else { 1 };
+            let chroma_shift = format_info.h_sub()[1];
+
+            Some(pack_p216(width, height, has_alpha, |y, row| {
+                let read = |line: &[u8], step: usize, out: &mut [u16]| {
+                    read_samples16(line, step, depth, false, u16::from_le_bytes, out)
+                };
+                read(line(0, y), 1, &mut row.y);
+                read(line(1, y >> chroma_shift), chroma_step, &mut row.u);
+                read(line(2, y >> chroma_shift), chroma_step, &mut row.v);
+                if has_alpha {
+                    read(line(3, y), 1, &mut row.a);
+                }
+            }))
+        }
+        _ => None,
     }
 }
 
+// Samples of one line of YUVA pixels with one U and V sample per pair of pixels
+struct Row<T> {
+    y: Vec<T>,
+    u: Vec<T>,
+    v: Vec<T>,
+    a: Vec<T>,
+}
+
+impl<T: Copy> Row<T> {
+    // Alpha stays opaque if it is never read
+    fn new(width: usize, opaque: T) -> Self {
+        let pairs = width / 2 + width % 2;
+        Row {
+            y: vec![opaque; width],
+            u: vec![opaque; pairs],
+            v: vec![opaque; pairs],
+            a: vec![opaque; width],
+        }
+    }
+}
+
+// Reads every `step`-th byte of `data` into `out`
+fn read_samples8(data: &[u8], step: usize, out: &mut [u8]) {
+    for (out, sample) in out.iter_mut().zip(data.iter().step_by(step)) {
+        *out = *sample;
+    }
+}
+
+// Reads every `step`-th 16 bit sample of `data` into `out`. Samples with `depth` bits in their
+// most significant bits if `msb` or their least significant bits otherwise are scaled to 16 bit
+// by replicating the most significant bits.
+#[inline]
+fn read_samples16(
+    data: &[u8],
+    step: usize,
+    depth: u32,
+    msb: bool,
+    from_bytes: fn([u8; 2]) -> u16,
+    out: &mut [u16],
+) {
+    let samples = out.iter_mut().zip(data.chunks(2 * step));
+
+    if depth >= 16 {
+        for (out, sample) in samples {
+            *out = from_bytes([sample[0], sample[1]]);
+        }
+    } else if msb {
+        for (out, sample) in samples {
+            let value = from_bytes([sample[0], sample[1]]) >> (16 - depth);
+            *out = (value << (16 - depth)) | (value >> (2 * depth - 16));
+        }
+    } else {
+        let mask = (1 << depth) - 1;
+        for (out, sample) in samples {
+            let value = from_bytes([sample[0], sample[1]]) & mask;
+            *out = (value << (16 - depth)) | (value >> (2 * depth - 16));
+        }
+    }
+}
+
+// Reads a line of v210, scaled to 16 bit. Each 16 bytes contain the samples U Y V Y U Y V Y U Y
+// V Y for 6 pixels, packed as three 10 bit values per little-endian 32 bit word.
+fn read_v210(line: &[u8], row: &mut Row<u16>) {
+    let width = row.y.len();
+    let pairs = row.u.len();
+
+    for (block, words) in line
+        .chunks_exact(16)
+        .enumerate()
+        .take_while(|(block, _)| 3 * block < pairs)
+    {
+        let mut samples = [0u16; 12];
+        for (samples, word) in samples.chunks_exact_mut(3).zip(words.chunks_exact(4)) {
+            let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            for (i, sample) in samples.iter_mut().enumerate() {
+                let value = ((word >> (10 * i)) & 0x3ff) as u16;
+                *sample = (value << 6) | (value >> 4);
+            }
+        }
+
+        for (i, samples) in samples.chunks_exact(4).enumerate() {
+            let pair = 3 * block + i;
+            if pair >= pairs {
+                break;
+            }
+            row.u[pair] = samples[0];
+            row.v[pair] = samples[2];
+            row.y[2 * pair] = samples[1];
+            if 2 * pair + 1 < width {
+                row.y[2 * pair + 1] = samples[3];
+            }
+        }
+    }
+}
+
+// Packs 8 bit YUVA lines into UYVY followed by an alpha plane with a stride of the width. For
+// odd widths the UYVY stride is rounded up to the next pair of pixels, with the luma of the last
+// pixel repeated.
+fn pack_uyva(
+    width: usize,
+    height: usize,
+    mut read_row: impl FnMut(usize, &mut Row<u8>),
+) -> (NDIlib_FourCC_video_type_e, Vec<u8>, i32) {
+    let stride = 2 * (width + width % 2);
+    let mut data = vec![0u8; stride * height + width * height];
+    let (uyvy, alpha) = data.split_at_mut(stride * height);
+    let mut row = Row::new(width, u8::MAX);
+
+    for (y, (uyvy, alpha)) in uyvy
+        .chunks_exact_mut(stride)
+        .zip(alpha.chunks_exact_mut(width))
+        .enumerate()
+    {
+        read_row(y, &mut row);
+
+        for (uyvy, (luma, (u, v))) in uyvy
+            .chunks_exact_mut(4)
+            .zip(row.y.chunks(2).zip(row.u.iter().zip(&row.v)))
+        {
+            uyvy[0] = *u;
+            uyvy[1] = luma[0];
+            uyvy[2] = *v;
+            uyvy[3] = luma[luma.len() - 1];
+        }
+        alpha.copy_from_slice(&row.a);
+    }
+
+    (ndisys::NDIlib_FourCC_video_type_UYVA, data, stride as i32)
+}
+
+// Packs 16 bit YUVA lines into a Y plane and an interleaved UV plane, followed by an alpha plane
+// if `has_alpha`. All samples are little-endian. For odd widths the stride is rounded up to the
+// next pair of pixels, with the luma and alpha of the last pixel repeated.
+fn pack_p216(
+    width: usize,
+    height: usize,
+    has_alpha: bool,
+    mut read_row: impl FnMut(usize, &mut Row<u16>),
+) -> (NDIlib_FourCC_video_type_e, Vec<u8>, i32) {
+    let stride = 2 * (width + width % 2);
+    let plane_size = stride * height;
+    let mut data = vec![0u8; plane_size * if has_alpha { 3 } else { 2 }];
+    let (luma, rest) = data.split_at_mut(plane_size);
+    let (chroma, alpha) = rest.split_at_mut(plane_size);
+    let mut row = Row::new(width, u16::MAX);
+
+    let write_pairs = |dest: &mut [u8], samples: &[u16]| {
+        for (dest, pair) in dest.chunks_exact_mut(4).zip(samples.chunks(2)) {
+            dest[..2].copy_from_slice(&pair[0].to_le_bytes());
+            dest[2..].copy_from_slice(&pair[pair.len() - 1].to_le_bytes());
+        }
+    };
+
+    for y in 0..height {
+        read_row(y, &mut row);
+
+        let lines = y * stride..(y + 1) * stride;
+        write_pairs(&mut luma[lines.clone()], &row.y);
+        for (dest, (u, v)) in chroma[lines.clone()]
+            .chunks_exact_mut(4)
+            .zip(row.u.iter().zip(&row.v))
+        {
+            dest[..2].copy_from_slice(&u.to_le_bytes());
+            dest[2..].copy_from_slice(&v.to_le_bytes());
+        }
+        if has_alpha {
+            write_pairs(&mut alpha[lines], &row.a);
+        }
+    }
+
+    let fourcc = if has_alpha {
+        ndisys::NDIlib_FourCC_video_type_PA16
+    } else {
+        ndisys::NDIlib_FourCC_video_type_P216
+    };
+
+    (fourcc, data, stride as i32)
+}
+
 impl<'a> Drop for VideoFrame<'a> {
-    #[allow(irrefutable_let_patterns)]
     fn drop(&mut self) {
-        if let VideoFrame::BorrowedRecv(ref mut frame, recv) = *self {
-            unsafe {
-                NDIlib_recv_free_video_v2(recv.0.as_ptr() as *mut _, frame);
-            }
+        match *self {
+            VideoFrame::BorrowedRecv(ref mut frame, recv) => unsafe {
+                backend().recv_free_video_v2(recv.as_ptr(), frame);
+            },
+            VideoFrame::BorrowedFramesync(ref mut frame, framesync) => unsafe {
+                backend().framesync_free_video(framesync.as_ptr(), frame);
+            },
+            VideoFrame::BorrowedGst(..) | VideoFrame::Owned(..) => (),
         }
     }
 }
@@ -833,46 +1591,57 @@ pub enum AudioFrame<'a> {
         Option<Vec<f32>>,
     ),
     BorrowedRecv(NDIlib_audio_frame_v3_t, &'a RecvInstance),
+    BorrowedFramesync(NDIlib_audio_frame_v3_t, &'a FramesyncInstance),
+    // Non-interleaved F32 buffer whose planes are used directly
+    Mapped(
+        NDIlib_audio_frame_v3_t,
+        gst::MappedBuffer<gst::buffer::Readable>,
+    ),
 }
 
 impl<'a> AudioFrame<'a> {
     pub fn sample_rate(&self) -> i32 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.sample_rate
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.sample_rate,
         }
     }
 
     pub fn no_channels(&self) -> i32 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.no_channels
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.no_channels,
         }
     }
 
     pub fn no_samples(&self) -> i32 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.no_samples
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.no_samples,
         }
     }
 
     pub fn timecode(&self) -> i64 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.timecode
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.timecode,
         }
     }
 
     pub fn fourcc(&self) -> NDIlib_FourCC_audio_type_e {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.FourCC
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.FourCC,
         }
     }
 
@@ -884,25 +1653,26 @@ impl<'a> AudioFrame<'a> {
 
             if [NDIlib_FourCC_audio_type_FLTp].contains(&fourcc) {
                 return match self {
-                    AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                        Some(slice::from_raw_parts(
-                            frame.p_data as *const u8,
-                            (frame.no_channels * frame.channel_stride_or_data_size_in_bytes)
-                                as usize,
-                        ))
-                    }
+                    AudioFrame::BorrowedRecv(ref frame, _)
+                    | AudioFrame::BorrowedFramesync(ref frame, _)
+                    | AudioFrame::Owned(ref frame, _, _)
+                    | AudioFrame::Mapped(ref frame, _) => Some(slice::from_raw_parts(
+                        frame.p_data as *const u8,
+                        (frame.no_channels * frame.channel_stride_or_data_size_in_bytes) as usize,
+                    )),
                 };
             }
 
             #[cfg(feature = "advanced-sdk")]
             if [NDIlib_FourCC_audio_type_Opus].contains(&fourcc) {
                 return match self {
-                    AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                        Some(slice::from_raw_parts(
-                            frame.p_data as *const u8,
-                            frame.channel_stride_or_data_size_in_bytes as usize,
-                        ))
-                    }
+                    AudioFrame::BorrowedRecv(ref frame, _)
+                    | AudioFrame::BorrowedFramesync(ref frame, _)
+                    | AudioFrame::Owned(ref frame, _, _)
+                    | AudioFrame::Mapped(ref frame, _) => Some(slice::from_raw_parts(
+                        frame.p_data as *const u8,
+                        frame.channel_stride_or_data_size_in_bytes as usize,
+                    )),
                 };
             }
 
@@ -919,17 +1689,18 @@ impl<'a> AudioFrame<'a> {
         unsafe {
             let fourcc = self.fourcc();
 
-            if ![NDIlib_FourCC_audio_type_AAC].contains(&fourcc) {
+            if ![NDIlib_FourCC_audio_type_AAC, NDIlib_FourCC_audio_type_Opus].contains(&fourcc) {
                 return None;
             }
 
             let data = match self {
-                AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                    slice::from_raw_parts(
-                        frame.p_data as *const u8,
-                        frame.channel_stride_or_data_size_in_bytes as usize,
-                    )
-                }
+                AudioFrame::BorrowedRecv(ref frame, _)
+                | AudioFrame::BorrowedFramesync(ref frame, _)
+                | AudioFrame::Owned(ref frame, _, _)
+                | AudioFrame::Mapped(ref frame, _) => slice::from_raw_parts(
+                    frame.p_data as *const u8,
+                    frame.channel_stride_or_data_size_in_bytes as usize,
+                ),
             };
 
             let mut cursor = Cursor::new(data);
@@ -974,16 +1745,20 @@ impl<'a> AudioFrame<'a> {
 
     pub fn channel_stride_or_data_size_in_bytes(&self) -> i32 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.channel_stride_or_data_size_in_bytes
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.channel_stride_or_data_size_in_bytes,
         }
     }
 
     pub fn metadata(&self) -> Option<&str> {
         unsafe {
             match self {
-                AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
+                AudioFrame::BorrowedRecv(ref frame, _)
+                | AudioFrame::BorrowedFramesync(ref frame, _)
+                | AudioFrame::Owned(ref frame, _, _)
+                | AudioFrame::Mapped(ref frame, _) => {
                     if frame.p_metadata.is_null() {
                         None
                     } else {
@@ -996,49 +1771,129 @@ impl<'a> AudioFrame<'a> {
 
     pub fn timestamp(&self) -> i64 {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => {
-                frame.timestamp
-            }
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame.timestamp,
         }
     }
 
     pub fn as_ptr(&self) -> *const NDIlib_audio_frame_v3_t {
         match self {
-            AudioFrame::BorrowedRecv(ref frame, _) | AudioFrame::Owned(ref frame, _, _) => frame,
+            AudioFrame::BorrowedRecv(ref frame, _)
+            | AudioFrame::BorrowedFramesync(ref frame, _)
+            | AudioFrame::Owned(ref frame, _, _)
+            | AudioFrame::Mapped(ref frame, _) => frame,
         }
     }
 
+    /// Converts F32, S16, S24 and S32 audio to planar F32. Integer samples are scaled so that
+    /// full scale is `reference_level` dB above the NDI reference level of +4 dBu.
+    #[cfg(feature = "sink")]
     pub fn try_from_buffer(
         info: &gst_audio::AudioInfo,
         buffer: &gst::BufferRef,
         timecode: i64,
+        reference_level: i32,
     ) -> Result<Self, ()> {
-        if info.format() != gst_audio::AUDIO_FORMAT_F32 {
-            return Err(());
-        }
+        let sample_size = match info.format() {
+            gst_audio::AUDIO_FORMAT_F32 | gst_audio::AUDIO_FORMAT_S32 => 4,
+            gst_audio::AUDIO_FORMAT_S24 => 3,
+            gst_audio::AUDIO_FORMAT_S16 => 2,
+            _ => return Err(()),
+        };
+        let no_channels = info.channels() as usize;
 
-        let map = buffer.map_readable().map_err(|_| ())?;
-        let src_data = map.as_slice_of::<f32>().map_err(|_| ())?;
+        // Non-interleaved F32 with equally spaced planes is already in the NDI layout
+        if info.format() == gst_audio::AUDIO_FORMAT_F32
+            && info.layout() == gst_audio::AudioLayout::NonInterleaved
+        {
+            let meta = buffer.meta::<gst_audio::AudioMeta>().ok_or(())?;
+            let offsets = meta.offsets();
+            let no_samples = meta.samples();
+            let stride = if no_channels > 1 {
+                offsets[1] - offsets[0]
+            } else {
+                no_samples * mem::size_of::<f32>()
+            };
 
-        let no_samples = src_data.len() as i32 / info.channels() as i32;
-        let channel_stride_or_data_size_in_bytes = no_samples * mem::size_of::<f32>() as i32;
-        let mut dest_data =
-            Vec::<f32>::with_capacity(no_samples as usize * info.channels() as usize);
+            if stride >= no_samples * mem::size_of::<f32>()
+                && offsets
+                    .iter()
+                    .enumerate()
+                    .all(|(c, offset)| *offset == offsets[0] + c * stride)
+                && stride % mem::align_of::<f32>() == 0
+            {
+                let offset = offsets[0];
+                let map = buffer
+                    .to_owned()
+                    .into_mapped_buffer_readable()
+                    .map_err(|_| ())?;
+
+                // Misaligned memory is copied like all other layouts below
+                if (map.as_ptr() as usize + offset) % mem::align_of::<f32>() == 0 {
+                    let dest = NDIlib_audio_frame_v3_t {
+                        sample_rate: info.rate() as i32,
+                        no_channels: no_channels as i32,
+                        no_samples: no_samples as i32,
+                        timecode,
+                        FourCC: NDIlib_FourCC_audio_type_FLTp,
+                        p_data: unsafe { map.as_ptr().add(offset) as *mut f32 },
+                        channel_stride_or_data_size_in_bytes: stride as i32,
+                        p_metadata: ptr::null(),
+                        timestamp: 0,
+                    };
+
+                    return Ok(AudioFrame::Mapped(dest, map));
+                }
+            }
+        }
 
-        assert_eq!(dest_data.capacity(), src_data.len());
+        let scale = 10f32.powf(reference_level as f32 / 20.0);
+        let sample: fn(&[u8]) -> f32 = match info.format() {
+            gst_audio::AUDIO_FORMAT_F32 => |s| f32::from_ne_bytes([s[0], s[1], s[2], s[3]]),
+            gst_audio::AUDIO_FORMAT_S32 => {
+                |s| i32::from_ne_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2_147_483_648.0
+            }
+            gst_audio::AUDIO_FORMAT_S24 => |s| {
+                let s = if cfg!(target_endian = "little") {
+                    i32::from_le_bytes([0, s[0], s[1], s[2]])
+                } else {
+                    i32::from_be_bytes([s[0], s[1], s[2], 0])
+                };
+                (s >> 8) as f32 / 8_388_608.0
+            },
+            _ => |s| i16::from_ne_bytes([s[0], s[1]]) as f32 / 32_768.0,
+        };
+        let scale = if info.format() == gst_audio::AUDIO_FORMAT_F32 {
+            1.0
+        } else {
+            scale
+        };
 
-        unsafe {
-            let dest_ptr = dest_data.as_mut_ptr();
+        let audio_buffer =
+            gst_audio::AudioBufferRef::from_buffer_ref_readable(buffer, info).map_err(|_| ())?;
+        let no_samples = audio_buffer.n_samples();
+        let channel_stride_or_data_size_in_bytes = (no_samples * mem::size_of::<f32>()) as i32;
+        let mut dest_data = vec![0f32; no_samples * no_channels];
 
-            for (i, samples) in src_data.chunks_exact(info.channels() as usize).enumerate() {
-                for (c, sample) in samples.iter().enumerate() {
-                    ptr::write(dest_ptr.add(c * no_samples as usize + i), *sample);
-                }
-            }
+        for (c, dest) in dest_data.chunks_exact_mut(no_samples.max(1)).enumerate() {
+            let (src, sample_stride) = if info.layout() == gst_audio::AudioLayout::Interleaved {
+                let src = audio_buffer.plane_data(0).map_err(|_| ())?;
+                (&src[c * sample_size..], no_channels * sample_size)
+            } else {
+                (
+                    audio_buffer.plane_data(c as u32).map_err(|_| ())?,
+                    sample_size,
+                )
+            };
 
-            dest_data.set_len(no_samples as usize * info.channels() as usize);
+            for (i, dest) in dest.iter_mut().enumerate() {
+                *dest = sample(&src[i * sample_stride..]) * scale;
+            }
         }
 
+        let no_samples = no_samples as i32;
         let dest = NDIlib_audio_frame_v3_t {
             sample_rate: info.rate() as i32,
             no_channels: info.channels() as i32,
@@ -1056,12 +1911,15 @@ impl<'a> AudioFrame<'a> {
 }
 
 impl<'a> Drop for AudioFrame<'a> {
-    #[allow(irrefutable_let_patterns)]
     fn drop(&mut self) {
-        if let AudioFrame::BorrowedRecv(ref mut frame, recv) = *self {
-            unsafe {
-                NDIlib_recv_free_audio_v3(recv.0.as_ptr() as *mut _, frame);
-            }
+        match *self {
+            AudioFrame::BorrowedRecv(ref mut frame, recv) => unsafe {
+                backend().recv_free_audio_v3(recv.as_ptr(), frame);
+            },
+            AudioFrame::BorrowedFramesync(ref mut frame, framesync) => unsafe {
+                backend().framesync_free_audio_v2(framesync.as_ptr(), frame);
+            },
+            AudioFrame::Owned(..) | AudioFrame::Mapped(..) => (),
         }
     }
 }
@@ -1080,6 +1938,7 @@ pub struct CompressedPacket<'a> {
 pub enum MetadataFrame<'a> {
     Owned(NDIlib_metadata_frame_t, Option<ffi::CString>),
     Borrowed(NDIlib_metadata_frame_t, &'a RecvInstance),
+    BorrowedSend(NDIlib_metadata_frame_t, &'a SendInstance),
 }
 
 impl<'a> MetadataFrame<'a> {
@@ -1088,9 +1947,10 @@ impl<'a> MetadataFrame<'a> {
 
         MetadataFrame::Owned(
             NDIlib_metadata_frame_t {
+                // Including the NUL terminator
                 length: data
                     .as_ref()
-                    .map(|s| s.to_str().unwrap().len())
+                    .map(|s| s.as_bytes_with_nul().len())
                     .unwrap_or(0) as i32,
                 timecode,
                 p_data: data
@@ -1105,7 +1965,9 @@ impl<'a> MetadataFrame<'a> {
     pub fn timecode(&self) -> i64 {
         match self {
             MetadataFrame::Owned(ref frame, _) => frame.timecode,
-            MetadataFrame::Borrowed(ref frame, _) => frame.timecode,
+            MetadataFrame::Borrowed(ref frame, _) | MetadataFrame::BorrowedSend(ref frame, _) => {
+                frame.timecode
+            }
         }
     }
 
@@ -1115,7 +1977,8 @@ impl<'a> MetadataFrame<'a> {
                 MetadataFrame::Owned(_, ref metadata) => {
                     metadata.as_ref().map(|s| s.to_str().unwrap())
                 }
-                MetadataFrame::Borrowed(ref frame, _) => {
+                MetadataFrame::Borrowed(ref frame, _)
+                | MetadataFrame::BorrowedSend(ref frame, _) => {
                     if frame.p_data.is_null() || frame.length == 0 {
                         None
                     } else if frame.length != 0 {
@@ -1140,7 +2003,9 @@ impl<'a> MetadataFrame<'a> {
     pub fn as_ptr(&self) -> *const NDIlib_metadata_frame_t {
         match self {
             MetadataFrame::Owned(ref frame, _) => frame,
-            MetadataFrame::Borrowed(ref frame, _) => frame,
+            MetadataFrame::Borrowed(ref frame, _) | MetadataFrame::BorrowedSend(ref frame, _) => {
+                frame
+            }
         }
     }
 }
@@ -1160,10 +2025,14 @@ impl<'a> Default for MetadataFrame<'a> {
 
 impl<'a> Drop for MetadataFrame<'a> {
     fn drop(&mut self) {
-        if let MetadataFrame::Borrowed(ref mut frame, recv) = *self {
-            unsafe {
-                NDIlib_recv_free_metadata(recv.0.as_ptr() as *mut _, frame);
-            }
+        match *self {
+            MetadataFrame::Borrowed(ref mut frame, recv) => unsafe {
+                backend().recv_free_metadata(recv.as_ptr(), frame);
+            },
+            MetadataFrame::BorrowedSend(ref frame, send) => unsafe {
+                backend().send_free_metadata(send.0.as_ptr() as *mut _, frame);
+            },
+            MetadataFrame::Owned(..) => (),
         }
     }
 }
@@ -1182,3 +2051,18 @@ impl Queue {
         self.0.metadata_frames
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Performance(NDIlib_recv_performance_t);
+
+impl Performance {
+    pub fn audio_frames(&self) -> i64 {
+        self.0.audio_frames
+    }
+    pub fn video_frames(&self) -> i64 {
+        self.0.video_frames
+    }
+    pub fn metadata_frames(&self) -> i64 {
+        self.0.metadata_frames
+    }
+}
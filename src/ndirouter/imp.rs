@@ -0,0 +1,250 @@
+use glib::subclass::prelude::*;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst::{gst_debug, gst_error, gst_info};
+
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::ndi::{RoutingInstance, Source};
+
+static DEFAULT_ROUTER_NDI_NAME: Lazy<String> = Lazy::new(|| {
+    format!(
+        "GStreamer NDI Router {}-{}",
+        env!("CARGO_PKG_VERSION"),
+        env!("COMMIT_ID")
+    )
+});
+
+#[derive(Debug)]
+struct Settings {
+    ndi_name: String,
+    source_ndi_name: Option<String>,
+    source_url_address: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            ndi_name: DEFAULT_ROUTER_NDI_NAME.clone(),
+            source_ndi_name: None,
+            source_url_address: None,
+        }
+    }
+}
+
+pub struct NdiRouter {
+    settings: Mutex<Settings>,
+    routing: Mutex<Option<RoutingInstance>>,
+}
+
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "ndirouter",
+        gst::DebugColorFlags::empty(),
+        Some("NDI Router"),
+    )
+});
+
+#[glib::object_subclass]
+impl ObjectSubclass for NdiRouter {
+    const NAME: &'static str = "NdiRouter";
+    type Type = super::NdiRouter;
+    type ParentType = gst::Element;
+
+    fn new() -> Self {
+        Self {
+            settings: Mutex::new(Default::default()),
+            routing: Mutex::new(None),
+        }
+    }
+}
+
+impl ObjectImpl for NdiRouter {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpec::new_string(
+                    "ndi-name",
+                    "NDI Name",
+                    "NDI Name under which the routed source is announced",
+                    Some(DEFAULT_ROUTER_NDI_NAME.as_ref()),
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "source-ndi-name",
+                    "Source NDI Name",
+                    "NDI stream name of the source to route",
+                    None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_string(
+                    "source-url-address",
+                    "Source URL/Address",
+                    "URL/address and port of the source to route",
+                    None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(
+        &self,
+        obj: &Self::Type,
+        _id: usize,
+        value: &glib::Value,
+        pspec: &glib::ParamSpec,
+    ) {
+        match pspec.name() {
+            "ndi-name" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.ndi_name = value
+                    .get::<String>()
+                    .unwrap_or_else(|_| DEFAULT_ROUTER_NDI_NAME.clone());
+            }
+            "source-ndi-name" => {
+                let mut settings = self.settings.lock().unwrap();
+                let source_ndi_name = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing source-ndi-name from {:?} to {:?}",
+                    settings.source_ndi_name,
+                    source_ndi_name,
+                );
+                settings.source_ndi_name = source_ndi_name;
+                drop(settings);
+                self.update_route(obj);
+            }
+            "source-url-address" => {
+                let mut settings = self.settings.lock().unwrap();
+                let source_url_address = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing source-url-address from {:?} to {:?}",
+                    settings.source_url_address,
+                    source_url_address,
+                );
+                settings.source_url_address = source_url_address;
+                drop(settings);
+                self.update_route(obj);
+            }
+            _ => unimplemented!(),
+        };
+    }
+
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "ndi-name" => {
+                let settings = self.settings.lock().unwrap();
+                settings.ndi_name.to_value()
+            }
+            "source-ndi-name" => {
+                let settings = self.settings.lock().unwrap();
+                settings.source_ndi_name.to_value()
+            }
+            "source-url-address" => {
+                let settings = self.settings.lock().unwrap();
+                settings.source_url_address.to_value()
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl ElementImpl for NdiRouter {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "NDI Router",
+                "Generic",
+                "Announce an NDI source that forwards another NDI source",
+                "Sebastian Dröge <sebastian@centricular.com>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn change_state(
+        &self,
+        element: &Self::Type,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        if transition == gst::StateChange::NullToReady {
+            if let Err(err) = crate::ndi::initialize() {
+                gst::element_error!(
+                    element,
+                    gst::LibraryError::Init,
+                    ["Failed to initialize NDI SDK: {}", err]
+                );
+                return Err(gst::StateChangeError);
+            }
+
+            let ndi_name = self.settings.lock().unwrap().ndi_name.clone();
+            let routing = match RoutingInstance::new(&ndi_name) {
+                Some(routing) => routing,
+                None => {
+                    gst::element_error!(
+                        element,
+                        gst::ResourceError::OpenWrite,
+                        ["Could not create routing instance"]
+                    );
+                    return Err(gst::StateChangeError);
+                }
+            };
+
+            gst_info!(CAT, obj: element, "Created routing instance {}", ndi_name);
+            *self.routing.lock().unwrap() = Some(routing);
+            self.update_route(element);
+        }
+
+        let res = self.parent_change_state(element, transition)?;
+
+        if transition == gst::StateChange::ReadyToNull {
+            let _ = self.routing.lock().unwrap().take();
+            gst_info!(CAT, obj: element, "Destroyed routing instance");
+        }
+
+        Ok(res)
+    }
+}
+
+impl NdiRouter {
+    // Routes to the source from the settings, or clears the route if neither is set
+    fn update_route(&self, element: &super::NdiRouter) {
+        let routing = self.routing.lock().unwrap();
+        let routing = match *routing {
+            Some(ref routing) => routing,
+            None => return,
+        };
+
+        let settings = self.settings.lock().unwrap();
+        let res = if settings.source_ndi_name.is_none() && settings.source_url_address.is_none() {
+            gst_debug!(CAT, obj: element, "Clearing route");
+            routing.clear()
+        } else {
+            let source = Source::new(
+                settings.source_ndi_name.as_deref(),
+                settings.source_url_address.as_deref(),
+            );
+            gst_debug!(
+                CAT,
+                obj: element,
+                "Routing to {:?} ({:?})",
+                settings.source_ndi_name,
+                settings.source_url_address,
+            );
+            routing.change(&source)
+        };
+
+        if !res {
+            gst_error!(CAT, obj: element, "Failed to change route");
+        }
+    }
+}
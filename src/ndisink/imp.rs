@@ -1,15 +1,18 @@
 use glib::subclass::prelude::*;
 use gst::prelude::*;
 use gst::subclass::prelude::*;
-use gst::{gst_debug, gst_error, gst_info, gst_trace};
+use gst::{gst_debug, gst_error, gst_info, gst_trace, gst_warning};
 use gst_base::prelude::*;
 use gst_base::subclass::prelude::*;
 
-use std::sync::Mutex;
+use std::ffi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 use once_cell::sync::Lazy;
 
-use crate::ndi::SendInstance;
+use crate::ndi::{MetadataFrame, SendInstance, Source, Tally};
 
 static DEFAULT_SENDER_NDI_NAME: Lazy<String> = Lazy::new(|| {
     format!(
@@ -22,25 +25,53 @@ static DEFAULT_SENDER_NDI_NAME: Lazy<String> = Lazy::new(|| {
 #[derive(Debug)]
 struct Settings {
     ndi_name: String,
+    groups: Option<String>,
+    failover_ndi_name: Option<String>,
+    failover_url_address: Option<String>,
+    connection_metadata: Option<String>,
+    wait_for_connection: bool,
+    audio_reference_level: i32,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             ndi_name: DEFAULT_SENDER_NDI_NAME.clone(),
+            groups: None,
+            failover_ndi_name: None,
+            failover_url_address: None,
+            connection_metadata: None,
+            wait_for_connection: false,
+            audio_reference_level: 0,
         }
     }
 }
 
 struct State {
-    send: SendInstance,
+    send: Arc<SendInstance>,
     video_info: Option<gst_video::VideoInfo>,
     audio_info: Option<gst_audio::AudioInfo>,
+    capture_thread: Option<CaptureThread>,
+}
+
+// Thread receiving metadata and tally from the receivers connected to this sender
+struct CaptureThread {
+    shutdown: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl CaptureThread {
+    fn shutdown(self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
 }
 
 pub struct NdiSink {
     settings: Mutex<Settings>,
     state: Mutex<Option<State>>,
+    tally: Mutex<Tally>,
+    connections: Mutex<u32>,
 }
 
 static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
@@ -57,6 +88,8 @@ impl ObjectSubclass for NdiSink {
         Self {
             settings: Mutex::new(Default::default()),
             state: Mutex::new(Default::default()),
+            tally: Mutex::new(Tally::new(false, false)),
+            connections: Mutex::new(0),
         }
     }
 }
@@ -64,13 +97,82 @@ impl ObjectSubclass for NdiSink {
 impl ObjectImpl for NdiSink {
     fn properties() -> &'static [glib::ParamSpec] {
         static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-            vec![glib::ParamSpec::new_string(
-                "ndi-name",
-                "NDI Name",
-                "NDI Name to use",
-                Some(DEFAULT_SENDER_NDI_NAME.as_ref()),
-                glib::ParamFlags::READWRITE,
-            )]
+            vec![
+                glib::ParamSpec::new_string(
+                    "ndi-name",
+                    "NDI Name",
+                    "NDI Name to use",
+                    Some(DEFAULT_SENDER_NDI_NAME.as_ref()),
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "groups",
+                    "Groups",
+                    "Comma separated list of NDI groups to announce the source in (default: public)",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "failover-ndi-name",
+                    "Failover NDI Name",
+                    "NDI stream name of the source receivers switch to if this sender goes away",
+                    None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_string(
+                    "failover-url-address",
+                    "Failover URL/Address",
+                    "URL/address and port of the source receivers switch to if this sender goes away",
+                    None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_string(
+                    "connection-metadata",
+                    "Connection Metadata",
+                    "XML, or a GstStructure converted to an XML element, sent to every receiver when it connects",
+                    None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "on-program",
+                    "On Program",
+                    "Whether any receiver signals program tally",
+                    false,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "on-preview",
+                    "On Preview",
+                    "Whether any receiver signals preview tally",
+                    false,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_uint(
+                    "connections",
+                    "Connections",
+                    "Number of receivers connected",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "wait-for-connection",
+                    "Wait For Connection",
+                    "Drop frames instead of sending them while no receiver is connected",
+                    false,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_int(
+                    "audio-reference-level",
+                    "Audio Reference Level",
+                    "Level in dB above the +4 dBu NDI reference level that full scale integer audio maps to",
+                    -100,
+                    100,
+                    0,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+            ]
         });
 
         PROPERTIES.as_ref()
@@ -78,7 +180,7 @@ impl ObjectImpl for NdiSink {
 
     fn set_property(
         &self,
-        _obj: &Self::Type,
+        obj: &Self::Type,
         _id: usize,
         value: &glib::Value,
         pspec: &glib::ParamSpec,
@@ -90,6 +192,44 @@ impl ObjectImpl for NdiSink {
                     .get::<String>()
                     .unwrap_or_else(|_| DEFAULT_SENDER_NDI_NAME.clone());
             }
+            "groups" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.groups = value.get().unwrap();
+            }
+            "failover-ndi-name" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.failover_ndi_name = value.get().unwrap();
+                drop(settings);
+                self.update_failover(obj);
+            }
+            "failover-url-address" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.failover_url_address = value.get().unwrap();
+                drop(settings);
+                self.update_failover(obj);
+            }
+            "connection-metadata" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.connection_metadata = value.get().unwrap();
+                drop(settings);
+
+                let state = self.state.lock().unwrap();
+                if let Some(ref state) = *state {
+                    let settings = self.settings.lock().unwrap();
+                    state.send.clear_connection_metadata();
+                    if Self::set_connection_metadata(&state.send, &settings).is_err() {
+                        gst_error!(CAT, obj: obj, "Invalid connection metadata");
+                    }
+                }
+            }
+            "wait-for-connection" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.wait_for_connection = value.get().unwrap();
+            }
+            "audio-reference-level" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.audio_reference_level = value.get().unwrap();
+            }
             _ => unimplemented!(),
         };
     }
@@ -100,6 +240,42 @@ impl ObjectImpl for NdiSink {
                 let settings = self.settings.lock().unwrap();
                 settings.ndi_name.to_value()
             }
+            "groups" => {
+                let settings = self.settings.lock().unwrap();
+                settings.groups.to_value()
+            }
+            "failover-ndi-name" => {
+                let settings = self.settings.lock().unwrap();
+                settings.failover_ndi_name.to_value()
+            }
+            "failover-url-address" => {
+                let settings = self.settings.lock().unwrap();
+                settings.failover_url_address.to_value()
+            }
+            "connection-metadata" => {
+                let settings = self.settings.lock().unwrap();
+                settings.connection_metadata.to_value()
+            }
+            "on-program" => {
+                let tally = self.tally.lock().unwrap();
+                tally.on_program().to_value()
+            }
+            "on-preview" => {
+                let tally = self.tally.lock().unwrap();
+                tally.on_preview().to_value()
+            }
+            "connections" => {
+                let connections = self.connections.lock().unwrap();
+                connections.to_value()
+            }
+            "wait-for-connection" => {
+                let settings = self.settings.lock().unwrap();
+                settings.wait_for_connection.to_value()
+            }
+            "audio-reference-level" => {
+                let settings = self.settings.lock().unwrap();
+                settings.audio_reference_level.to_value()
+            }
             _ => unimplemented!(),
         }
     }
@@ -136,6 +312,21 @@ impl ElementImpl for NdiSink {
                                 &gst_video::VideoFormat::Bgrx.to_str(),
                                 &gst_video::VideoFormat::Rgba.to_str(),
                                 &gst_video::VideoFormat::Rgbx.to_str(),
+                                &gst_video::VideoFormat::Ayuv.to_str(),
+                                &gst_video::VideoFormat::A420.to_str(),
+                                &gst_video::VideoFormat::Ayuv64.to_str(),
+                                &gst_video::VideoFormat::A44410le.to_str(),
+                                &gst_video::VideoFormat::A42210le.to_str(),
+                                &gst_video::VideoFormat::A42010le.to_str(),
+                                &gst_video::VideoFormat::V210.to_str(),
+                                &gst_video::VideoFormat::Y210.to_str(),
+                                &gst_video::VideoFormat::Y44412le.to_str(),
+                                &gst_video::VideoFormat::I42212le.to_str(),
+                                &gst_video::VideoFormat::I42012le.to_str(),
+                                &gst_video::VideoFormat::Y44410le.to_str(),
+                                &gst_video::VideoFormat::I42210le.to_str(),
+                                &gst_video::VideoFormat::I42010le.to_str(),
+                                &gst_video::VideoFormat::P01010le.to_str(),
                             ]),
                         )
                         .field("width", &gst::IntRange::<i32>::new(1, std::i32::MAX))
@@ -151,10 +342,21 @@ impl ElementImpl for NdiSink {
                 )
                 .structure(
                     gst::Structure::builder("audio/x-raw")
-                        .field("format", &gst_audio::AUDIO_FORMAT_F32.to_str())
+                        .field(
+                            "format",
+                            &gst::List::new(&[
+                                &gst_audio::AUDIO_FORMAT_F32.to_str(),
+                                &gst_audio::AUDIO_FORMAT_S32.to_str(),
+                                &gst_audio::AUDIO_FORMAT_S24.to_str(),
+                                &gst_audio::AUDIO_FORMAT_S16.to_str(),
+                            ]),
+                        )
                         .field("rate", &gst::IntRange::<i32>::new(1, i32::MAX))
                         .field("channels", &gst::IntRange::<i32>::new(1, i32::MAX))
-                        .field("layout", &"interleaved")
+                        .field(
+                            "layout",
+                            &gst::List::new(&[&"interleaved", &"non-interleaved"]),
+                        )
                         .build(),
                 )
                 .build();
@@ -178,19 +380,58 @@ impl BaseSinkImpl for NdiSink {
         let mut state_storage = self.state.lock().unwrap();
         let settings = self.settings.lock().unwrap();
 
-        let send = SendInstance::builder(&settings.ndi_name)
-            .build()
-            .ok_or_else(|| {
-                gst::error_msg!(
-                    gst::ResourceError::OpenWrite,
-                    ["Could not create send instance"]
-                )
-            })?;
+        crate::ndi::initialize().map_err(|err| {
+            gst::error_msg!(
+                gst::LibraryError::Init,
+                ["Failed to initialize NDI SDK: {}", err]
+            )
+        })?;
+
+        let mut builder = SendInstance::builder(&settings.ndi_name);
+        if let Some(ref groups) = settings.groups {
+            builder = builder.groups(groups);
+        }
+        let send = builder.build().ok_or_else(|| {
+            gst::error_msg!(
+                gst::ResourceError::OpenWrite,
+                ["Could not create send instance"]
+            )
+        })?;
+
+        Self::set_failover(element, &send, &settings);
+        Self::set_connection_metadata(&send, &settings).map_err(|_| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                [
+                    "Invalid connection metadata {:?}",
+                    settings.connection_metadata
+                ]
+            )
+        })?;
+
+        let send = Arc::new(send);
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let element_weak = element.downgrade();
+            let send = send.clone();
+            let shutdown = shutdown.clone();
+            thread::Builder::new()
+                .name("ndisink-capture".into())
+                .spawn(move || Self::capture_thread(&element_weak, &send, &shutdown))
+                .map_err(|err| {
+                    gst::error_msg!(
+                        gst::CoreError::Thread,
+                        ["Failed to spawn capture thread: {}", err]
+                    )
+                })?
+        };
 
         let state = State {
             send,
             video_info: None,
             audio_info: None,
+            capture_thread: Some(CaptureThread { shutdown, handle }),
         };
         *state_storage = Some(state);
         gst_info!(CAT, obj: element, "Started");
@@ -201,7 +442,14 @@ impl BaseSinkImpl for NdiSink {
     fn stop(&self, element: &Self::Type) -> Result<(), gst::ErrorMessage> {
         let mut state_storage = self.state.lock().unwrap();
 
-        *state_storage = None;
+        let state = state_storage.take();
+        drop(state_storage);
+
+        if let Some(capture_thread) = state.and_then(|mut state| state.capture_thread.take()) {
+            capture_thread.shutdown();
+        }
+        self.update_tally(element, Tally::new(false, false));
+        self.update_connections(element, 0);
         gst_info!(CAT, obj: element, "Stopped");
 
         Ok(())
@@ -247,6 +495,21 @@ impl BaseSinkImpl for NdiSink {
         element: &Self::Type,
         buffer: &gst::Buffer,
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (wait_for_connection, audio_reference_level) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.wait_for_connection, settings.audio_reference_level)
+        };
+
+        if wait_for_connection && *self.connections.lock().unwrap() == 0 {
+            gst_trace!(
+                CAT,
+                obj: element,
+                "Dropping buffer {:?} without connections",
+                buffer
+            );
+            return Ok(gst::FlowSuccess::Ok);
+        }
+
         let mut state_storage = self.state.lock().unwrap();
         let state = match &mut *state_storage {
             None => return Err(gst::FlowError::Error),
@@ -256,11 +519,16 @@ impl BaseSinkImpl for NdiSink {
         if let Some(ref info) = state.video_info {
             if let Some(audio_meta) = buffer.meta::<crate::ndisinkmeta::NdiSinkAudioMeta>() {
                 for (buffer, info, timecode) in audio_meta.buffers() {
-                    let frame = crate::ndi::AudioFrame::try_from_buffer(info, buffer, *timecode)
-                        .map_err(|_| {
-                            gst_error!(CAT, obj: element, "Unsupported audio frame");
-                            gst::FlowError::NotNegotiated
-                        })?;
+                    let frame = crate::ndi::AudioFrame::try_from_buffer(
+                        info,
+                        buffer,
+                        *timecode,
+                        audio_reference_level,
+                    )
+                    .map_err(|_| {
+                        gst_error!(CAT, obj: element, "Unsupported audio frame");
+                        gst::FlowError::NotNegotiated
+                    })?;
 
                     gst_trace!(
                         CAT,
@@ -278,6 +546,43 @@ impl BaseSinkImpl for NdiSink {
                 }
             }
 
+            if let Some(metadata_meta) = buffer.meta::<crate::ndisinkmeta::NdiSinkMetadataMeta>() {
+                for (buffer, timecode) in metadata_meta.buffers() {
+                    let map = buffer.map_readable().map_err(|_| {
+                        gst_error!(CAT, obj: element, "Failed to map buffer");
+                        gst::FlowError::Error
+                    })?;
+
+                    let metadata = match std::str::from_utf8(&map) {
+                        Ok(metadata) => metadata.trim_end_matches('\0'),
+                        Err(_) => {
+                            gst_warning!(CAT, obj: element, "Dropping non-UTF-8 metadata");
+                            continue;
+                        }
+                    };
+                    if metadata.is_empty() || metadata.contains('\0') {
+                        gst_warning!(CAT, obj: element, "Dropping invalid metadata");
+                        continue;
+                    }
+
+                    let frame = crate::ndi::MetadataFrame::new(*timecode, Some(metadata));
+
+                    gst_trace!(
+                        CAT,
+                        obj: element,
+                        "Sending metadata buffer {:?} with timecode {}: {}",
+                        buffer,
+                        if *timecode < 0 {
+                            gst::ClockTime::NONE.display()
+                        } else {
+                            Some(gst::ClockTime::from_nseconds(*timecode as u64 * 100)).display()
+                        },
+                        metadata,
+                    );
+                    state.send.send_metadata(&frame);
+                }
+            }
+
             // Skip empty/gap buffers from ndisinkcombiner
             if buffer.size() != 0 {
                 let timecode = element
@@ -299,11 +604,25 @@ impl BaseSinkImpl for NdiSink {
                         gst::FlowError::Error
                     })?;
 
-                let frame = crate::ndi::VideoFrame::try_from_video_frame(&frame, timecode)
-                    .map_err(|_| {
-                        gst_error!(CAT, obj: element, "Unsupported video frame");
-                        gst::FlowError::NotNegotiated
-                    })?;
+                let frame_metadata = buffer
+                    .meta::<crate::ndisinkmeta::NdiSinkFrameMetadataMeta>()
+                    .and_then(|meta| match ffi::CString::new(meta.metadata()) {
+                        Ok(metadata) => Some(metadata),
+                        Err(_) => {
+                            gst_warning!(CAT, obj: element, "Dropping invalid frame metadata");
+                            None
+                        }
+                    });
+
+                let frame = crate::ndi::VideoFrame::try_from_video_frame(
+                    &frame,
+                    timecode,
+                    frame_metadata.as_deref(),
+                )
+                .map_err(|_| {
+                    gst_error!(CAT, obj: element, "Unsupported video frame");
+                    gst::FlowError::NotNegotiated
+                })?;
 
                 gst_trace!(
                     CAT,
@@ -333,11 +652,16 @@ impl BaseSinkImpl for NdiSink {
                 .map(|time| (time.nseconds() / 100) as i64)
                 .unwrap_or(crate::ndisys::NDIlib_send_timecode_synthesize);
 
-            let frame =
-                crate::ndi::AudioFrame::try_from_buffer(info, buffer, timecode).map_err(|_| {
-                    gst_error!(CAT, obj: element, "Unsupported audio frame");
-                    gst::FlowError::NotNegotiated
-                })?;
+            let frame = crate::ndi::AudioFrame::try_from_buffer(
+                info,
+                buffer,
+                timecode,
+                audio_reference_level,
+            )
+            .map_err(|_| {
+                gst_error!(CAT, obj: element, "Unsupported audio frame");
+                gst::FlowError::NotNegotiated
+            })?;
 
             gst_trace!(
                 CAT,
@@ -359,3 +683,165 @@ impl BaseSinkImpl for NdiSink {
         Ok(gst::FlowSuccess::Ok)
     }
 }
+
+impl NdiSink {
+    fn set_failover(element: &super::NdiSink, send: &SendInstance, settings: &Settings) {
+        if settings.failover_ndi_name.is_none() && settings.failover_url_address.is_none() {
+            send.set_failover(None);
+            return;
+        }
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Setting failover source with NDI name {:?} and URL/address {:?}",
+            settings.failover_ndi_name,
+            settings.failover_url_address,
+        );
+        send.set_failover(Some(&Source::new(
+            settings.failover_ndi_name.as_deref(),
+            settings.failover_url_address.as_deref(),
+        )));
+    }
+
+    fn set_connection_metadata(send: &SendInstance, settings: &Settings) -> Result<(), ()> {
+        let metadata = match settings.connection_metadata {
+            Some(ref metadata) => metadata,
+            None => return Ok(()),
+        };
+
+        let xml = crate::metadata_to_xml(metadata).ok_or(())?;
+        send.add_connection_metadata(&MetadataFrame::new(0, Some(&xml)));
+
+        Ok(())
+    }
+
+    fn update_failover(&self, element: &super::NdiSink) {
+        let state = self.state.lock().unwrap();
+        if let Some(ref state) = *state {
+            let settings = self.settings.lock().unwrap();
+            Self::set_failover(element, &state.send, &settings);
+        }
+    }
+
+    fn update_tally(&self, element: &super::NdiSink, tally: Tally) {
+        let mut current_tally = self.tally.lock().unwrap();
+        let program_changed = current_tally.on_program() != tally.on_program();
+        let preview_changed = current_tally.on_preview() != tally.on_preview();
+        if !program_changed && !preview_changed {
+            return;
+        }
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Tally changed from {:?} to {:?}",
+            *current_tally,
+            tally
+        );
+        *current_tally = tally;
+        drop(current_tally);
+
+        if program_changed {
+            element.notify("on-program");
+        }
+        if preview_changed {
+            element.notify("on-preview");
+        }
+
+        let _ = element.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("tally-changed")
+                    .field("on-program", &tally.on_program())
+                    .field("on-preview", &tally.on_preview())
+                    .build(),
+            )
+            .src(element)
+            .build(),
+        );
+    }
+
+    fn update_connections(&self, element: &super::NdiSink, connections: u32) {
+        let mut current_connections = self.connections.lock().unwrap();
+        if *current_connections == connections {
+            return;
+        }
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Connections changed from {} to {}",
+            *current_connections,
+            connections
+        );
+        *current_connections = connections;
+        drop(current_connections);
+
+        element.notify("connections");
+    }
+
+    fn capture_thread(
+        element_weak: &glib::WeakRef<super::NdiSink>,
+        send: &SendInstance,
+        shutdown: &AtomicBool,
+    ) {
+        while !shutdown.load(Ordering::SeqCst) {
+            let res = send.capture(100);
+
+            let element = match element_weak.upgrade() {
+                Some(element) => element,
+                None => break,
+            };
+
+            let sink = Self::from_instance(&element);
+            if let Some(tally) = send.get_tally(0) {
+                sink.update_tally(&element, tally);
+            }
+            sink.update_connections(&element, send.get_no_connections(0).max(0) as u32);
+
+            let frame = match res {
+                Ok(Some(frame)) => frame,
+                Ok(None) => continue,
+                Err(_) => {
+                    gst_error!(CAT, obj: &element, "Error capturing metadata");
+                    break;
+                }
+            };
+
+            let metadata = match frame.metadata() {
+                Some(metadata) => metadata.trim_end_matches('\0'),
+                None => continue,
+            };
+
+            // Negative and synthesized timecodes don't correspond to a time
+            let timecode = Some(frame.timecode())
+                .filter(|timecode| *timecode >= 0)
+                .and_then(|timecode| (timecode as u64).checked_mul(100))
+                .map(gst::ClockTime::from_nseconds);
+
+            gst_debug!(
+                CAT,
+                obj: &element,
+                "Received metadata from receiver at timecode {}: {}",
+                timecode.display(),
+                metadata,
+            );
+
+            let mut s = gst::Structure::builder("ndi-receiver-metadata")
+                .field("metadata", &metadata)
+                .build();
+            if let Some(timecode) = timecode {
+                s.set("timecode", timecode);
+            }
+
+            let _ = element.post_message(
+                gst::message::Element::builder(s.clone())
+                    .src(&element)
+                    .build(),
+            );
+
+            let sinkpad = element.static_pad("sink").unwrap();
+            sinkpad.push_event(gst::event::CustomUpstream::new(s));
+        }
+    }
+}
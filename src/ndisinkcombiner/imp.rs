@@ -26,11 +26,13 @@ struct State {
     audio_info: Option<gst_audio::AudioInfo>,
     current_video_buffer: Option<(gst::Buffer, gst::ClockTime)>,
     current_audio_buffers: Vec<(gst::Buffer, gst_audio::AudioInfo, i64)>,
+    current_metadata_buffers: Vec<(gst::Buffer, i64)>,
 }
 
 pub struct NdiSinkCombiner {
     video_pad: gst_base::AggregatorPad,
     audio_pad: Mutex<Option<gst_base::AggregatorPad>>,
+    metadata_pad: Mutex<Option<gst_base::AggregatorPad>>,
     state: Mutex<Option<State>>,
 }
 
@@ -49,6 +51,7 @@ impl ObjectSubclass for NdiSinkCombiner {
         Self {
             video_pad,
             audio_pad: Mutex::new(None),
+            metadata_pad: Mutex::new(None),
             state: Mutex::new(None),
         }
     }
@@ -67,8 +70,8 @@ impl ElementImpl for NdiSinkCombiner {
         static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
             gst::subclass::ElementMetadata::new(
                 "NDI Sink Combiner",
-                "Combiner/Audio/Video",
-                "NDI sink audio/video combiner",
+                "Combiner/Audio/Video/Metadata",
+                "NDI sink audio/video/metadata combiner",
                 "Sebastian Dröge <sebastian@centricular.com>",
             )
         });
@@ -91,6 +94,21 @@ impl ElementImpl for NdiSinkCombiner {
                         &gst_video::VideoFormat::Bgrx.to_str(),
                         &gst_video::VideoFormat::Rgba.to_str(),
                         &gst_video::VideoFormat::Rgbx.to_str(),
+                        &gst_video::VideoFormat::Ayuv.to_str(),
+                        &gst_video::VideoFormat::A420.to_str(),
+                        &gst_video::VideoFormat::Ayuv64.to_str(),
+                        &gst_video::VideoFormat::A44410le.to_str(),
+                        &gst_video::VideoFormat::A42210le.to_str(),
+                        &gst_video::VideoFormat::A42010le.to_str(),
+                        &gst_video::VideoFormat::V210.to_str(),
+                        &gst_video::VideoFormat::Y210.to_str(),
+                        &gst_video::VideoFormat::Y44412le.to_str(),
+                        &gst_video::VideoFormat::I42212le.to_str(),
+                        &gst_video::VideoFormat::I42012le.to_str(),
+                        &gst_video::VideoFormat::Y44410le.to_str(),
+                        &gst_video::VideoFormat::I42210le.to_str(),
+                        &gst_video::VideoFormat::I42010le.to_str(),
+                        &gst_video::VideoFormat::P01010le.to_str(),
                     ]),
                 )
                 .field("width", &gst::IntRange::<i32>::new(1, i32::MAX))
@@ -122,10 +140,21 @@ impl ElementImpl for NdiSinkCombiner {
             .unwrap();
 
             let caps = gst::Caps::builder("audio/x-raw")
-                .field("format", &gst_audio::AUDIO_FORMAT_F32.to_str())
+                .field(
+                    "format",
+                    &gst::List::new(&[
+                        &gst_audio::AUDIO_FORMAT_F32.to_str(),
+                        &gst_audio::AUDIO_FORMAT_S32.to_str(),
+                        &gst_audio::AUDIO_FORMAT_S24.to_str(),
+                        &gst_audio::AUDIO_FORMAT_S16.to_str(),
+                    ]),
+                )
                 .field("rate", &gst::IntRange::<i32>::new(1, i32::MAX))
                 .field("channels", &gst::IntRange::<i32>::new(1, i32::MAX))
-                .field("layout", &"interleaved")
+                .field(
+                    "layout",
+                    &gst::List::new(&[&"interleaved", &"non-interleaved"]),
+                )
                 .build();
             let audio_sink_pad_template = gst::PadTemplate::with_gtype(
                 "audio",
@@ -135,10 +164,21 @@ impl ElementImpl for NdiSinkCombiner {
                 gst_base::AggregatorPad::static_type(),
             )
             .unwrap();
+
+            let metadata_sink_pad_template = gst::PadTemplate::with_gtype(
+                "metadata",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Request,
+                &gst::Caps::builder("application/x-ndi-metadata").build(),
+                gst_base::AggregatorPad::static_type(),
+            )
+            .unwrap();
+
             vec![
                 src_pad_template,
                 video_sink_pad_template,
                 audio_sink_pad_template,
+                metadata_sink_pad_template,
             ]
         });
 
@@ -152,6 +192,16 @@ impl ElementImpl for NdiSinkCombiner {
             gst_debug!(CAT, obj: element, "Release audio pad");
             self.parent_release_pad(element, pad);
             *audio_pad_storage = None;
+            return;
+        }
+        drop(audio_pad_storage);
+
+        let mut metadata_pad_storage = self.metadata_pad.lock().unwrap();
+
+        if metadata_pad_storage.as_ref().map(|p| p.upcast_ref()) == Some(pad) {
+            gst_debug!(CAT, obj: element, "Release metadata pad");
+            self.parent_release_pad(element, pad);
+            *metadata_pad_storage = None;
         }
     }
 }
@@ -164,24 +214,27 @@ impl AggregatorImpl for NdiSinkCombiner {
         _req_name: Option<&str>,
         _caps: Option<&gst::Caps>,
     ) -> Option<gst_base::AggregatorPad> {
-        let mut audio_pad_storage = self.audio_pad.lock().unwrap();
-
-        if audio_pad_storage.is_some() {
-            gst_error!(CAT, obj: agg, "Audio pad already requested");
+        let (pad_storage, name) = if templ == &agg.pad_template("audio").unwrap() {
+            (&self.audio_pad, "audio")
+        } else if templ == &agg.pad_template("metadata").unwrap() {
+            (&self.metadata_pad, "metadata")
+        } else {
+            gst_error!(CAT, obj: agg, "Wrong pad template");
             return None;
-        }
+        };
 
-        let sink_templ = agg.pad_template("audio").unwrap();
-        if templ != &sink_templ {
-            gst_error!(CAT, obj: agg, "Wrong pad template");
+        let mut pad_storage = pad_storage.lock().unwrap();
+
+        if pad_storage.is_some() {
+            gst_error!(CAT, obj: agg, "{} pad already requested", name);
             return None;
         }
 
         let pad =
-            gst::PadBuilder::<gst_base::AggregatorPad>::from_template(templ, Some("audio")).build();
-        *audio_pad_storage = Some(pad.clone());
+            gst::PadBuilder::<gst_base::AggregatorPad>::from_template(templ, Some(name)).build();
+        *pad_storage = Some(pad.clone());
 
-        gst_debug!(CAT, obj: agg, "Requested audio pad");
+        gst_debug!(CAT, obj: agg, "Requested {} pad", name);
 
         Some(pad)
     }
@@ -193,6 +246,7 @@ impl AggregatorImpl for NdiSinkCombiner {
             video_info: None,
             current_video_buffer: None,
             current_audio_buffers: Vec::new(),
+            current_metadata_buffers: Vec::new(),
         });
 
         gst_debug!(CAT, obj: agg, "Started");
@@ -210,8 +264,24 @@ impl AggregatorImpl for NdiSinkCombiner {
     }
 
     fn next_time(&self, _agg: &Self::Type) -> Option<gst::ClockTime> {
-        // FIXME: What to do here? We don't really know when the next buffer is expected
-        gst::ClockTime::NONE
+        // In live pipelines the sparse metadata pad must not hold back the video, so once the
+        // next video buffer and audio are there the aggregator times out at the start of that
+        // video buffer plus latency instead of waiting for metadata. Otherwise we don't really
+        // know when the next buffer is expected.
+        self.metadata_pad.lock().unwrap().as_ref()?;
+
+        if let Some(audio_pad) = self.audio_pad.lock().unwrap().clone() {
+            if !audio_pad.has_buffer() && !audio_pad.is_eos() {
+                return gst::ClockTime::NONE;
+            }
+        }
+
+        let video_buffer = self.video_pad.peek_buffer()?;
+        self.video_pad
+            .segment()
+            .downcast::<gst::ClockTime>()
+            .ok()?
+            .to_running_time(video_buffer.pts())
     }
 
     fn clip(
@@ -234,6 +304,11 @@ impl AggregatorImpl for NdiSinkCombiner {
             return Some(buffer);
         }
 
+        // Metadata is only dropped if it starts outside the segment
+        if self.metadata_pad.lock().unwrap().as_ref() == Some(agg_pad) {
+            return segment.clip(pts, pts).map(|_| buffer);
+        }
+
         let duration = buffer.duration();
 
         gst_trace!(
@@ -315,9 +390,9 @@ impl AggregatorImpl for NdiSinkCombiner {
         agg: &Self::Type,
         timeout: bool,
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
-        // FIXME: Can't really happen because we always return NONE from get_next_time() but that
-        // should be improved!
-        assert!(!timeout);
+        // Timeouts only happen while waiting for metadata, which is handled below without
+        // waiting for it
+        gst_trace!(CAT, obj: agg, "Aggregating, timeout {}", timeout);
 
         // Because peek_buffer() can call into clip() and that would take the state lock again,
         // first try getting buffers from both pads here
@@ -382,6 +457,10 @@ impl AggregatorImpl for NdiSinkCombiner {
             audio_buffer_segment_and_pad = None;
         }
 
+        // Don't keep the pad locked here as peek_buffer() can call into clip(), which also
+        // checks the metadata pad
+        let metadata_pad = self.metadata_pad.lock().unwrap().clone();
+
         let mut state_storage = self.state.lock().unwrap();
         let state = match &mut *state_storage {
             Some(ref mut state) => state,
@@ -407,7 +486,31 @@ impl AggregatorImpl for NdiSinkCombiner {
                     ),
                 }
             } else {
-                match (&state.current_video_buffer, &audio_buffer_segment_and_pad) {
+                let running_time = audio_buffer_segment_and_pad
+                    .as_ref()
+                    .map(|(audio_buffer, audio_segment, _)| {
+                        audio_segment.to_running_time(audio_buffer.pts()).unwrap()
+                    })
+                    .or_else(|| {
+                        let metadata_pad = metadata_pad.as_ref()?;
+                        let metadata_buffer = metadata_pad.peek_buffer()?;
+                        metadata_pad
+                            .segment()
+                            .downcast::<gst::ClockTime>()
+                            .ok()?
+                            .to_running_time(metadata_buffer.pts())
+                    });
+
+                match (&state.current_video_buffer, running_time) {
+                    (None, None)
+                        if metadata_pad
+                            .as_ref()
+                            .map(|pad| !pad.is_eos())
+                            .unwrap_or(false) =>
+                    {
+                        gst_trace!(CAT, obj: agg, "Waiting for metadata buffer or EOS");
+                        return Err(gst_base::AGGREGATOR_FLOW_NEED_DATA);
+                    }
                     (None, None) => {
                         gst_trace!(
                             CAT,
@@ -416,11 +519,9 @@ impl AggregatorImpl for NdiSinkCombiner {
                         );
                         return Err(gst::FlowError::Eos);
                     }
-                    (None, Some((ref audio_buffer, ref audio_segment, _))) => {
-                        // Create an empty dummy buffer for attaching the audio. This is going to
-                        // be dropped by the sink later.
-                        let audio_running_time =
-                            audio_segment.to_running_time(audio_buffer.pts()).unwrap();
+                    (None, Some(running_time)) => {
+                        // Create an empty dummy buffer for attaching the audio or metadata. This
+                        // is going to be dropped by the sink later.
 
                         let video_segment = self.video_pad.segment();
                         let video_segment = match video_segment.downcast::<gst::ClockTime>() {
@@ -435,10 +536,9 @@ impl AggregatorImpl for NdiSinkCombiner {
                                 return Err(gst::FlowError::Error);
                             }
                         };
-                        let video_pts =
-                            video_segment.position_from_running_time(audio_running_time);
+                        let video_pts = video_segment.position_from_running_time(running_time);
                         if video_pts.is_none() {
-                            gst_warning!(CAT, obj: agg, "Can't output more audio after video EOS");
+                            gst_warning!(CAT, obj: agg, "Can't output more data after video EOS");
                             return Err(gst::FlowError::Eos);
                         }
 
@@ -454,6 +554,63 @@ impl AggregatorImpl for NdiSinkCombiner {
                 }
             };
 
+        // Metadata is sparse, so all metadata that is queued until the end of the current video
+        // buffer is included but the metadata pad is never waited for
+        if let Some(ref metadata_pad) = metadata_pad {
+            while let Some(metadata_buffer) = metadata_pad.peek_buffer() {
+                if metadata_buffer.size() == 0 {
+                    // Skip empty/gap metadata buffer
+                    metadata_pad.drop_buffer();
+                    continue;
+                }
+
+                let metadata_segment = metadata_pad.segment();
+                let metadata_segment = match metadata_segment.downcast::<gst::ClockTime>() {
+                    Ok(metadata_segment) => metadata_segment,
+                    Err(metadata_segment) => {
+                        gst_error!(
+                            CAT,
+                            obj: agg,
+                            "Metadata segment of wrong format {:?}",
+                            metadata_segment.format()
+                        );
+                        return Err(gst::FlowError::Error);
+                    }
+                };
+
+                let metadata_running_time = metadata_segment.to_running_time(metadata_buffer.pts());
+                if !metadata_running_time
+                    .zip(current_video_running_time_end)
+                    .map(|(metadata, video)| metadata < video)
+                    .unwrap_or(true)
+                {
+                    break;
+                }
+
+                let timecode = agg
+                    .base_time()
+                    .zip(metadata_running_time)
+                    .map(|(base_time, metadata_running_time)| {
+                        ((base_time.nseconds() + metadata_running_time.nseconds()) / 100) as i64
+                    })
+                    .unwrap_or(crate::ndisys::NDIlib_send_timecode_synthesize);
+
+                gst_trace!(
+                    CAT,
+                    obj: agg,
+                    "Including metadata buffer {:?} with timecode {}: {} < {}",
+                    metadata_buffer,
+                    timecode,
+                    metadata_running_time.display(),
+                    current_video_running_time_end.display(),
+                );
+                state
+                    .current_metadata_buffers
+                    .push((metadata_buffer, timecode));
+                metadata_pad.drop_buffer();
+            }
+        }
+
         if let Some((audio_buffer, audio_segment, audio_pad)) = audio_buffer_segment_and_pad {
             let audio_info = match state.audio_info {
                 Some(ref audio_info) => audio_info,
@@ -517,6 +674,13 @@ impl AggregatorImpl for NdiSinkCombiner {
             crate::ndisinkmeta::NdiSinkAudioMeta::add(current_video_buffer, audio_buffers);
         }
 
+        let metadata_buffers = mem::take(&mut state.current_metadata_buffers);
+
+        if !metadata_buffers.is_empty() {
+            let current_video_buffer = current_video_buffer.make_mut();
+            crate::ndisinkmeta::NdiSinkMetadataMeta::add(current_video_buffer, metadata_buffers);
+        }
+
         if let Some((video_buffer, video_running_time)) = next_video_buffer {
             state.current_video_buffer = Some((video_buffer, video_running_time));
             drop(state_storage);
@@ -584,6 +748,8 @@ impl AggregatorImpl for NdiSinkCombiner {
 
                     // The video caps are passed through as the audio is included only in a meta
                     agg.set_src_caps(&caps);
+                } else if self.metadata_pad.lock().unwrap().as_ref() == Some(pad) {
+                    // Nothing to configure for metadata
                 } else {
                     let info = match gst_audio::AudioInfo::from_caps(&caps) {
                         Ok(info) => info,
@@ -1,3 +1,4 @@
+use glib::translate::*;
 use gst::prelude::*;
 use std::fmt;
 use std::mem;
@@ -49,6 +50,109 @@ impl fmt::Debug for NdiSinkAudioMeta {
     }
 }
 
+#[repr(transparent)]
+pub struct NdiSinkMetadataMeta(imp::NdiSinkMetadataMeta);
+
+unsafe impl Send for NdiSinkMetadataMeta {}
+unsafe impl Sync for NdiSinkMetadataMeta {}
+
+impl NdiSinkMetadataMeta {
+    pub fn add<'a>(
+        buffer: &'a mut gst::BufferRef,
+        buffers: Vec<(gst::Buffer, i64)>,
+    ) -> gst::MetaRefMut<'a, Self, gst::meta::Standalone> {
+        unsafe {
+            // Manually dropping because gst_buffer_add_meta() takes ownership of the
+            // content of the struct
+            let mut params = mem::ManuallyDrop::new(imp::NdiSinkMetadataMetaParams { buffers });
+
+            let meta = gst::ffi::gst_buffer_add_meta(
+                buffer.as_mut_ptr(),
+                imp::ndi_sink_metadata_meta_get_info(),
+                &mut *params as *mut imp::NdiSinkMetadataMetaParams as glib::ffi::gpointer,
+            ) as *mut imp::NdiSinkMetadataMeta;
+
+            Self::from_mut_ptr(buffer, meta)
+        }
+    }
+
+    pub fn buffers(&self) -> &[(gst::Buffer, i64)] {
+        &self.0.buffers
+    }
+}
+
+unsafe impl MetaAPI for NdiSinkMetadataMeta {
+    type GstType = imp::NdiSinkMetadataMeta;
+
+    fn meta_api() -> glib::Type {
+        imp::ndi_sink_metadata_meta_api_get_type()
+    }
+}
+
+impl fmt::Debug for NdiSinkMetadataMeta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NdiSinkMetadataMeta")
+            .field("buffers", &self.buffers())
+            .finish()
+    }
+}
+
+/// Registers the metas that applications can use so that they can be looked up by name, e.g. with
+/// `gst_meta_get_info("GstNdiSinkFrameMetadataMeta")`, as soon as the plugin is loaded.
+pub fn register() {
+    imp::ndi_sink_frame_metadata_meta_get_info();
+}
+
+/// Metadata that is sent together with the video frame of the buffer it is attached to.
+///
+/// Applications can add it via `gst_buffer_add_meta()` with the `GstNdiSinkFrameMetadataMeta`
+/// meta info and the NUL-terminated UTF-8 metadata string as parameter.
+#[repr(transparent)]
+pub struct NdiSinkFrameMetadataMeta(imp::NdiSinkFrameMetadataMeta);
+
+unsafe impl Send for NdiSinkFrameMetadataMeta {}
+unsafe impl Sync for NdiSinkFrameMetadataMeta {}
+
+impl NdiSinkFrameMetadataMeta {
+    pub fn add<'a>(
+        buffer: &'a mut gst::BufferRef,
+        metadata: &str,
+    ) -> gst::MetaRefMut<'a, Self, gst::meta::Standalone> {
+        unsafe {
+            // The metadata is copied by the init function
+            let metadata: Stash<*const std::os::raw::c_char, _> = metadata.to_glib_none();
+
+            let meta = gst::ffi::gst_buffer_add_meta(
+                buffer.as_mut_ptr(),
+                imp::ndi_sink_frame_metadata_meta_get_info(),
+                metadata.0 as glib::ffi::gpointer,
+            ) as *mut imp::NdiSinkFrameMetadataMeta;
+
+            Self::from_mut_ptr(buffer, meta)
+        }
+    }
+
+    pub fn metadata(&self) -> &str {
+        &self.0.metadata
+    }
+}
+
+unsafe impl MetaAPI for NdiSinkFrameMetadataMeta {
+    type GstType = imp::NdiSinkFrameMetadataMeta;
+
+    fn meta_api() -> glib::Type {
+        imp::ndi_sink_frame_metadata_meta_api_get_type()
+    }
+}
+
+impl fmt::Debug for NdiSinkFrameMetadataMeta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NdiSinkFrameMetadataMeta")
+            .field("metadata", &self.metadata())
+            .finish()
+    }
+}
+
 mod imp {
     use glib::translate::*;
     use once_cell::sync::Lazy;
@@ -139,4 +243,178 @@ mod imp {
 
         META_INFO.0.as_ptr()
     }
+
+    pub(super) struct NdiSinkMetadataMetaParams {
+        pub buffers: Vec<(gst::Buffer, i64)>,
+    }
+
+    #[repr(C)]
+    pub struct NdiSinkMetadataMeta {
+        parent: gst::ffi::GstMeta,
+        pub(super) buffers: Vec<(gst::Buffer, i64)>,
+    }
+
+    pub(super) fn ndi_sink_metadata_meta_api_get_type() -> glib::Type {
+        static TYPE: Lazy<glib::Type> = Lazy::new(|| unsafe {
+            let t = from_glib(gst::ffi::gst_meta_api_type_register(
+                b"GstNdiSinkMetadataMetaAPI\0".as_ptr() as *const _,
+                [ptr::null::<std::os::raw::c_char>()].as_ptr() as *mut *const _,
+            ));
+
+            assert_ne!(t, glib::Type::INVALID);
+
+            t
+        });
+
+        *TYPE
+    }
+
+    unsafe extern "C" fn ndi_sink_metadata_meta_init(
+        meta: *mut gst::ffi::GstMeta,
+        params: glib::ffi::gpointer,
+        _buffer: *mut gst::ffi::GstBuffer,
+    ) -> glib::ffi::gboolean {
+        assert!(!params.is_null());
+
+        let meta = &mut *(meta as *mut NdiSinkMetadataMeta);
+        let params = ptr::read(params as *const NdiSinkMetadataMetaParams);
+
+        ptr::write(&mut meta.buffers, params.buffers);
+
+        true.into_glib()
+    }
+
+    unsafe extern "C" fn ndi_sink_metadata_meta_free(
+        meta: *mut gst::ffi::GstMeta,
+        _buffer: *mut gst::ffi::GstBuffer,
+    ) {
+        let meta = &mut *(meta as *mut NdiSinkMetadataMeta);
+
+        ptr::drop_in_place(&mut meta.buffers);
+    }
+
+    unsafe extern "C" fn ndi_sink_metadata_meta_transform(
+        dest: *mut gst::ffi::GstBuffer,
+        meta: *mut gst::ffi::GstMeta,
+        _buffer: *mut gst::ffi::GstBuffer,
+        _type_: glib::ffi::GQuark,
+        _data: glib::ffi::gpointer,
+    ) -> glib::ffi::gboolean {
+        let meta = &*(meta as *mut NdiSinkMetadataMeta);
+
+        super::NdiSinkMetadataMeta::add(gst::BufferRef::from_mut_ptr(dest), meta.buffers.clone());
+
+        true.into_glib()
+    }
+
+    pub(super) fn ndi_sink_metadata_meta_get_info() -> *const gst::ffi::GstMetaInfo {
+        struct MetaInfo(ptr::NonNull<gst::ffi::GstMetaInfo>);
+        unsafe impl Send for MetaInfo {}
+        unsafe impl Sync for MetaInfo {}
+
+        static META_INFO: Lazy<MetaInfo> = Lazy::new(|| unsafe {
+            MetaInfo(
+                ptr::NonNull::new(gst::ffi::gst_meta_register(
+                    ndi_sink_metadata_meta_api_get_type().into_glib(),
+                    b"GstNdiSinkMetadataMeta\0".as_ptr() as *const _,
+                    mem::size_of::<NdiSinkMetadataMeta>(),
+                    Some(ndi_sink_metadata_meta_init),
+                    Some(ndi_sink_metadata_meta_free),
+                    Some(ndi_sink_metadata_meta_transform),
+                ) as *mut gst::ffi::GstMetaInfo)
+                .expect("Failed to register meta API"),
+            )
+        });
+
+        META_INFO.0.as_ptr()
+    }
+
+    #[repr(C)]
+    pub struct NdiSinkFrameMetadataMeta {
+        parent: gst::ffi::GstMeta,
+        pub(super) metadata: String,
+    }
+
+    pub(super) fn ndi_sink_frame_metadata_meta_api_get_type() -> glib::Type {
+        static TYPE: Lazy<glib::Type> = Lazy::new(|| unsafe {
+            let t = from_glib(gst::ffi::gst_meta_api_type_register(
+                b"GstNdiSinkFrameMetadataMetaAPI\0".as_ptr() as *const _,
+                [ptr::null::<std::os::raw::c_char>()].as_ptr() as *mut *const _,
+            ));
+
+            assert_ne!(t, glib::Type::INVALID);
+
+            t
+        });
+
+        *TYPE
+    }
+
+    unsafe extern "C" fn ndi_sink_frame_metadata_meta_init(
+        meta: *mut gst::ffi::GstMeta,
+        params: glib::ffi::gpointer,
+        _buffer: *mut gst::ffi::GstBuffer,
+    ) -> glib::ffi::gboolean {
+        // This is called by applications via gst_buffer_add_meta() too, so invalid parameters
+        // must not panic
+        if params.is_null() {
+            return false.into_glib();
+        }
+
+        let metadata =
+            match std::ffi::CStr::from_ptr(params as *const std::os::raw::c_char).to_str() {
+                Ok(metadata) => metadata,
+                Err(_) => return false.into_glib(),
+            };
+
+        let meta = &mut *(meta as *mut NdiSinkFrameMetadataMeta);
+        ptr::write(&mut meta.metadata, String::from(metadata));
+
+        true.into_glib()
+    }
+
+    unsafe extern "C" fn ndi_sink_frame_metadata_meta_free(
+        meta: *mut gst::ffi::GstMeta,
+        _buffer: *mut gst::ffi::GstBuffer,
+    ) {
+        let meta = &mut *(meta as *mut NdiSinkFrameMetadataMeta);
+
+        ptr::drop_in_place(&mut meta.metadata);
+    }
+
+    unsafe extern "C" fn ndi_sink_frame_metadata_meta_transform(
+        dest: *mut gst::ffi::GstBuffer,
+        meta: *mut gst::ffi::GstMeta,
+        _buffer: *mut gst::ffi::GstBuffer,
+        _type_: glib::ffi::GQuark,
+        _data: glib::ffi::gpointer,
+    ) -> glib::ffi::gboolean {
+        let meta = &*(meta as *mut NdiSinkFrameMetadataMeta);
+
+        super::NdiSinkFrameMetadataMeta::add(gst::BufferRef::from_mut_ptr(dest), &meta.metadata);
+
+        true.into_glib()
+    }
+
+    pub(super) fn ndi_sink_frame_metadata_meta_get_info() -> *const gst::ffi::GstMetaInfo {
+        struct MetaInfo(ptr::NonNull<gst::ffi::GstMetaInfo>);
+        unsafe impl Send for MetaInfo {}
+        unsafe impl Sync for MetaInfo {}
+
+        static META_INFO: Lazy<MetaInfo> = Lazy::new(|| unsafe {
+            MetaInfo(
+                ptr::NonNull::new(gst::ffi::gst_meta_register(
+                    ndi_sink_frame_metadata_meta_api_get_type().into_glib(),
+                    b"GstNdiSinkFrameMetadataMeta\0".as_ptr() as *const _,
+                    mem::size_of::<NdiSinkFrameMetadataMeta>(),
+                    Some(ndi_sink_frame_metadata_meta_init),
+                    Some(ndi_sink_frame_metadata_meta_free),
+                    Some(ndi_sink_frame_metadata_meta_transform),
+                ) as *mut gst::ffi::GstMetaInfo)
+                .expect("Failed to register meta API"),
+            )
+        });
+
+        META_INFO.0.as_ptr()
+    }
 }
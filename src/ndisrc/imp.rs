@@ -1,6 +1,6 @@
 use gst::prelude::*;
 use gst::subclass::prelude::*;
-use gst::{gst_debug, gst_error};
+use gst::{gst_debug, gst_error, gst_info, gst_warning};
 use gst_base::prelude::*;
 use gst_base::subclass::base_src::CreateSuccess;
 use gst_base::subclass::prelude::*;
@@ -10,14 +10,19 @@ use std::{i32, u32};
 
 use once_cell::sync::Lazy;
 
+use crate::ndi;
 use crate::ndisys;
 
 use crate::ndisrcmeta;
 use crate::Buffer;
+use crate::NdiNameMatch;
+use crate::ReceiveMode;
 use crate::Receiver;
 use crate::ReceiverControlHandle;
 use crate::ReceiverItem;
 use crate::RecvColorFormat;
+use crate::SourceFinder;
+use crate::TimeoutAction;
 use crate::TimestampMode;
 use crate::DEFAULT_RECEIVER_NDI_NAME;
 
@@ -32,28 +37,60 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
 #[derive(Debug, Clone)]
 struct Settings {
     ndi_name: Option<String>,
+    ndi_name_match: NdiNameMatch,
     url_address: Option<String>,
+    groups: Option<String>,
+    extra_ips: Option<String>,
+    show_local_sources: bool,
     connect_timeout: u32,
     timeout: u32,
+    timeout_action: TimeoutAction,
+    reconnect_interval: u32,
+    max_reconnect_attempts: u32,
+    backup_ndi_name: Option<String>,
+    backup_url_address: Option<String>,
+    failover_timeout: u32,
     max_queue_length: u32,
     receiver_ndi_name: String,
+    receiver_metadata: Option<String>,
+    hwaccel: bool,
     bandwidth: ndisys::NDIlib_recv_bandwidth_e,
     color_format: RecvColorFormat,
+    preserve_alpha: bool,
+    receive_mode: ReceiveMode,
     timestamp_mode: TimestampMode,
+    on_program: bool,
+    on_preview: bool,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             ndi_name: None,
+            ndi_name_match: NdiNameMatch::Exact,
             url_address: None,
+            groups: None,
+            extra_ips: None,
+            show_local_sources: true,
             receiver_ndi_name: DEFAULT_RECEIVER_NDI_NAME.clone(),
+            receiver_metadata: None,
+            hwaccel: true,
             connect_timeout: 10000,
             timeout: 5000,
+            timeout_action: TimeoutAction::Eos,
+            reconnect_interval: 1000,
+            max_reconnect_attempts: 0,
+            backup_ndi_name: None,
+            backup_url_address: None,
+            failover_timeout: 1000,
             max_queue_length: 10,
             bandwidth: ndisys::NDIlib_recv_bandwidth_highest,
             color_format: RecvColorFormat::UyvyBgra,
+            preserve_alpha: false,
+            receive_mode: ReceiveMode::Capture,
             timestamp_mode: TimestampMode::ReceiveTimeTimecode,
+            on_program: true,
+            on_preview: false,
         }
     }
 }
@@ -65,6 +102,8 @@ struct State {
     audio_caps: Option<gst::Caps>,
     current_latency: Option<gst::ClockTime>,
     receiver: Option<Receiver>,
+    // NDI name and URL/address the receiver was last connected to from the settings
+    source: Option<(Option<String>, Option<String>)>,
 }
 
 impl Default for State {
@@ -76,13 +115,22 @@ impl Default for State {
             audio_caps: None,
             current_latency: gst::ClockTime::NONE,
             receiver: None,
+            source: None,
         }
     }
 }
 
+// Source properties that were set since the source was last switched
+#[derive(Default)]
+struct SourceChanges {
+    ndi_name: bool,
+    url_address: bool,
+}
+
 pub struct NdiSrc {
     settings: Mutex<Settings>,
     state: Mutex<State>,
+    source_changes: Mutex<SourceChanges>,
     receiver_controller: Mutex<Option<ReceiverControlHandle>>,
 }
 
@@ -96,6 +144,7 @@ impl ObjectSubclass for NdiSrc {
         Self {
             settings: Mutex::new(Default::default()),
             state: Mutex::new(Default::default()),
+            source_changes: Mutex::new(Default::default()),
             receiver_controller: Mutex::new(None),
         }
     }
@@ -110,13 +159,56 @@ impl ObjectImpl for NdiSrc {
                     "NDI Name",
                     "NDI stream name of the sender",
                     None,
-                    glib::ParamFlags::READWRITE,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
                 ),
                 glib::ParamSpec::new_string(
                     "url-address",
                     "URL/Address",
                     "URL/address and port of the sender, e.g. 127.0.0.1:5961",
                     None,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_enum(
+                    "ndi-name-match",
+                    "NDI Name Match",
+                    "How ndi-name is matched against the discovered sources if no url-address is given",
+                    NdiNameMatch::static_type(),
+                    NdiNameMatch::Exact as i32,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "resolved-ndi-name",
+                    "Resolved NDI Name",
+                    "NDI stream name of the sender the receiver is connected to",
+                    None,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_string(
+                    "resolved-url-address",
+                    "Resolved URL/Address",
+                    "URL/address of the sender the receiver is connected to, if known",
+                    None,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_string(
+                    "groups",
+                    "Groups",
+                    "Comma separated list of NDI groups to discover the sender in (default: public)",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "extra-ips",
+                    "Extra IPs",
+                    "Comma separated list of additional IP addresses to query for the sender",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "show-local-sources",
+                    "Show Local Sources",
+                    "Whether senders running on this machine are discovered",
+                    true,
                     glib::ParamFlags::READWRITE,
                 ),
                 glib::ParamSpec::new_string(
@@ -126,6 +218,20 @@ impl ObjectImpl for NdiSrc {
                     Some(&*DEFAULT_RECEIVER_NDI_NAME),
                     glib::ParamFlags::READWRITE,
                 ),
+                glib::ParamSpec::new_string(
+                    "receiver-metadata",
+                    "Receiver Metadata",
+                    "XML, or a GstStructure converted to an XML element, sent to the sender on every connection",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "hwaccel",
+                    "Hardware Acceleration",
+                    "Ask the sender to enable hardware acceleration for this receiver",
+                    true,
+                    glib::ParamFlags::READWRITE,
+                ),
                 glib::ParamSpec::new_uint(
                     "connect-timeout",
                     "Connect Timeout",
@@ -144,6 +250,55 @@ impl ObjectImpl for NdiSrc {
                     5000,
                     glib::ParamFlags::READWRITE,
                 ),
+                glib::ParamSpec::new_enum(
+                    "timeout-action",
+                    "Timeout Action",
+                    "What to do when the connect or receive timeout elapsed",
+                    TimeoutAction::static_type(),
+                    TimeoutAction::Eos as i32,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_uint(
+                    "reconnect-interval",
+                    "Reconnect Interval",
+                    "Time in ms to wait for frames after each reconnection attempt",
+                    1,
+                    u32::MAX,
+                    1000,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_uint(
+                    "max-reconnect-attempts",
+                    "Max Reconnect Attempts",
+                    "Maximum number of reconnection attempts before erroring out (0 = unlimited)",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "backup-ndi-name",
+                    "Backup NDI Name",
+                    "NDI stream name of the sender to fail over to if the primary sender stops delivering frames",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_string(
+                    "backup-url-address",
+                    "Backup URL/Address",
+                    "URL/address and port of the sender to fail over to if the primary sender stops delivering frames",
+                    None,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_uint(
+                    "failover-timeout",
+                    "Failover Timeout",
+                    "Switch to the backup sender if the primary sender did not deliver frames for this many milliseconds (0 = never)",
+                    0,
+                    u32::MAX,
+                    1000,
+                    glib::ParamFlags::READWRITE,
+                ),
                 glib::ParamSpec::new_uint(
                     "max-queue-length",
                     "Max Queue Length",
@@ -170,6 +325,21 @@ impl ObjectImpl for NdiSrc {
                     RecvColorFormat::UyvyBgra as u32 as i32,
                     glib::ParamFlags::READWRITE,
                 ),
+                glib::ParamSpec::new_boolean(
+                    "preserve-alpha",
+                    "Preserve Alpha",
+                    "Output UYVA video as AYUV with alpha instead of UYVY without alpha",
+                    false,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpec::new_enum(
+                    "receive-mode",
+                    "Receive Mode",
+                    "Capture frames as they arrive, or pull them through the NDI frame synchronizer on the pipeline clock (timestamp-mode is ignored then)",
+                    ReceiveMode::static_type(),
+                    ReceiveMode::Capture as i32,
+                    glib::ParamFlags::READWRITE,
+                ),
                 glib::ParamSpec::new_enum(
                     "timestamp-mode",
                     "Timestamp Mode",
@@ -178,12 +348,105 @@ impl ObjectImpl for NdiSrc {
                     TimestampMode::ReceiveTimeTimecode as i32,
                     glib::ParamFlags::READWRITE,
                 ),
+                glib::ParamSpec::new_boolean(
+                    "on-program",
+                    "On Program",
+                    "Signal program tally to the sender",
+                    true,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "on-preview",
+                    "On Preview",
+                    "Signal preview tally to the sender",
+                    false,
+                    glib::ParamFlags::READWRITE | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpec::new_boxed(
+                    "stats",
+                    "Statistics",
+                    "Receiver performance and queue statistics",
+                    gst::Structure::static_type(),
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpec::new_boolean(
+                    "ptz-supported",
+                    "PTZ Supported",
+                    "Whether the source supports PTZ control via the ptz-* action signals",
+                    false,
+                    glib::ParamFlags::READABLE,
+                ),
             ]
         });
 
         PROPERTIES.as_ref()
     }
 
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            let f32_type = f32::static_type();
+            let i32_type = i32::static_type();
+
+            vec![
+                ptz_signal("ptz-store-preset", &[i32_type], |recv, args| {
+                    recv.ptz_store_preset(args[0].get().unwrap())
+                }),
+                ptz_signal("ptz-recall-preset", &[i32_type, f32_type], |recv, args| {
+                    recv.ptz_recall_preset(args[0].get().unwrap(), args[1].get().unwrap())
+                }),
+                ptz_signal("ptz-zoom", &[f32_type], |recv, args| {
+                    recv.ptz_zoom(args[0].get().unwrap())
+                }),
+                ptz_signal("ptz-zoom-speed", &[f32_type], |recv, args| {
+                    recv.ptz_zoom_speed(args[0].get().unwrap())
+                }),
+                ptz_signal("ptz-pan-tilt", &[f32_type, f32_type], |recv, args| {
+                    recv.ptz_pan_tilt(args[0].get().unwrap(), args[1].get().unwrap())
+                }),
+                ptz_signal("ptz-pan-tilt-speed", &[f32_type, f32_type], |recv, args| {
+                    recv.ptz_pan_tilt_speed(args[0].get().unwrap(), args[1].get().unwrap())
+                }),
+                ptz_signal("ptz-auto-focus", &[], |recv, _args| recv.ptz_auto_focus()),
+                ptz_signal("ptz-focus", &[f32_type], |recv, args| {
+                    recv.ptz_focus(args[0].get().unwrap())
+                }),
+                ptz_signal("ptz-focus-speed", &[f32_type], |recv, args| {
+                    recv.ptz_focus_speed(args[0].get().unwrap())
+                }),
+                ptz_signal("ptz-white-balance-auto", &[], |recv, _args| {
+                    recv.ptz_white_balance_auto()
+                }),
+                ptz_signal("ptz-white-balance-indoor", &[], |recv, _args| {
+                    recv.ptz_white_balance_indoor()
+                }),
+                ptz_signal("ptz-white-balance-outdoor", &[], |recv, _args| {
+                    recv.ptz_white_balance_outdoor()
+                }),
+                ptz_signal("ptz-white-balance-oneshot", &[], |recv, _args| {
+                    recv.ptz_white_balance_oneshot()
+                }),
+                ptz_signal(
+                    "ptz-white-balance-manual",
+                    &[f32_type, f32_type],
+                    |recv, args| {
+                        recv.ptz_white_balance_manual(
+                            args[0].get().unwrap(),
+                            args[1].get().unwrap(),
+                        )
+                    },
+                ),
+                ptz_signal("ptz-exposure-auto", &[], |recv, _args| {
+                    recv.ptz_exposure_auto()
+                }),
+                ptz_signal("ptz-exposure-manual", &[f32_type], |recv, args| {
+                    recv.ptz_exposure_manual(args[0].get().unwrap())
+                }),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn constructed(&self, obj: &Self::Type) {
         self.parent_constructed(obj);
 
@@ -191,6 +454,14 @@ impl ObjectImpl for NdiSrc {
         // we'd like to operate in Time format
         obj.set_live(true);
         obj.set_format(gst::Format::Time);
+
+        // The source is only switched once all properties of a g_object_set() call are set, so
+        // that changing ndi-name and url-address together switches only once
+        for property in &["ndi-name", "url-address"] {
+            obj.connect_notify(Some(property), |obj, _pspec| {
+                NdiSrc::from_instance(obj).switch_source(obj);
+            });
+        }
     }
 
     fn set_property(
@@ -212,6 +483,7 @@ impl ObjectImpl for NdiSrc {
                     ndi_name,
                 );
                 settings.ndi_name = ndi_name;
+                self.source_changes.lock().unwrap().ndi_name = true;
             }
             "url-address" => {
                 let mut settings = self.settings.lock().unwrap();
@@ -224,6 +496,55 @@ impl ObjectImpl for NdiSrc {
                     url_address,
                 );
                 settings.url_address = url_address;
+                self.source_changes.lock().unwrap().url_address = true;
+            }
+            "ndi-name-match" => {
+                let mut settings = self.settings.lock().unwrap();
+                let ndi_name_match = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing ndi-name-match from {:?} to {:?}",
+                    settings.ndi_name_match,
+                    ndi_name_match,
+                );
+                settings.ndi_name_match = ndi_name_match;
+            }
+            "groups" => {
+                let mut settings = self.settings.lock().unwrap();
+                let groups = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing groups from {:?} to {:?}",
+                    settings.groups,
+                    groups,
+                );
+                settings.groups = groups;
+            }
+            "extra-ips" => {
+                let mut settings = self.settings.lock().unwrap();
+                let extra_ips = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing extra-ips from {:?} to {:?}",
+                    settings.extra_ips,
+                    extra_ips,
+                );
+                settings.extra_ips = extra_ips;
+            }
+            "show-local-sources" => {
+                let mut settings = self.settings.lock().unwrap();
+                let show_local_sources = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing show-local-sources from {} to {}",
+                    settings.show_local_sources,
+                    show_local_sources,
+                );
+                settings.show_local_sources = show_local_sources;
             }
             "receiver-ndi-name" => {
                 let mut settings = self.settings.lock().unwrap();
@@ -238,6 +559,30 @@ impl ObjectImpl for NdiSrc {
                 settings.receiver_ndi_name =
                     receiver_ndi_name.unwrap_or_else(|| DEFAULT_RECEIVER_NDI_NAME.clone());
             }
+            "receiver-metadata" => {
+                let mut settings = self.settings.lock().unwrap();
+                let receiver_metadata = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing receiver-metadata from {:?} to {:?}",
+                    settings.receiver_metadata,
+                    receiver_metadata,
+                );
+                settings.receiver_metadata = receiver_metadata;
+            }
+            "hwaccel" => {
+                let mut settings = self.settings.lock().unwrap();
+                let hwaccel = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing hwaccel from {} to {}",
+                    settings.hwaccel,
+                    hwaccel,
+                );
+                settings.hwaccel = hwaccel;
+            }
             "connect-timeout" => {
                 let mut settings = self.settings.lock().unwrap();
                 let connect_timeout = value.get().unwrap();
@@ -262,6 +607,78 @@ impl ObjectImpl for NdiSrc {
                 );
                 settings.timeout = timeout;
             }
+            "timeout-action" => {
+                let mut settings = self.settings.lock().unwrap();
+                let timeout_action = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing timeout action from {:?} to {:?}",
+                    settings.timeout_action,
+                    timeout_action,
+                );
+                settings.timeout_action = timeout_action;
+            }
+            "reconnect-interval" => {
+                let mut settings = self.settings.lock().unwrap();
+                let reconnect_interval = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing reconnect-interval from {} to {}",
+                    settings.reconnect_interval,
+                    reconnect_interval,
+                );
+                settings.reconnect_interval = reconnect_interval;
+            }
+            "max-reconnect-attempts" => {
+                let mut settings = self.settings.lock().unwrap();
+                let max_reconnect_attempts = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing max-reconnect-attempts from {} to {}",
+                    settings.max_reconnect_attempts,
+                    max_reconnect_attempts,
+                );
+                settings.max_reconnect_attempts = max_reconnect_attempts;
+            }
+            "backup-ndi-name" => {
+                let mut settings = self.settings.lock().unwrap();
+                let backup_ndi_name = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing backup-ndi-name from {:?} to {:?}",
+                    settings.backup_ndi_name,
+                    backup_ndi_name,
+                );
+                settings.backup_ndi_name = backup_ndi_name;
+            }
+            "backup-url-address" => {
+                let mut settings = self.settings.lock().unwrap();
+                let backup_url_address = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing backup-url-address from {:?} to {:?}",
+                    settings.backup_url_address,
+                    backup_url_address,
+                );
+                settings.backup_url_address = backup_url_address;
+            }
+            "failover-timeout" => {
+                let mut settings = self.settings.lock().unwrap();
+                let failover_timeout = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing failover-timeout from {} to {}",
+                    settings.failover_timeout,
+                    failover_timeout,
+                );
+                settings.failover_timeout = failover_timeout;
+            }
             "max-queue-length" => {
                 let mut settings = self.settings.lock().unwrap();
                 let max_queue_length = value.get().unwrap();
@@ -298,6 +715,30 @@ impl ObjectImpl for NdiSrc {
                 );
                 settings.color_format = color_format;
             }
+            "preserve-alpha" => {
+                let mut settings = self.settings.lock().unwrap();
+                let preserve_alpha = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing preserve-alpha from {} to {}",
+                    settings.preserve_alpha,
+                    preserve_alpha,
+                );
+                settings.preserve_alpha = preserve_alpha;
+            }
+            "receive-mode" => {
+                let mut settings = self.settings.lock().unwrap();
+                let receive_mode = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing receive mode from {:?} to {:?}",
+                    settings.receive_mode,
+                    receive_mode
+                );
+                settings.receive_mode = receive_mode;
+            }
             "timestamp-mode" => {
                 let mut settings = self.settings.lock().unwrap();
                 let timestamp_mode = value.get().unwrap();
@@ -313,6 +754,36 @@ impl ObjectImpl for NdiSrc {
                 }
                 settings.timestamp_mode = timestamp_mode;
             }
+            "on-program" => {
+                let mut settings = self.settings.lock().unwrap();
+                let on_program = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing on-program from {} to {}",
+                    settings.on_program,
+                    on_program,
+                );
+                settings.on_program = on_program;
+                let tally = ndi::Tally::new(settings.on_program, settings.on_preview);
+                drop(settings);
+                self.set_tally(&tally);
+            }
+            "on-preview" => {
+                let mut settings = self.settings.lock().unwrap();
+                let on_preview = value.get().unwrap();
+                gst_debug!(
+                    CAT,
+                    obj: obj,
+                    "Changing on-preview from {} to {}",
+                    settings.on_preview,
+                    on_preview,
+                );
+                settings.on_preview = on_preview;
+                let tally = ndi::Tally::new(settings.on_program, settings.on_preview);
+                drop(settings);
+                self.set_tally(&tally);
+            }
             _ => unimplemented!(),
         }
     }
@@ -327,10 +798,36 @@ impl ObjectImpl for NdiSrc {
                 let settings = self.settings.lock().unwrap();
                 settings.url_address.to_value()
             }
+            "ndi-name-match" => {
+                let settings = self.settings.lock().unwrap();
+                settings.ndi_name_match.to_value()
+            }
+            "resolved-ndi-name" => self.resolved_source().0.to_value(),
+            "resolved-url-address" => self.resolved_source().1.to_value(),
+            "groups" => {
+                let settings = self.settings.lock().unwrap();
+                settings.groups.to_value()
+            }
+            "extra-ips" => {
+                let settings = self.settings.lock().unwrap();
+                settings.extra_ips.to_value()
+            }
+            "show-local-sources" => {
+                let settings = self.settings.lock().unwrap();
+                settings.show_local_sources.to_value()
+            }
             "receiver-ndi-name" => {
                 let settings = self.settings.lock().unwrap();
                 settings.receiver_ndi_name.to_value()
             }
+            "receiver-metadata" => {
+                let settings = self.settings.lock().unwrap();
+                settings.receiver_metadata.to_value()
+            }
+            "hwaccel" => {
+                let settings = self.settings.lock().unwrap();
+                settings.hwaccel.to_value()
+            }
             "connect-timeout" => {
                 let settings = self.settings.lock().unwrap();
                 settings.connect_timeout.to_value()
@@ -339,6 +836,30 @@ impl ObjectImpl for NdiSrc {
                 let settings = self.settings.lock().unwrap();
                 settings.timeout.to_value()
             }
+            "timeout-action" => {
+                let settings = self.settings.lock().unwrap();
+                settings.timeout_action.to_value()
+            }
+            "reconnect-interval" => {
+                let settings = self.settings.lock().unwrap();
+                settings.reconnect_interval.to_value()
+            }
+            "max-reconnect-attempts" => {
+                let settings = self.settings.lock().unwrap();
+                settings.max_reconnect_attempts.to_value()
+            }
+            "backup-ndi-name" => {
+                let settings = self.settings.lock().unwrap();
+                settings.backup_ndi_name.to_value()
+            }
+            "backup-url-address" => {
+                let settings = self.settings.lock().unwrap();
+                settings.backup_url_address.to_value()
+            }
+            "failover-timeout" => {
+                let settings = self.settings.lock().unwrap();
+                settings.failover_timeout.to_value()
+            }
             "max-queue-length" => {
                 let settings = self.settings.lock().unwrap();
                 settings.max_queue_length.to_value()
@@ -351,10 +872,34 @@ impl ObjectImpl for NdiSrc {
                 let settings = self.settings.lock().unwrap();
                 settings.color_format.to_value()
             }
+            "preserve-alpha" => {
+                let settings = self.settings.lock().unwrap();
+                settings.preserve_alpha.to_value()
+            }
+            "receive-mode" => {
+                let settings = self.settings.lock().unwrap();
+                settings.receive_mode.to_value()
+            }
             "timestamp-mode" => {
                 let settings = self.settings.lock().unwrap();
                 settings.timestamp_mode.to_value()
             }
+            "on-program" => {
+                let settings = self.settings.lock().unwrap();
+                settings.on_program.to_value()
+            }
+            "on-preview" => {
+                let settings = self.settings.lock().unwrap();
+                settings.on_preview.to_value()
+            }
+            "stats" => match *self.receiver_controller.lock().unwrap() {
+                Some(ref controller) => controller.stats().to_value(),
+                None => gst::Structure::new_empty("application/x-ndi-receiver-stats").to_value(),
+            },
+            "ptz-supported" => match *self.receiver_controller.lock().unwrap() {
+                Some(ref controller) => controller.recv().ptz_is_supported().to_value(),
+                None => false.to_value(),
+            },
             _ => unimplemented!(),
         }
     }
@@ -452,17 +997,48 @@ impl BaseSrcImpl for NdiSrc {
             ));
         }
 
+        let receiver_metadata = match settings.receiver_metadata {
+            Some(ref metadata) => Some(crate::metadata_to_xml(metadata).ok_or_else(|| {
+                gst::error_msg!(
+                    gst::LibraryError::Settings,
+                    ["Invalid receiver metadata {:?}", metadata]
+                )
+            })?),
+            None => None,
+        };
+
+        ndi::initialize().map_err(|err| {
+            gst::error_msg!(
+                gst::LibraryError::Init,
+                ["Failed to initialize NDI SDK: {}", err]
+            )
+        })?;
+
+        let finder = Self::source_finder(&settings)?;
+
         let receiver = Receiver::connect(
             element.upcast_ref(),
             settings.ndi_name.as_deref(),
             settings.url_address.as_deref(),
+            finder,
+            settings.backup_ndi_name.as_deref(),
+            settings.backup_url_address.as_deref(),
+            settings.failover_timeout,
             &settings.receiver_ndi_name,
+            receiver_metadata.as_deref(),
+            settings.hwaccel,
             settings.connect_timeout,
             settings.bandwidth,
             settings.color_format.into(),
+            settings.preserve_alpha,
+            settings.receive_mode,
             settings.timestamp_mode,
             settings.timeout,
+            settings.timeout_action,
+            settings.reconnect_interval,
+            settings.max_reconnect_attempts,
             settings.max_queue_length as usize,
+            &ndi::Tally::new(settings.on_program, settings.on_preview),
         );
 
         match receiver {
@@ -475,6 +1051,11 @@ impl BaseSrcImpl for NdiSrc {
                     Some(receiver.receiver_control_handle());
                 let mut state = self.state.lock().unwrap();
                 state.receiver = Some(receiver);
+                state.source = Some((settings.ndi_name.clone(), settings.url_address.clone()));
+                drop(state);
+
+                element.notify("resolved-ndi-name");
+                element.notify("resolved-url-address");
 
                 Ok(())
             }
@@ -503,10 +1084,13 @@ impl BaseSrcImpl for NdiSrc {
                 let settings = self.settings.lock().unwrap();
 
                 if let Some(latency) = state.current_latency {
-                    let min = if matches!(
-                        settings.timestamp_mode,
-                        TimestampMode::ReceiveTimeTimecode | TimestampMode::ReceiveTimeTimestamp
-                    ) {
+                    // With the frame synchronizer, buffers are captured at their running time
+                    let min = if settings.receive_mode == ReceiveMode::Framesync
+                        || matches!(
+                            settings.timestamp_mode,
+                            TimestampMode::ReceiveTimeTimecode
+                                | TimestampMode::ReceiveTimeTimestamp
+                        ) {
                         latency
                     } else {
                         gst::ClockTime::ZERO
@@ -616,6 +1200,18 @@ impl BaseSrcImpl for NdiSrc {
                             );
                         }
 
+                        buffer
+                    }
+                    Buffer::Metadata(mut buffer) => {
+                        {
+                            let buffer = buffer.get_mut().unwrap();
+                            ndisrcmeta::NdiSrcMeta::add(
+                                buffer,
+                                ndisrcmeta::StreamType::Metadata,
+                                &crate::METADATA_CAPS,
+                            );
+                        }
+
                         buffer
                     }
                 };
@@ -628,3 +1224,162 @@ impl BaseSrcImpl for NdiSrc {
         }
     }
 }
+
+impl NdiSrc {
+    // Without a URL/address the NDI name is looked up by the receiver with the discovery settings
+    // if it is a pattern or any of them differs from the default, otherwise the SDK resolves the
+    // name itself
+    fn source_finder(settings: &Settings) -> Result<Option<SourceFinder>, gst::ErrorMessage> {
+        match settings.ndi_name {
+            Some(ref ndi_name)
+                if settings.url_address.is_none()
+                    && (settings.ndi_name_match != NdiNameMatch::Exact
+                        || settings.groups.is_some()
+                        || settings.extra_ips.is_some()
+                        || !settings.show_local_sources) =>
+            {
+                SourceFinder::new(
+                    settings.ndi_name_match,
+                    ndi_name,
+                    settings.groups.as_deref(),
+                    settings.extra_ips.as_deref(),
+                    settings.show_local_sources,
+                )
+                .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn resolved_source(&self) -> (Option<String>, Option<String>) {
+        match *self.receiver_controller.lock().unwrap() {
+            Some(ref controller) => controller.resolved_source(),
+            None => (None, None),
+        }
+    }
+
+    fn set_tally(&self, tally: &ndi::Tally) {
+        if let Some(ref controller) = *self.receiver_controller.lock().unwrap() {
+            controller.set_tally(tally);
+        }
+    }
+
+    // Connects the running receiver, if any, to the source from the settings if it changed
+    fn switch_source(&self, element: &super::NdiSrc) {
+        let changes = std::mem::take(&mut *self.source_changes.lock().unwrap());
+
+        let controller_guard = self.receiver_controller.lock().unwrap();
+        let controller = match *controller_guard {
+            Some(ref controller) => controller,
+            None => return,
+        };
+
+        // A URL/address takes precedence over the NDI name, so a previous one is dropped when
+        // only the NDI name is changed
+        let mut settings_guard = self.settings.lock().unwrap();
+        let clear_url_address = changes.ndi_name
+            && !changes.url_address
+            && settings_guard.ndi_name.is_some()
+            && settings_guard.url_address.is_some();
+        if clear_url_address {
+            gst_debug!(
+                CAT,
+                obj: element,
+                "Clearing url-address {:?} for the new ndi-name",
+                settings_guard.url_address,
+            );
+            settings_guard.url_address = None;
+        }
+        let settings = settings_guard.clone();
+        drop(settings_guard);
+        let (ndi_name, url_address) = (settings.ndi_name.clone(), settings.url_address.clone());
+
+        let source = Some((ndi_name.clone(), url_address.clone()));
+        if self.state.lock().unwrap().source == source {
+            gst_debug!(CAT, obj: element, "Source unchanged");
+            return;
+        }
+
+        if ndi_name.is_none() && url_address.is_none() {
+            gst_warning!(
+                CAT,
+                obj: element,
+                "No NDI name or URL/address given, keeping current source"
+            );
+            return;
+        }
+
+        // The source is looked up by the capture thread as in start()
+        let finder = match Self::source_finder(&settings) {
+            Ok(finder) => finder,
+            Err(err) => {
+                gst_warning!(CAT, obj: element, "Keeping current source: {}", err);
+                return;
+            }
+        };
+
+        gst_info!(
+            CAT,
+            obj: element,
+            "Switching to NDI source with NDI name {:?} and URL/address {:?}",
+            ndi_name,
+            url_address,
+        );
+
+        controller.connect(ndi_name.as_deref(), url_address.as_deref(), finder);
+        drop(controller_guard);
+
+        // Caps and latency are updated with the first buffers of the new source
+        {
+            let mut state = self.state.lock().unwrap();
+            state.video_info = None;
+            state.audio_info = None;
+            state.source = source;
+        }
+
+        if clear_url_address {
+            element.notify("url-address");
+        }
+        element.notify("resolved-ndi-name");
+        element.notify("resolved-url-address");
+
+        let _ = element.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("ndi-source-changed")
+                    .field("ndi-name", &ndi_name)
+                    .field("url-address", &url_address)
+                    .build(),
+            )
+            .src(element)
+            .build(),
+        );
+    }
+}
+
+// Creates an action signal that runs the PTZ command on the receiver, if any, and returns whether
+// it was sent successfully
+fn ptz_signal(
+    name: &str,
+    param_types: &[glib::Type],
+    func: fn(&ndi::RecvInstance, &[glib::Value]) -> bool,
+) -> glib::subclass::Signal {
+    let param_types = param_types.iter().map(|t| (*t).into()).collect::<Vec<_>>();
+
+    glib::subclass::Signal::builder(name, &param_types, bool::static_type().into())
+        .action()
+        .class_handler(move |_, args| {
+            let element = args[0].get::<super::NdiSrc>().expect("signal arg");
+            let src = NdiSrc::from_instance(&element);
+
+            let res = match *src.receiver_controller.lock().unwrap() {
+                Some(ref controller) => func(controller.recv(), &args[1..]),
+                None => {
+                    gst_debug!(CAT, obj: &element, "Not connected, can't send PTZ command");
+                    false
+                }
+            };
+
+            Some(res.to_value())
+        })
+        .build()
+}
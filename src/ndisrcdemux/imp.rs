@@ -24,6 +24,11 @@ struct State {
 
     audio_pad: Option<gst::Pad>,
     audio_caps: Option<gst::Caps>,
+    #[cfg(feature = "non-interleaved-audio")]
+    audio_interleave: Option<gst_audio::AudioInfo>,
+
+    metadata_pad: Option<gst::Pad>,
+    metadata_caps: Option<gst::Caps>,
 }
 
 pub struct NdiSrcDemux {
@@ -70,7 +75,7 @@ impl ElementImpl for NdiSrcDemux {
         static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
             gst::subclass::ElementMetadata::new(
                 "NewTek NDI Source Demuxer",
-                "Demuxer/Audio/Video",
+                "Demuxer/Audio/Video/Metadata",
                 "NewTek NDI source demuxer",
                 "Sebastian Dröge <sebastian@centricular.com>",
             )
@@ -105,10 +110,19 @@ impl ElementImpl for NdiSrcDemux {
             )
             .unwrap();
 
+            let metadata_src_pad_template = gst::PadTemplate::new(
+                "metadata",
+                gst::PadDirection::Src,
+                gst::PadPresence::Sometimes,
+                &gst::Caps::builder("application/x-ndi-metadata").build(),
+            )
+            .unwrap();
+
             vec![
                 sink_pad_template,
                 audio_src_pad_template,
                 video_src_pad_template,
+                metadata_src_pad_template,
             ]
         });
 
@@ -125,9 +139,13 @@ impl ElementImpl for NdiSrcDemux {
         match transition {
             gst::StateChange::PausedToReady => {
                 let mut state = self.state.lock().unwrap();
-                for pad in [state.audio_pad.take(), state.video_pad.take()]
-                    .iter()
-                    .flatten()
+                for pad in [
+                    state.audio_pad.take(),
+                    state.video_pad.take(),
+                    state.metadata_pad.take(),
+                ]
+                .iter()
+                .flatten()
                 {
                     element.remove_pad(pad).unwrap();
                 }
@@ -159,108 +177,93 @@ impl NdiSrcDemux {
         let mut add_pad = false;
 
         let mut state = self.state.lock().unwrap();
+        let state_ref = &mut *state;
         let caps = meta.caps();
-        match meta.stream_type() {
+        let stream_type = meta.stream_type();
+        // Audio caps depend on whether downstream accepts non-interleaved audio, so they are only
+        // decided once the pad is added and linked
+        let negotiate_caps =
+            cfg!(feature = "non-interleaved-audio") && stream_type == ndisrcmeta::StreamType::Audio;
+        let mut negotiate_audio = false;
+        let (stream_pad, stream_caps, pad_name) = match stream_type {
             ndisrcmeta::StreamType::Audio => {
-                if let Some(ref pad) = state.audio_pad {
-                    srcpad = pad.clone();
+                (&mut state_ref.audio_pad, &mut state_ref.audio_caps, "audio")
+            }
+            ndisrcmeta::StreamType::Video => {
+                (&mut state_ref.video_pad, &mut state_ref.video_caps, "video")
+            }
+            ndisrcmeta::StreamType::Metadata => (
+                &mut state_ref.metadata_pad,
+                &mut state_ref.metadata_caps,
+                "metadata",
+            ),
+        };
+
+        if let Some(ref pad) = stream_pad {
+            srcpad = pad.clone();
+        } else {
+            gst_debug!(CAT, obj: element, "Adding {} pad with caps {}", pad_name, caps);
+
+            let klass = element.element_class();
+            let templ = klass.pad_template(pad_name).unwrap();
+            let pad = gst::Pad::builder_with_template(&templ, Some(pad_name))
+                .flags(gst::PadFlags::FIXED_CAPS)
+                .build();
+
+            let mut caps_event = if negotiate_caps {
+                None
+            } else {
+                Some(gst::event::Caps::new(&caps))
+            };
+
+            // Negotiated audio caps are only known once the pad is linked, so the sticky events
+            // that follow the caps are pushed after them instead of being stored right away
+            let mut later_events = vec![];
+            self.sinkpad.sticky_events_foreach(|ev| {
+                if ev.type_() < gst::EventType::Caps {
+                    events.push(ev.clone());
+                } else if negotiate_caps {
+                    if ev.type_() != gst::EventType::Caps {
+                        later_events.push(ev.clone());
+                    }
                 } else {
-                    gst_debug!(CAT, obj: element, "Adding audio pad with caps {}", caps);
-
-                    let klass = element.element_class();
-                    let templ = klass.pad_template("audio").unwrap();
-                    let pad = gst::Pad::builder_with_template(&templ, Some("audio"))
-                        .flags(gst::PadFlags::FIXED_CAPS)
-                        .build();
-
-                    let mut caps_event = Some(gst::event::Caps::new(&caps));
-
-                    self.sinkpad.sticky_events_foreach(|ev| {
-                        if ev.type_() < gst::EventType::Caps {
-                            events.push(ev.clone());
-                        } else {
-                            if let Some(ev) = caps_event.take() {
-                                events.push(ev);
-                            }
-
-                            if ev.type_() != gst::EventType::Caps {
-                                events.push(ev.clone());
-                            }
-                        }
-
-                        Ok(Some(ev))
-                    });
-
-                    state.audio_caps = Some(caps.clone());
-                    state.audio_pad = Some(pad.clone());
-
-                    let _ = pad.set_active(true);
-                    for ev in events.drain(..) {
-                        let _ = pad.store_sticky_event(&ev);
+                    if let Some(ev) = caps_event.take() {
+                        events.push(ev);
                     }
 
-                    state.combiner.add_pad(&pad);
-
-                    add_pad = true;
-                    srcpad = pad;
+                    if ev.type_() != gst::EventType::Caps {
+                        events.push(ev.clone());
+                    }
                 }
 
-                if state.audio_caps.as_ref() != Some(&caps) {
-                    gst_debug!(CAT, obj: element, "Audio caps changed to {}", caps);
-                    events.push(gst::event::Caps::new(&caps));
-                    state.audio_caps = Some(caps);
-                }
+                Ok(Some(ev))
+            });
+
+            if !negotiate_caps {
+                *stream_caps = Some(caps.clone());
             }
-            ndisrcmeta::StreamType::Video => {
-                if let Some(ref pad) = state.video_pad {
-                    srcpad = pad.clone();
-                } else {
-                    gst_debug!(CAT, obj: element, "Adding video pad with caps {}", caps);
-
-                    let klass = element.element_class();
-                    let templ = klass.pad_template("video").unwrap();
-                    let pad = gst::Pad::builder_with_template(&templ, Some("video"))
-                        .flags(gst::PadFlags::FIXED_CAPS)
-                        .build();
-
-                    let mut caps_event = Some(gst::event::Caps::new(&caps));
-
-                    self.sinkpad.sticky_events_foreach(|ev| {
-                        if ev.type_() < gst::EventType::Caps {
-                            events.push(ev.clone());
-                        } else {
-                            if let Some(ev) = caps_event.take() {
-                                events.push(ev);
-                            }
-
-                            if ev.type_() != gst::EventType::Caps {
-                                events.push(ev.clone());
-                            }
-                        }
-
-                        Ok(Some(ev))
-                    });
-
-                    state.video_caps = Some(caps.clone());
-                    state.video_pad = Some(pad.clone());
-
-                    let _ = pad.set_active(true);
-                    for ev in events.drain(..) {
-                        let _ = pad.store_sticky_event(&ev);
-                    }
+            *stream_pad = Some(pad.clone());
 
-                    state.combiner.add_pad(&pad);
+            let _ = pad.set_active(true);
+            for ev in events.drain(..) {
+                let _ = pad.store_sticky_event(&ev);
+            }
+            events = later_events;
 
-                    add_pad = true;
-                    srcpad = pad;
-                }
+            state_ref.combiner.add_pad(&pad);
 
-                if state.video_caps.as_ref() != Some(&caps) {
-                    gst_debug!(CAT, obj: element, "Video caps changed to {}", caps);
-                    events.push(gst::event::Caps::new(&caps));
-                    state.video_caps = Some(caps);
-                }
+            add_pad = true;
+            srcpad = pad;
+        }
+
+        if stream_caps.as_ref() != Some(&caps) {
+            gst_debug!(CAT, obj: element, "{} caps changed to {}", pad_name, caps);
+            if negotiate_caps {
+                negotiate_audio = true;
+            } else {
+                events.push(gst::event::Caps::new(&caps));
             }
+            *stream_caps = Some(caps.clone());
         }
         drop(state);
 
@@ -268,6 +271,25 @@ impl NdiSrcDemux {
             element.add_pad(&srcpad).unwrap();
         }
 
+        #[cfg(feature = "non-interleaved-audio")]
+        let buffer = if stream_type == ndisrcmeta::StreamType::Audio {
+            if negotiate_audio || srcpad.check_reconfigure() {
+                let (caps, interleave) = self.negotiate_audio(element, &srcpad, &caps);
+                self.state.lock().unwrap().audio_interleave = interleave;
+                events.insert(0, gst::event::Caps::new(&caps));
+            }
+
+            let interleave = self.state.lock().unwrap().audio_interleave.clone();
+            match interleave {
+                Some(ref info) => self.interleave_audio(element, info, &buffer)?,
+                None => buffer,
+            }
+        } else {
+            buffer
+        };
+        #[cfg(not(feature = "non-interleaved-audio"))]
+        let _ = negotiate_audio;
+
         for ev in events {
             srcpad.push_event(ev);
         }
@@ -277,4 +299,82 @@ impl NdiSrcDemux {
         let mut state = self.state.lock().unwrap();
         state.combiner.update_pad_flow(&srcpad, res)
     }
+
+    /// Returns the caps to use on the audio pad for the `caps` from `ndisrc`, and the audio info
+    /// to interleave the buffers with if downstream does not accept non-interleaved audio.
+    #[cfg(feature = "non-interleaved-audio")]
+    fn negotiate_audio(
+        &self,
+        element: &super::NdiSrcDemux,
+        pad: &gst::Pad,
+        caps: &gst::Caps,
+    ) -> (gst::Caps, Option<gst_audio::AudioInfo>) {
+        let info = match gst_audio::AudioInfo::from_caps(caps) {
+            Ok(info) if info.layout() == gst_audio::AudioLayout::NonInterleaved => info,
+            _ => return (caps.clone(), None),
+        };
+
+        if pad.peer_query_accept_caps(caps) {
+            return (caps.clone(), None);
+        }
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Downstream does not accept non-interleaved audio, interleaving"
+        );
+
+        let mut caps = caps.clone();
+        caps.make_mut().set_simple(&[("layout", &"interleaved")]);
+
+        (caps, Some(info))
+    }
+
+    #[cfg(feature = "non-interleaved-audio")]
+    fn interleave_audio(
+        &self,
+        element: &super::NdiSrcDemux,
+        info: &gst_audio::AudioInfo,
+        buffer: &gst::BufferRef,
+    ) -> Result<gst::Buffer, gst::FlowError> {
+        let src =
+            gst_audio::AudioBufferRef::from_buffer_ref_readable(buffer, info).map_err(|_| {
+                gst_error!(CAT, obj: element, "Failed to map audio buffer {:?}", buffer);
+                gst::FlowError::Error
+            })?;
+        let planes = (0..src.n_planes())
+            .map(|plane| src.plane_data(plane))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| gst::FlowError::Error)?;
+
+        let bpf = info.bpf() as usize;
+        let width = bpf / info.channels() as usize;
+
+        let mut outbuf = gst::Buffer::with_size(src.n_samples() * bpf).unwrap();
+        {
+            let outbuf = outbuf.get_mut().unwrap();
+            buffer
+                .copy_into(
+                    outbuf,
+                    gst::BufferCopyFlags::FLAGS
+                        | gst::BufferCopyFlags::TIMESTAMPS
+                        | gst::BufferCopyFlags::META,
+                    0,
+                    None,
+                )
+                .map_err(|_| gst::FlowError::Error)?;
+            if let Some(meta) = outbuf.meta_mut::<gst_audio::AudioMeta>() {
+                meta.remove();
+            }
+
+            let mut map = outbuf.map_writable().unwrap();
+            for (i, frame) in map.chunks_exact_mut(bpf).enumerate() {
+                for (sample, plane) in frame.chunks_exact_mut(width).zip(planes.iter()) {
+                    sample.copy_from_slice(&plane[i * width..(i + 1) * width]);
+                }
+            }
+        }
+
+        Ok(outbuf)
+    }
 }
@@ -9,6 +9,7 @@ pub struct NdiSrcMeta(imp::NdiSrcMeta);
 pub enum StreamType {
     Audio,
     Video,
+    Metadata,
 }
 
 unsafe impl Send for NdiSrcMeta {}
@@ -1,79 +1,403 @@
 #![allow(non_camel_case_types, non_upper_case_globals, non_snake_case)]
 
-#[cfg_attr(
-    all(target_arch = "x86_64", target_os = "windows"),
-    link(name = "Processing.NDI.Lib.x64")
-)]
-#[cfg_attr(
-    all(target_arch = "x86", target_os = "windows"),
-    link(name = "Processing.NDI.Lib.x86")
-)]
-#[cfg_attr(
-    not(any(target_os = "windows", target_os = "macos")),
-    link(name = "ndi")
-)]
-extern "C" {
-    pub fn NDIlib_initialize() -> bool;
-    pub fn NDIlib_destroy();
-    pub fn NDIlib_find_create_v2(
+use once_cell::sync::OnceCell;
+
+use std::env;
+use std::path;
+
+#[cfg(all(target_arch = "x86_64", target_os = "windows"))]
+const LIBRARY_NAMES: &[&str] = &["Processing.NDI.Lib.x64.dll"];
+#[cfg(all(target_arch = "x86", target_os = "windows"))]
+const LIBRARY_NAMES: &[&str] = &["Processing.NDI.Lib.x86.dll"];
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libndi.dylib"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const LIBRARY_NAMES: &[&str] = &["libndi.so.5", "libndi.so.4", "libndi.so"];
+
+// Full path of the library to use instead of searching for it
+const LIBRARY_PATH_ENV: &str = "GST_NDI_LIBRARY";
+const RUNTIME_DIR_ENVS: &[&str] = &["NDI_RUNTIME_DIR_V5", "NDI_RUNTIME_DIR_V4"];
+
+pub struct NdiLib {
+    _library: libloading::Library,
+    pub NDIlib_initialize: unsafe extern "C" fn() -> bool,
+    pub NDIlib_destroy: unsafe extern "C" fn(),
+    pub NDIlib_find_create_v2: unsafe extern "C" fn(
         p_create_settings: *const NDIlib_find_create_t,
-    ) -> NDIlib_find_instance_t;
-    pub fn NDIlib_find_destroy(p_instance: NDIlib_find_instance_t);
-    pub fn NDIlib_find_wait_for_sources(
-        p_instance: NDIlib_find_instance_t,
-        timeout_in_ms: u32,
-    ) -> bool;
-    pub fn NDIlib_find_get_current_sources(
+    ) -> NDIlib_find_instance_t,
+    pub NDIlib_find_destroy: unsafe extern "C" fn(p_instance: NDIlib_find_instance_t),
+    pub NDIlib_find_wait_for_sources:
+        unsafe extern "C" fn(p_instance: NDIlib_find_instance_t, timeout_in_ms: u32) -> bool,
+    pub NDIlib_find_get_current_sources: unsafe extern "C" fn(
         p_instance: NDIlib_find_instance_t,
         p_no_sources: *mut u32,
-    ) -> *const NDIlib_source_t;
-    pub fn NDIlib_recv_create_v3(
+    ) -> *const NDIlib_source_t,
+    pub NDIlib_recv_create_v3: unsafe extern "C" fn(
         p_create_settings: *const NDIlib_recv_create_v3_t,
-    ) -> NDIlib_recv_instance_t;
-    pub fn NDIlib_recv_destroy(p_instance: NDIlib_recv_instance_t);
-    pub fn NDIlib_recv_set_tally(
+    ) -> NDIlib_recv_instance_t,
+    pub NDIlib_recv_destroy: unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t),
+    pub NDIlib_recv_connect:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, p_src: *const NDIlib_source_t),
+    pub NDIlib_recv_set_tally: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_tally: *const NDIlib_tally_t,
-    ) -> bool;
-    pub fn NDIlib_recv_send_metadata(
+    ) -> bool,
+    pub NDIlib_recv_send_metadata: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_metadata: *const NDIlib_metadata_frame_t,
-    ) -> bool;
-    pub fn NDIlib_recv_capture_v3(
+    ) -> bool,
+    pub NDIlib_recv_add_connection_metadata: unsafe extern "C" fn(
+        p_instance: NDIlib_recv_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ),
+    pub NDIlib_recv_capture_v3: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_video_data: *mut NDIlib_video_frame_v2_t,
         p_audio_data: *mut NDIlib_audio_frame_v3_t,
         p_metadata: *mut NDIlib_metadata_frame_t,
         timeout_in_ms: u32,
-    ) -> NDIlib_frame_type_e;
-    pub fn NDIlib_recv_free_video_v2(
+    ) -> NDIlib_frame_type_e,
+    pub NDIlib_recv_free_video_v2: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_video_data: *mut NDIlib_video_frame_v2_t,
-    );
-    pub fn NDIlib_recv_free_audio_v3(
+    ),
+    pub NDIlib_recv_free_audio_v3: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_audio_data: *mut NDIlib_audio_frame_v3_t,
-    );
-    pub fn NDIlib_recv_free_metadata(
+    ),
+    pub NDIlib_recv_free_metadata: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
         p_metadata: *mut NDIlib_metadata_frame_t,
-    );
-    pub fn NDIlib_recv_get_queue(
+    ),
+    pub NDIlib_recv_get_queue:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, p_total: *mut NDIlib_recv_queue_t),
+    pub NDIlib_recv_get_no_connections:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> i32,
+    pub NDIlib_recv_get_web_control:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> *const ::std::os::raw::c_char,
+    pub NDIlib_recv_free_string: unsafe extern "C" fn(
+        p_instance: NDIlib_recv_instance_t,
+        p_string: *const ::std::os::raw::c_char,
+    ),
+    pub NDIlib_recv_get_performance: unsafe extern "C" fn(
+        p_instance: NDIlib_recv_instance_t,
+        p_total: *mut NDIlib_recv_performance_t,
+        p_dropped: *mut NDIlib_recv_performance_t,
+    ),
+    pub NDIlib_recv_ptz_is_supported:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_store_preset:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, preset_no: i32) -> bool,
+    pub NDIlib_recv_ptz_recall_preset: unsafe extern "C" fn(
         p_instance: NDIlib_recv_instance_t,
-        p_total: *mut NDIlib_recv_queue_t,
-    );
-    pub fn NDIlib_send_create(
+        preset_no: i32,
+        speed: f32,
+    ) -> bool,
+    pub NDIlib_recv_ptz_zoom:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, zoom_value: f32) -> bool,
+    pub NDIlib_recv_ptz_zoom_speed:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, zoom_speed: f32) -> bool,
+    pub NDIlib_recv_ptz_pan_tilt: unsafe extern "C" fn(
+        p_instance: NDIlib_recv_instance_t,
+        pan_value: f32,
+        tilt_value: f32,
+    ) -> bool,
+    pub NDIlib_recv_ptz_pan_tilt_speed: unsafe extern "C" fn(
+        p_instance: NDIlib_recv_instance_t,
+        pan_speed: f32,
+        tilt_speed: f32,
+    ) -> bool,
+    pub NDIlib_recv_ptz_auto_focus:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_focus:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, focus_value: f32) -> bool,
+    pub NDIlib_recv_ptz_focus_speed:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, focus_speed: f32) -> bool,
+    pub NDIlib_recv_ptz_white_balance_auto:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_white_balance_indoor:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_white_balance_outdoor:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_white_balance_oneshot:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_white_balance_manual:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, red: f32, blue: f32) -> bool,
+    pub NDIlib_recv_ptz_exposure_auto:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t) -> bool,
+    pub NDIlib_recv_ptz_exposure_manual:
+        unsafe extern "C" fn(p_instance: NDIlib_recv_instance_t, exposure_level: f32) -> bool,
+    pub NDIlib_send_create: unsafe extern "C" fn(
         p_create_settings: *const NDIlib_send_create_t,
-    ) -> NDIlib_send_instance_t;
-    pub fn NDIlib_send_destroy(p_instance: NDIlib_send_instance_t);
-    pub fn NDIlib_send_send_video_v2(
+    ) -> NDIlib_send_instance_t,
+    pub NDIlib_send_destroy: unsafe extern "C" fn(p_instance: NDIlib_send_instance_t),
+    pub NDIlib_send_send_video_v2: unsafe extern "C" fn(
         p_instance: NDIlib_send_instance_t,
         p_video_data: *const NDIlib_video_frame_v2_t,
-    );
-    pub fn NDIlib_send_send_audio_v3(
+    ),
+    pub NDIlib_send_send_audio_v3: unsafe extern "C" fn(
         p_instance: NDIlib_send_instance_t,
         p_audio_data: *const NDIlib_audio_frame_v3_t,
-    );
+    ),
+    pub NDIlib_send_send_metadata: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ),
+    pub NDIlib_send_capture: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *mut NDIlib_metadata_frame_t,
+        timeout_in_ms: u32,
+    ) -> NDIlib_frame_type_e,
+    pub NDIlib_send_free_metadata: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ),
+    pub NDIlib_send_get_tally: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_tally: *mut NDIlib_tally_t,
+        timeout_in_ms: u32,
+    ) -> bool,
+    pub NDIlib_send_get_no_connections:
+        unsafe extern "C" fn(p_instance: NDIlib_send_instance_t, timeout_in_ms: u32) -> i32,
+    pub NDIlib_send_set_failover: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_failover_source: *const NDIlib_source_t,
+    ),
+    pub NDIlib_send_add_connection_metadata: unsafe extern "C" fn(
+        p_instance: NDIlib_send_instance_t,
+        p_metadata: *const NDIlib_metadata_frame_t,
+    ),
+    pub NDIlib_send_clear_connection_metadata:
+        unsafe extern "C" fn(p_instance: NDIlib_send_instance_t),
+    pub NDIlib_routing_create: unsafe extern "C" fn(
+        p_create_settings: *const NDIlib_routing_create_t,
+    ) -> NDIlib_routing_instance_t,
+    pub NDIlib_routing_destroy: unsafe extern "C" fn(p_instance: NDIlib_routing_instance_t),
+    pub NDIlib_routing_change: unsafe extern "C" fn(
+        p_instance: NDIlib_routing_instance_t,
+        p_source: *const NDIlib_source_t,
+    ) -> bool,
+    pub NDIlib_routing_clear: unsafe extern "C" fn(p_instance: NDIlib_routing_instance_t) -> bool,
+    // The frame synchronizer API with v3 audio frames is only available since NDI 5
+    pub NDIlib_framesync_create: Option<
+        unsafe extern "C" fn(p_receiver: NDIlib_recv_instance_t) -> NDIlib_framesync_instance_t,
+    >,
+    pub NDIlib_framesync_destroy:
+        Option<unsafe extern "C" fn(p_instance: NDIlib_framesync_instance_t)>,
+    pub NDIlib_framesync_capture_video: Option<
+        unsafe extern "C" fn(
+            p_instance: NDIlib_framesync_instance_t,
+            p_video_data: *mut NDIlib_video_frame_v2_t,
+            field_type: NDIlib_frame_format_type_e,
+        ),
+    >,
+    pub NDIlib_framesync_free_video: Option<
+        unsafe extern "C" fn(
+            p_instance: NDIlib_framesync_instance_t,
+            p_video_data: *mut NDIlib_video_frame_v2_t,
+        ),
+    >,
+    pub NDIlib_framesync_capture_audio_v2: Option<
+        unsafe extern "C" fn(
+            p_instance: NDIlib_framesync_instance_t,
+            p_audio_data: *mut NDIlib_audio_frame_v3_t,
+            sample_rate: i32,
+            no_channels: i32,
+            no_samples: i32,
+        ),
+    >,
+    pub NDIlib_framesync_free_audio_v2: Option<
+        unsafe extern "C" fn(
+            p_instance: NDIlib_framesync_instance_t,
+            p_audio_data: *mut NDIlib_audio_frame_v3_t,
+        ),
+    >,
+}
+
+macro_rules! load_symbol {
+    ($library:expr, $name:ident) => {
+        *$library
+            .get(concat!(stringify!($name), "\0").as_bytes())
+            .map_err(|err| {
+                glib::bool_error!("Failed to load symbol {}: {}", stringify!($name), err)
+            })?
+    };
+}
+
+macro_rules! load_optional_symbol {
+    ($library:expr, $name:ident) => {
+        $library
+            .get(concat!(stringify!($name), "\0").as_bytes())
+            .ok()
+            .map(|symbol| *symbol)
+    };
+}
+
+impl NdiLib {
+    fn candidates() -> Vec<path::PathBuf> {
+        if let Some(path) = env::var_os(LIBRARY_PATH_ENV) {
+            return vec![path::PathBuf::from(path)];
+        }
+
+        let mut candidates = vec![];
+        for dir in RUNTIME_DIR_ENVS.iter().filter_map(env::var_os) {
+            for name in LIBRARY_NAMES {
+                candidates.push(path::Path::new(&dir).join(name));
+            }
+        }
+        // Let the dynamic linker search the default locations last
+        for name in LIBRARY_NAMES {
+            candidates.push(path::PathBuf::from(name));
+        }
+
+        candidates
+    }
+
+    fn load() -> Result<Self, glib::BoolError> {
+        let candidates = Self::candidates();
+        let library = candidates
+            .iter()
+            .find_map(|path| unsafe { libloading::Library::new(path).ok() })
+            .ok_or_else(|| {
+                glib::bool_error!(
+                    "Failed to load NDI SDK library, tried {:?}. Set {} to the library path",
+                    candidates,
+                    LIBRARY_PATH_ENV,
+                )
+            })?;
+
+        unsafe {
+            Ok(NdiLib {
+                NDIlib_initialize: load_symbol!(library, NDIlib_initialize),
+                NDIlib_destroy: load_symbol!(library, NDIlib_destroy),
+                NDIlib_find_create_v2: load_symbol!(library, NDIlib_find_create_v2),
+                NDIlib_find_destroy: load_symbol!(library, NDIlib_find_destroy),
+                NDIlib_find_wait_for_sources: load_symbol!(library, NDIlib_find_wait_for_sources),
+                NDIlib_find_get_current_sources: load_symbol!(
+                    library,
+                    NDIlib_find_get_current_sources
+                ),
+                NDIlib_recv_create_v3: load_symbol!(library, NDIlib_recv_create_v3),
+                NDIlib_recv_destroy: load_symbol!(library, NDIlib_recv_destroy),
+                NDIlib_recv_connect: load_symbol!(library, NDIlib_recv_connect),
+                NDIlib_recv_set_tally: load_symbol!(library, NDIlib_recv_set_tally),
+                NDIlib_recv_send_metadata: load_symbol!(library, NDIlib_recv_send_metadata),
+                NDIlib_recv_add_connection_metadata: load_symbol!(
+                    library,
+                    NDIlib_recv_add_connection_metadata
+                ),
+                NDIlib_recv_capture_v3: load_symbol!(library, NDIlib_recv_capture_v3),
+                NDIlib_recv_free_video_v2: load_symbol!(library, NDIlib_recv_free_video_v2),
+                NDIlib_recv_free_audio_v3: load_symbol!(library, NDIlib_recv_free_audio_v3),
+                NDIlib_recv_free_metadata: load_symbol!(library, NDIlib_recv_free_metadata),
+                NDIlib_recv_get_queue: load_symbol!(library, NDIlib_recv_get_queue),
+                NDIlib_recv_get_no_connections: load_symbol!(
+                    library,
+                    NDIlib_recv_get_no_connections
+                ),
+                NDIlib_recv_get_web_control: load_symbol!(library, NDIlib_recv_get_web_control),
+                NDIlib_recv_free_string: load_symbol!(library, NDIlib_recv_free_string),
+                NDIlib_recv_get_performance: load_symbol!(library, NDIlib_recv_get_performance),
+                NDIlib_recv_ptz_is_supported: load_symbol!(library, NDIlib_recv_ptz_is_supported),
+                NDIlib_recv_ptz_store_preset: load_symbol!(library, NDIlib_recv_ptz_store_preset),
+                NDIlib_recv_ptz_recall_preset: load_symbol!(library, NDIlib_recv_ptz_recall_preset),
+                NDIlib_recv_ptz_zoom: load_symbol!(library, NDIlib_recv_ptz_zoom),
+                NDIlib_recv_ptz_zoom_speed: load_symbol!(library, NDIlib_recv_ptz_zoom_speed),
+                NDIlib_recv_ptz_pan_tilt: load_symbol!(library, NDIlib_recv_ptz_pan_tilt),
+                NDIlib_recv_ptz_pan_tilt_speed: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_pan_tilt_speed
+                ),
+                NDIlib_recv_ptz_auto_focus: load_symbol!(library, NDIlib_recv_ptz_auto_focus),
+                NDIlib_recv_ptz_focus: load_symbol!(library, NDIlib_recv_ptz_focus),
+                NDIlib_recv_ptz_focus_speed: load_symbol!(library, NDIlib_recv_ptz_focus_speed),
+                NDIlib_recv_ptz_white_balance_auto: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_white_balance_auto
+                ),
+                NDIlib_recv_ptz_white_balance_indoor: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_white_balance_indoor
+                ),
+                NDIlib_recv_ptz_white_balance_outdoor: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_white_balance_outdoor
+                ),
+                NDIlib_recv_ptz_white_balance_oneshot: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_white_balance_oneshot
+                ),
+                NDIlib_recv_ptz_white_balance_manual: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_white_balance_manual
+                ),
+                NDIlib_recv_ptz_exposure_auto: load_symbol!(library, NDIlib_recv_ptz_exposure_auto),
+                NDIlib_recv_ptz_exposure_manual: load_symbol!(
+                    library,
+                    NDIlib_recv_ptz_exposure_manual
+                ),
+                NDIlib_send_create: load_symbol!(library, NDIlib_send_create),
+                NDIlib_send_destroy: load_symbol!(library, NDIlib_send_destroy),
+                NDIlib_send_send_video_v2: load_symbol!(library, NDIlib_send_send_video_v2),
+                NDIlib_send_send_audio_v3: load_symbol!(library, NDIlib_send_send_audio_v3),
+                NDIlib_send_send_metadata: load_symbol!(library, NDIlib_send_send_metadata),
+                NDIlib_send_capture: load_symbol!(library, NDIlib_send_capture),
+                NDIlib_send_free_metadata: load_symbol!(library, NDIlib_send_free_metadata),
+                NDIlib_send_get_tally: load_symbol!(library, NDIlib_send_get_tally),
+                NDIlib_send_get_no_connections: load_symbol!(
+                    library,
+                    NDIlib_send_get_no_connections
+                ),
+                NDIlib_send_set_failover: load_symbol!(library, NDIlib_send_set_failover),
+                NDIlib_send_add_connection_metadata: load_symbol!(
+                    library,
+                    NDIlib_send_add_connection_metadata
+                ),
+                NDIlib_send_clear_connection_metadata: load_symbol!(
+                    library,
+                    NDIlib_send_clear_connection_metadata
+                ),
+                NDIlib_routing_create: load_symbol!(library, NDIlib_routing_create),
+                NDIlib_routing_destroy: load_symbol!(library, NDIlib_routing_destroy),
+                NDIlib_routing_change: load_symbol!(library, NDIlib_routing_change),
+                NDIlib_routing_clear: load_symbol!(library, NDIlib_routing_clear),
+                NDIlib_framesync_create: load_optional_symbol!(library, NDIlib_framesync_create),
+                NDIlib_framesync_destroy: load_optional_symbol!(library, NDIlib_framesync_destroy),
+                NDIlib_framesync_capture_video: load_optional_symbol!(
+                    library,
+                    NDIlib_framesync_capture_video
+                ),
+                NDIlib_framesync_free_video: load_optional_symbol!(
+                    library,
+                    NDIlib_framesync_free_video
+                ),
+                NDIlib_framesync_capture_audio_v2: load_optional_symbol!(
+                    library,
+                    NDIlib_framesync_capture_audio_v2
+                ),
+                NDIlib_framesync_free_audio_v2: load_optional_symbol!(
+                    library,
+                    NDIlib_framesync_free_audio_v2
+                ),
+                _library: library,
+            })
+        }
+    }
+}
+
+static NDILIB: OnceCell<Result<NdiLib, glib::BoolError>> = OnceCell::new();
+
+/// Loads the NDI SDK library if that didn't happen yet.
+pub fn load() -> Result<&'static NdiLib, glib::BoolError> {
+    NDILIB
+        .get_or_init(NdiLib::load)
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+/// Returns the NDI SDK library, which must have been loaded successfully before.
+pub fn lib() -> &'static NdiLib {
+    load().expect("NDI SDK library not loaded")
 }
 
 pub type NDIlib_find_instance_t = *mut ::std::os::raw::c_void;
@@ -215,6 +539,9 @@ pub const NDIlib_compressed_FourCC_type_HEVC: NDIlib_compressed_FourCC_type_e =
     make_fourcc(b"HEVC");
 #[cfg(feature = "advanced-sdk")]
 pub const NDIlib_compressed_FourCC_type_AAC: NDIlib_compressed_FourCC_type_e = 0x000000ff;
+#[cfg(feature = "advanced-sdk")]
+pub const NDIlib_compressed_FourCC_type_Opus: NDIlib_compressed_FourCC_type_e =
+    make_fourcc(b"Opus");
 
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -251,6 +578,17 @@ pub struct NDIlib_send_create_t {
 
 pub type NDIlib_send_instance_t = *mut ::std::os::raw::c_void;
 
+pub type NDIlib_framesync_instance_t = *mut ::std::os::raw::c_void;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct NDIlib_routing_create_t {
+    pub p_ndi_name: *const ::std::os::raw::c_char,
+    pub p_groups: *const ::std::os::raw::c_char,
+}
+
+pub type NDIlib_routing_instance_t = *mut ::std::os::raw::c_void;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct NDIlib_tally_t {
@@ -266,6 +604,14 @@ pub struct NDIlib_recv_queue_t {
     pub metadata_frames: i32,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct NDIlib_recv_performance_t {
+    pub video_frames: i64,
+    pub audio_frames: i64,
+    pub metadata_frames: i64,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct NDIlib_metadata_frame_t {
@@ -1,8 +1,9 @@
 use glib::prelude::*;
 use gst::prelude::*;
-use gst::{gst_debug, gst_error, gst_log, gst_trace, gst_warning};
+use gst::{gst_debug, gst_error, gst_info, gst_log, gst_trace, gst_warning};
 use gst_video::prelude::*;
 
+#[cfg(not(feature = "non-interleaved-audio"))]
 use byte_slice_cast::*;
 
 use std::cmp;
@@ -30,6 +31,10 @@ pub enum AudioInfo {
     OpusInfo {
         sample_rate: i32,
         no_channels: i32,
+        channel_mapping_family: i32,
+        stream_count: i32,
+        coupled_count: i32,
+        channel_mapping: Vec<u8>,
     },
     #[cfg(feature = "advanced-sdk")]
     AacInfo {
@@ -47,11 +52,35 @@ impl AudioInfo {
             AudioInfo::OpusInfo {
                 sample_rate,
                 no_channels,
-            } => Ok(gst::Caps::builder("audio/x-opus")
-                .field("channels", *no_channels)
-                .field("rate", *sample_rate)
-                .field("channel-mapping-family", 0i32)
-                .build()),
+                channel_mapping_family,
+                stream_count,
+                coupled_count,
+                channel_mapping,
+            } => {
+                let builder = gst::Caps::builder("audio/x-opus")
+                    .field("channels", *no_channels)
+                    .field("rate", *sample_rate)
+                    .field("channel-mapping-family", *channel_mapping_family);
+
+                // With mapping family 0 the streams and their mapping are implied by the channels
+                if *channel_mapping_family == 0 {
+                    return Ok(builder.build());
+                }
+
+                Ok(builder
+                    .field("stream-count", *stream_count)
+                    .field("coupled-count", *coupled_count)
+                    .field(
+                        "channel-mapping",
+                        gst::Array::from_owned(
+                            channel_mapping
+                                .iter()
+                                .map(|channel| (*channel as i32).to_send_value())
+                                .collect(),
+                        ),
+                    )
+                    .build())
+            }
             #[cfg(feature = "advanced-sdk")]
             AudioInfo::AacInfo {
                 sample_rate,
@@ -68,7 +97,7 @@ impl AudioInfo {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VideoInfo {
     VideoInfo(gst_video::VideoInfo),
     #[cfg(feature = "advanced-sdk")]
@@ -172,6 +201,7 @@ impl VideoInfo {
 pub enum Buffer {
     Audio(gst::Buffer, AudioInfo),
     Video(gst::Buffer, VideoInfo),
+    Metadata(gst::Buffer),
 }
 
 #[derive(Debug)]
@@ -184,7 +214,10 @@ pub enum ReceiverItem {
 
 pub struct ReceiverInner {
     queue: ReceiverQueue,
+    recv: RecvInstance,
     max_queue_length: usize,
+    // Output UYVA as AYUV instead of dropping the alpha plane
+    preserve_alpha: bool,
 
     observations: Observations,
 
@@ -193,6 +226,15 @@ pub struct ReceiverInner {
 
     timeout: u32,
     connect_timeout: u32,
+    timeout_action: TimeoutAction,
+    reconnect_interval: u32,
+    max_reconnect_attempts: u32,
+
+    // Program and preview tally of the sender as echoed to its receivers
+    tally_echo: Mutex<Option<(bool, bool)>>,
+
+    receiver_ndi_name: String,
+    failover_timeout: u32,
 
     thread: Mutex<Option<std::thread::JoinHandle<()>>>,
 }
@@ -214,11 +256,192 @@ struct ReceiverQueueInner {
     //
     // On timeout/error will contain a single item and then never be filled again
     buffer_queue: VecDeque<Buffer>,
+    // Number of buffers dropped because the queue was full
+    dropped_buffers: u64,
+    // Clock wait of the frame synchronizer thread, unscheduled when flushing or shutting down
+    clock_id: Option<gst::SingleShotClockId>,
+    // NDI name and URL/address of the current source, used for reconnecting
+    source: (Option<String>, Option<String>),
+    // Looks up the primary source by its NDI name before each connection, if needed
+    finder: Option<Arc<SourceFinder>>,
+    // Set while the primary source has to be looked up before connecting to it
+    resolve: bool,
+    // NDI name and URL/address of the primary source as last found by the finder
+    resolved_source: (Option<String>, Option<String>),
+    // Source to fail over to if the current one stops delivering frames
+    backup_source: Option<(Option<String>, Option<String>)>,
+    on_backup: bool,
+    // Incremented when switching to a different source. Frames captured for a previous generation
+    // are dropped, and the first buffers of the new one are marked as DISCONT.
+    generation: u64,
 
     error: Option<gst::FlowError>,
     timeout: bool,
 }
 
+impl ReceiverQueueInner {
+    fn active_source(&self) -> &(Option<String>, Option<String>) {
+        match self.backup_source {
+            Some(ref backup_source) if self.on_backup => backup_source,
+            // Not found yet
+            _ if self.finder.is_some() && self.resolved_source.0.is_none() => &self.source,
+            _ => self.primary_source(),
+        }
+    }
+
+    // The primary source is only known once found if it is looked up by the finder
+    fn primary_source(&self) -> &(Option<String>, Option<String>) {
+        if self.finder.is_some() {
+            &self.resolved_source
+        } else {
+            &self.source
+        }
+    }
+
+    fn unschedule(&mut self) {
+        if let Some(clock_id) = self.clock_id.take() {
+            clock_id.unschedule();
+        }
+    }
+}
+
+// Looks up the source with the discovery settings of the element by its NDI name, which can also
+// be a pattern depending on the NDI name match mode
+pub struct SourceFinder {
+    find: Mutex<FindInstance>,
+    matcher: NameMatcher,
+}
+
+enum NameMatcher {
+    Exact(String),
+    Glob(String),
+    Regex(Regex),
+}
+
+impl SourceFinder {
+    pub fn new(
+        ndi_name_match: NdiNameMatch,
+        ndi_name: &str,
+        groups: Option<&str>,
+        extra_ips: Option<&str>,
+        show_local_sources: bool,
+    ) -> Result<Self, gst::ErrorMessage> {
+        let matcher = match ndi_name_match {
+            NdiNameMatch::Exact => NameMatcher::Exact(String::from(ndi_name)),
+            NdiNameMatch::Glob => NameMatcher::Glob(String::from(ndi_name)),
+            NdiNameMatch::Regex => NameMatcher::Regex(Regex::new(ndi_name).map_err(|err| {
+                gst::error_msg!(
+                    gst::LibraryError::Settings,
+                    ["Invalid regular expression {}: {}", ndi_name, err]
+                )
+            })?),
+        };
+
+        let mut builder = FindInstance::builder().show_local_sources(show_local_sources);
+        if let Some(groups) = groups {
+            builder = builder.groups(groups);
+        }
+        if let Some(extra_ips) = extra_ips {
+            builder = builder.extra_ips(extra_ips);
+        }
+
+        let find = builder.build().ok_or_else(|| {
+            gst::error_msg!(gst::LibraryError::Init, ["Failed to create Find instance"])
+        })?;
+
+        Ok(SourceFinder {
+            find: Mutex::new(find),
+            matcher,
+        })
+    }
+
+    // Returns the NDI name and URL/address of the matching source, waiting at most `timeout`
+    // milliseconds for changes if none is known yet. If multiple sources match, the one with the
+    // smallest NDI name is used.
+    fn find_source(&self, timeout: u32) -> Option<(String, String)> {
+        let mut find = self.find.lock().unwrap();
+
+        let source = self.matching_source(&mut find);
+        if source.is_none() && timeout > 0 && find.wait_for_sources(timeout) {
+            self.matching_source(&mut find)
+        } else {
+            source
+        }
+    }
+
+    fn matching_source(&self, find: &mut FindInstance) -> Option<(String, String)> {
+        find.get_current_sources()
+            .iter()
+            .filter(|source| self.matches(source.ndi_name()))
+            .min_by(|a, b| (a.ndi_name(), a.url_address()).cmp(&(b.ndi_name(), b.url_address())))
+            .map(|source| {
+                (
+                    String::from(source.ndi_name()),
+                    String::from(source.url_address()),
+                )
+            })
+    }
+
+    fn matches(&self, ndi_name: &str) -> bool {
+        match self.matcher {
+            NameMatcher::Exact(ref name) => name == ndi_name,
+            NameMatcher::Glob(ref pattern) => glib::pattern_match_simple(pattern, ndi_name),
+            NameMatcher::Regex(ref regex) => regex.is_match(ndi_name),
+        }
+    }
+}
+
+// Compiled regular expression that has to match the whole string, like glob patterns
+struct Regex(std::ptr::NonNull<glib::ffi::GRegex>);
+
+// GRegex is immutable after creation
+unsafe impl Send for Regex {}
+unsafe impl Sync for Regex {}
+
+impl Regex {
+    fn new(pattern: &str) -> Result<Self, glib::Error> {
+        // The pattern is checked on its own first so that it can't close the anchoring group
+        Self::compile(pattern)?;
+        Self::compile(&format!("\\A(?:{})\\z", pattern))
+    }
+
+    fn compile(pattern: &str) -> Result<Self, glib::Error> {
+        use glib::translate::from_glib_full;
+
+        let pattern = std::ffi::CString::new(pattern).unwrap();
+        unsafe {
+            let mut error = std::ptr::null_mut();
+            let regex = glib::ffi::g_regex_new(pattern.as_ptr(), 0, 0, &mut error);
+            match std::ptr::NonNull::new(regex) {
+                Some(regex) => Ok(Regex(regex)),
+                None => Err(from_glib_full(error)),
+            }
+        }
+    }
+
+    fn is_match(&self, s: &str) -> bool {
+        unsafe {
+            glib::ffi::g_regex_match_full(
+                self.0.as_ptr(),
+                s.as_ptr() as *const _,
+                s.len() as isize,
+                0,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            ) != glib::ffi::GFALSE
+        }
+    }
+}
+
+impl Drop for Regex {
+    fn drop(&mut self) {
+        unsafe {
+            glib::ffi::g_regex_unref(self.0.as_ptr());
+        }
+    }
+}
+
 const WINDOW_LENGTH: u64 = 512;
 const WINDOW_DURATION: u64 = 2_000_000_000;
 
@@ -262,6 +485,10 @@ impl Observations {
         Self(Arc::new(Mutex::new(ObservationsInner::default())))
     }
 
+    fn skew(&self) -> i64 {
+        self.0.lock().unwrap().skew
+    }
+
     // Based on the algorithm used in GStreamer's rtpjitterbuffer, which comes from
     // Fober, Orlarey and Letz, 2005, "Real Time Clock Skew Estimation over Network Delays":
     // http://citeseerx.ist.psu.edu/viewdoc/summary?doi=10.1.1.102.1546
@@ -429,6 +656,31 @@ impl Observations {
 
         (gst::ClockTime::from_nseconds(out_time), duration, false)
     }
+
+    // Maps the remote time with the current skew estimation without updating it, or returns the
+    // local time if there is no estimation for it yet
+    fn map(&self, time: (Option<gst::ClockTime>, gst::ClockTime)) -> gst::ClockTime {
+        let inner = self.0.lock().unwrap();
+
+        let (remote_time, base_remote_time, base_local_time) =
+            match (time.0, inner.base_remote_time, inner.base_local_time) {
+                (Some(remote), Some(base_remote), Some(base_local))
+                    if remote.nseconds() >= base_remote =>
+                {
+                    (remote.nseconds(), base_remote, base_local)
+                }
+                _ => return time.1,
+            };
+
+        let out_time = base_local_time + (remote_time - base_remote_time);
+        let out_time = if inner.skew < 0 {
+            out_time.saturating_sub((-inner.skew) as u64)
+        } else {
+            out_time + (inner.skew as u64)
+        };
+
+        gst::ClockTime::from_nseconds(out_time)
+    }
 }
 
 impl Default for TimeMapping {
@@ -442,15 +694,123 @@ impl Default for TimeMapping {
     }
 }
 
+// State of a capture thread for detecting when to switch between primary and backup source
+struct FailoverState {
+    // Generation of the source the state refers to
+    generation: u64,
+    // When the connection to the current source was started
+    connecting: time::Instant,
+    // Total number of received audio and video frames and when it last changed, if frames were
+    // received from the current source at all
+    frames: Option<i64>,
+    last_frame: Option<time::Instant>,
+    // Receiver connected to the primary source while the backup source is active
+    probe: Option<Probe>,
+}
+
+impl FailoverState {
+    fn new(generation: u64) -> Self {
+        Self {
+            generation,
+            connecting: time::Instant::now(),
+            frames: None,
+            last_frame: None,
+            probe: None,
+        }
+    }
+}
+
+struct Probe {
+    recv: RecvInstance,
+    source: (Option<String>, Option<String>),
+    created: time::Instant,
+    frames: i64,
+    // Since when the primary source delivers frames without interruption, and when it delivered
+    // the last one
+    delivering: Option<(time::Instant, time::Instant)>,
+}
+
 #[derive(Clone)]
 pub struct ReceiverControlHandle {
     queue: ReceiverQueue,
+    recv: RecvInstance,
+    observations: Observations,
 }
 
 impl ReceiverControlHandle {
+    pub fn recv(&self) -> &RecvInstance {
+        &self.recv
+    }
+
+    pub fn set_tally(&self, tally: &Tally) -> bool {
+        self.recv.set_tally(tally)
+    }
+
+    pub fn stats(&self) -> gst::Structure {
+        let (total, dropped) = self.recv.get_performance();
+        let sdk_queue = self.recv.get_queue();
+        let (queued, dropped_buffers) = {
+            let queue = (self.queue.0).0.lock().unwrap();
+            (queue.buffer_queue.len() as u32, queue.dropped_buffers)
+        };
+
+        gst::Structure::builder("application/x-ndi-receiver-stats")
+            .field("video-frames", total.video_frames())
+            .field("audio-frames", total.audio_frames())
+            .field("metadata-frames", total.metadata_frames())
+            .field("dropped-video-frames", dropped.video_frames())
+            .field("dropped-audio-frames", dropped.audio_frames())
+            .field("dropped-metadata-frames", dropped.metadata_frames())
+            .field("queued-video-frames", sdk_queue.video_frames())
+            .field("queued-audio-frames", sdk_queue.audio_frames())
+            .field("queued-metadata-frames", sdk_queue.metadata_frames())
+            .field("queued-buffers", queued)
+            .field("dropped-buffers", dropped_buffers)
+            .field("skew", self.observations.skew())
+            .build()
+    }
+
+    // NDI name and URL/address of the primary source, if known
+    pub fn resolved_source(&self) -> (Option<String>, Option<String>) {
+        let queue = (self.queue.0).0.lock().unwrap();
+        queue.primary_source().clone()
+    }
+
+    // Switches the existing receiver to a different source, which is looked up by the finder
+    // first if given. Buffers of the previous source that are still queued are dropped and the
+    // timestamp observations are restarted.
+    pub fn connect(
+        &self,
+        ndi_name: Option<&str>,
+        url_address: Option<&str>,
+        finder: Option<SourceFinder>,
+    ) {
+        let mut queue = (self.queue.0).0.lock().unwrap();
+
+        // Stays disconnected until the finder found the source
+        if finder.is_none() {
+            self.recv.connect(Some(&Source::new(ndi_name, url_address)));
+        } else {
+            self.recv.connect(None);
+        }
+
+        queue.source = (ndi_name.map(String::from), url_address.map(String::from));
+        queue.resolve = finder.is_some();
+        queue.finder = finder.map(Arc::new);
+        queue.resolved_source = (None, None);
+        queue.on_backup = false;
+        queue.buffer_queue.clear();
+        queue.generation += 1;
+        *self.observations.0.lock().unwrap() = ObservationsInner::default();
+        (self.queue.0).1.notify_all();
+    }
+
     pub fn set_flushing(&self, flushing: bool) {
         let mut queue = (self.queue.0).0.lock().unwrap();
         queue.flushing = flushing;
+        if flushing {
+            queue.unschedule();
+        }
         (self.queue.0).1.notify_all();
     }
 
@@ -462,6 +822,7 @@ impl ReceiverControlHandle {
     pub fn shutdown(&self) {
         let mut queue = (self.queue.0).0.lock().unwrap();
         queue.shutdown = true;
+        queue.unschedule();
         (self.queue.0).1.notify_all();
     }
 }
@@ -484,10 +845,20 @@ impl Drop for ReceiverInner {
 impl Receiver {
     fn new(
         recv: RecvInstance,
+        framesync: Option<FramesyncInstance>,
+        source: (Option<String>, Option<String>),
+        finder: Option<SourceFinder>,
+        backup_source: Option<(Option<String>, Option<String>)>,
+        receiver_ndi_name: &str,
+        failover_timeout: u32,
         timestamp_mode: TimestampMode,
         timeout: u32,
         connect_timeout: u32,
+        timeout_action: TimeoutAction,
+        reconnect_interval: u32,
+        max_reconnect_attempts: u32,
         max_queue_length: usize,
+        preserve_alpha: bool,
         element: &gst_base::BaseSrc,
     ) -> Self {
         let receiver = Receiver(Arc::new(ReceiverInner {
@@ -497,27 +868,47 @@ impl Receiver {
                     playing: false,
                     flushing: false,
                     buffer_queue: VecDeque::with_capacity(max_queue_length),
+                    dropped_buffers: 0,
+                    clock_id: None,
+                    source,
+                    resolve: finder.is_some(),
+                    finder: finder.map(Arc::new),
+                    resolved_source: (None, None),
+                    backup_source,
+                    on_backup: false,
+                    generation: 0,
                     error: None,
                     timeout: false,
                 }),
                 Condvar::new(),
             ))),
+            recv: recv.clone(),
             max_queue_length,
+            preserve_alpha,
             observations: Observations::new(),
             element: element.downgrade(),
             timestamp_mode,
             timeout,
             connect_timeout,
+            timeout_action,
+            reconnect_interval,
+            max_reconnect_attempts,
+            tally_echo: Mutex::new(None),
+            receiver_ndi_name: String::from(receiver_ndi_name),
+            failover_timeout,
             thread: Mutex::new(None),
         }));
 
+        receiver.post_connection_state(element, "connecting", None);
+
         let weak = Arc::downgrade(&receiver.0);
         let thread = thread::spawn(move || {
             use std::panic;
 
             let weak_clone = weak.clone();
-            match panic::catch_unwind(panic::AssertUnwindSafe(move || {
-                Self::receive_thread(&weak_clone, recv)
+            match panic::catch_unwind(panic::AssertUnwindSafe(move || match framesync {
+                Some(framesync) => Self::framesync_thread(&weak_clone, recv, framesync),
+                None => Self::receive_thread(&weak_clone, recv),
             })) {
                 Ok(_) => (),
                 Err(_) => {
@@ -546,12 +937,17 @@ impl Receiver {
     pub fn receiver_control_handle(&self) -> ReceiverControlHandle {
         ReceiverControlHandle {
             queue: self.0.queue.clone(),
+            recv: self.0.recv.clone(),
+            observations: self.0.observations.clone(),
         }
     }
 
     pub fn set_flushing(&self, flushing: bool) {
         let mut queue = (self.0.queue.0).0.lock().unwrap();
         queue.flushing = flushing;
+        if flushing {
+            queue.unschedule();
+        }
         (self.0.queue.0).1.notify_all();
     }
 
@@ -563,6 +959,7 @@ impl Receiver {
     pub fn shutdown(&self) {
         let mut queue = (self.0.queue.0).0.lock().unwrap();
         queue.shutdown = true;
+        queue.unschedule();
         (self.0.queue.0).1.notify_all();
     }
 
@@ -587,13 +984,25 @@ impl Receiver {
         element: &gst_base::BaseSrc,
         ndi_name: Option<&str>,
         url_address: Option<&str>,
+        finder: Option<SourceFinder>,
+        backup_ndi_name: Option<&str>,
+        backup_url_address: Option<&str>,
+        failover_timeout: u32,
         receiver_ndi_name: &str,
+        receiver_metadata: Option<&str>,
+        hwaccel: bool,
         connect_timeout: u32,
         bandwidth: NDIlib_recv_bandwidth_e,
         color_format: NDIlib_recv_color_format_e,
+        preserve_alpha: bool,
+        receive_mode: ReceiveMode,
         timestamp_mode: TimestampMode,
         timeout: u32,
+        timeout_action: TimeoutAction,
+        reconnect_interval: u32,
+        max_reconnect_attempts: u32,
         max_queue_length: usize,
+        tally: &Tally,
     ) -> Option<Self> {
         gst_debug!(CAT, obj: element, "Starting NDI connection...");
 
@@ -607,9 +1016,16 @@ impl Receiver {
             url_address,
         );
 
+        // With a finder the receiver is only connected once the source was found
+        let (connect_ndi_name, connect_url_address) = if finder.is_some() {
+            (None, None)
+        } else {
+            (ndi_name, url_address)
+        };
+
         // FIXME: Ideally we would use NDIlib_recv_color_format_fastest here but that seems to be
         // broken with interlaced content currently
-        let recv = RecvInstance::builder(ndi_name, url_address, receiver_ndi_name)
+        let recv = RecvInstance::builder(connect_ndi_name, connect_url_address, receiver_ndi_name)
             .bandwidth(bandwidth)
             .color_format(color_format)
             .allow_video_fields(true)
@@ -626,18 +1042,57 @@ impl Receiver {
             Some(recv) => recv,
         };
 
-        recv.set_tally(&Tally::default());
+        recv.set_tally(tally);
+
+        // Sent to the source on every connection, including reconnections, switches and failovers
+        if hwaccel {
+            let enable_hw_accel = MetadataFrame::new(0, Some("<ndi_hwaccel enabled=\"true\"/>"));
+            recv.add_connection_metadata(&enable_hw_accel);
+        }
 
-        let enable_hw_accel = MetadataFrame::new(0, Some("<ndi_hwaccel enabled=\"true\"/>"));
-        recv.send_metadata(&enable_hw_accel);
+        if let Some(receiver_metadata) = receiver_metadata {
+            recv.add_connection_metadata(&MetadataFrame::new(0, Some(receiver_metadata)));
+        }
+
+        let framesync = match receive_mode {
+            ReceiveMode::Capture => None,
+            ReceiveMode::Framesync => match FramesyncInstance::new(&recv) {
+                None => {
+                    gst::element_error!(
+                        element,
+                        gst::LibraryError::Init,
+                        ["Frame synchronizer not supported by the NDI SDK"]
+                    );
+                    return None;
+                }
+                Some(framesync) => Some(framesync),
+            },
+        };
 
         // This will set info.audio/video accordingly
         let receiver = Receiver::new(
             recv,
+            framesync,
+            (ndi_name.map(String::from), url_address.map(String::from)),
+            finder,
+            if backup_ndi_name.is_some() || backup_url_address.is_some() {
+                Some((
+                    backup_ndi_name.map(String::from),
+                    backup_url_address.map(String::from),
+                ))
+            } else {
+                None
+            },
+            receiver_ndi_name,
+            failover_timeout,
             timestamp_mode,
             timeout,
             connect_timeout,
+            timeout_action,
+            reconnect_interval,
+            max_reconnect_attempts,
             max_queue_length,
+            preserve_alpha,
             element,
         );
 
@@ -647,8 +1102,16 @@ impl Receiver {
     fn receive_thread(receiver: &Weak<ReceiverInner>, recv: RecvInstance) {
         let mut first_video_frame = true;
         let mut first_audio_frame = true;
+        let mut first_metadata_frame = true;
         let mut first_frame = true;
         let mut timer = time::Instant::now();
+        // Number of reconnection attempts since the last received frame
+        let mut reconnect_attempts = 0;
+        // Last video buffer, repeated while reconnecting
+        let mut last_video_buffer: Option<(gst::Buffer, VideoInfo)> = None;
+        let mut connected = false;
+        let mut failover = FailoverState::new(0);
+        let mut generation = 0;
 
         // Capture until error or shutdown
         loop {
@@ -662,6 +1125,8 @@ impl Receiver {
                 Some(element) => element,
             };
 
+            receiver.check_failover(&element, &mut failover);
+
             let flushing = {
                 let queue = (receiver.0.queue.0).0.lock().unwrap();
                 if queue.shutdown {
@@ -675,16 +1140,49 @@ impl Receiver {
                     return;
                 }
 
+                if queue.generation != generation {
+                    gst_debug!(CAT, obj: &element, "Source changed");
+                    generation = queue.generation;
+                    first_video_frame = true;
+                    first_audio_frame = true;
+                    first_metadata_frame = true;
+                    first_frame = true;
+                    timer = time::Instant::now();
+                    reconnect_attempts = 0;
+                    last_video_buffer = None;
+                    connected = false;
+                    receiver.post_connection_state(&element, "connecting", None);
+                }
+
                 queue.flushing
             };
 
-            let timeout = if first_frame {
+            let timeout = if reconnect_attempts > 0 {
+                receiver.0.reconnect_interval
+            } else if first_frame {
                 receiver.0.connect_timeout
             } else {
                 receiver.0.timeout
             };
 
-            let res = match recv.capture(50) {
+            // Wake up often enough to repeat the last video frame at its frame rate
+            let capture_timeout = match last_video_buffer {
+                Some((ref buffer, _)) if reconnect_attempts > 0 => buffer
+                    .duration()
+                    .map(|duration| duration.mseconds().clamp(1, 50) as u32)
+                    .unwrap_or(50),
+                _ => 50,
+            };
+
+            // Metadata attached to audio/video frames, output as separate buffer after the frame
+            let mut frame_metadata = None;
+
+            // Nothing to capture until the source was found
+            let res = match if receiver.resolve_source(&element, capture_timeout) {
+                Ok(None)
+            } else {
+                recv.capture(capture_timeout)
+            } {
                 _ if flushing => {
                     gst_debug!(CAT, obj: &element, "Flushing");
                     Err(gst::FlowError::Flushing)
@@ -698,15 +1196,42 @@ impl Receiver {
                     Err(gst::FlowError::Error)
                 }
                 Ok(None) if timeout > 0 && timer.elapsed().as_millis() >= timeout as u128 => {
-                    gst_debug!(CAT, obj: &element, "Timed out -- assuming EOS",);
-                    Err(gst::FlowError::Eos)
+                    match receiver.handle_timeout(
+                        &element,
+                        &recv,
+                        &mut reconnect_attempts,
+                        &mut connected,
+                    ) {
+                        Ok(()) => {
+                            first_video_frame = true;
+                            first_audio_frame = true;
+                            first_metadata_frame = true;
+                            timer = time::Instant::now();
+                            continue;
+                        }
+                        Err(err) => Err(err),
+                    }
                 }
                 Ok(None) => {
                     gst_debug!(CAT, obj: &element, "No frame received yet, retry");
+                    if reconnect_attempts > 0 {
+                        if let Some((ref buffer, ref info)) = last_video_buffer {
+                            if let Some(buffer) = Self::repeat_video_buffer(&element, buffer) {
+                                let info = info.clone();
+                                last_video_buffer = Some((buffer.clone(), info.clone()));
+                                receiver.queue_buffers(
+                                    &element,
+                                    generation,
+                                    Some(Buffer::Video(buffer, info)),
+                                );
+                            }
+                        }
+                    }
                     continue;
                 }
                 Ok(Some(Frame::Video(frame))) => {
                     first_frame = false;
+                    frame_metadata = frame.metadata().map(String::from);
                     let mut buffer = receiver.create_video_buffer_and_info(&element, frame);
                     if first_video_frame {
                         if let Ok(Buffer::Video(ref mut buffer, _)) = buffer {
@@ -721,9 +1246,10 @@ impl Receiver {
                 }
                 Ok(Some(Frame::Audio(frame))) => {
                     first_frame = false;
+                    frame_metadata = frame.metadata().map(String::from);
                     let mut buffer = receiver.create_audio_buffer_and_info(&element, frame);
                     if first_audio_frame {
-                        if let Ok(Buffer::Video(ref mut buffer, _)) = buffer {
+                        if let Ok(Buffer::Audio(ref mut buffer, _)) = buffer {
                             buffer
                                 .get_mut()
                                 .unwrap()
@@ -733,62 +1259,847 @@ impl Receiver {
                     }
                     buffer
                 }
+                Ok(Some(Frame::StatusChange)) => {
+                    receiver.handle_status_change(&element, &mut connected);
+                    continue;
+                }
                 Ok(Some(Frame::Metadata(frame))) => {
                     if let Some(metadata) = frame.metadata() {
-                        gst_debug!(
-                            CAT,
-                            obj: &element,
-                            "Received metadata at timecode {}: {}",
-                            gst::ClockTime::from_nseconds(frame.timecode() as u64 * 100),
-                            metadata,
+                        receiver.handle_tally_echo(&element, metadata);
+                    }
+                    let mut buffer = match receiver.create_metadata_buffer(&element, frame) {
+                        Some(buffer) => buffer,
+                        None => continue,
+                    };
+                    if first_metadata_frame {
+                        if let Ok(Buffer::Metadata(ref mut buffer)) = buffer {
+                            buffer
+                                .get_mut()
+                                .unwrap()
+                                .set_flags(gst::BufferFlags::DISCONT);
+                            first_metadata_frame = false;
+                        }
+                    }
+                    buffer
+                }
+            };
+
+            match res {
+                Ok(item) => {
+                    let metadata_item = match (frame_metadata, &item) {
+                        (Some(metadata), Buffer::Audio(buffer, _))
+                        | (Some(metadata), Buffer::Video(buffer, _)) => {
+                            Self::create_metadata_buffer_with_data(
+                                &element,
+                                &metadata,
+                                buffer.pts(),
+                                buffer.duration(),
+                            )
+                        }
+                        _ => None,
+                    }
+                    .map(|mut buffer| {
+                        if first_metadata_frame {
+                            buffer
+                                .get_mut()
+                                .unwrap()
+                                .set_flags(gst::BufferFlags::DISCONT);
+                            first_metadata_frame = false;
+                        }
+
+                        Buffer::Metadata(buffer)
+                    });
+
+                    if reconnect_attempts > 0 {
+                        gst_info!(CAT, obj: &element, "Reconnected");
+                        reconnect_attempts = 0;
+                    }
+                    if !connected {
+                        receiver.post_connection_state(&element, "connected", None);
+                        connected = true;
+                    }
+                    if let Buffer::Video(ref buffer, ref info) = item {
+                        last_video_buffer = Some((buffer.clone(), info.clone()));
+                    }
+
+                    receiver.queue_buffers(
+                        &element,
+                        generation,
+                        Some(item).into_iter().chain(metadata_item),
+                    );
+                    timer = time::Instant::now();
+                }
+                Err(gst::FlowError::Eos) => {
+                    gst_debug!(CAT, obj: &element, "Signalling EOS");
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    queue.timeout = true;
+                    (receiver.0.queue.0).1.notify_one();
+                    break;
+                }
+                Err(gst::FlowError::Flushing) => {
+                    // Flushing, nothing to be done here except for emptying our queue
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    queue.buffer_queue.clear();
+                    (receiver.0.queue.0).1.notify_one();
+                    timer = time::Instant::now();
+                }
+                Err(err) => {
+                    gst_error!(CAT, obj: &element, "Signalling error");
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    if queue.error.is_none() {
+                        queue.error = Some(err);
+                    }
+                    (receiver.0.queue.0).1.notify_one();
+                    break;
+                }
+            }
+        }
+    }
+
+    // Pulls one video frame and the corresponding amount of audio from the frame synchronizer
+    // per tick of the pipeline clock. The tick rate is the frame rate of the received video, or
+    // 25 fps as long as no video was received.
+    fn framesync_thread(
+        receiver: &Weak<ReceiverInner>,
+        recv: RecvInstance,
+        framesync: FramesyncInstance,
+    ) {
+        let mut first_video_frame = true;
+        let mut first_audio_frame = true;
+        let mut first_metadata_frame = true;
+        let mut received = false;
+        let mut timer = time::Instant::now();
+        let mut reconnect_attempts = 0;
+        let mut connected = false;
+        let mut failover = FailoverState::new(0);
+        let mut generation = 0;
+
+        let mut frame_rate = (25, 1);
+        // Running time of the first tick at the current frame rate and number of ticks since
+        let mut ticks: Option<(gst::ClockTime, u64)> = None;
+        let mut last_video_timecode = None;
+
+        loop {
+            let receiver = match receiver.upgrade().map(Receiver) {
+                None => break,
+                Some(receiver) => receiver,
+            };
+
+            let element = match receiver.0.element.upgrade() {
+                None => return,
+                Some(element) => element,
+            };
+
+            let clock = element.clock().zip(element.base_time());
+
+            receiver.check_failover(&element, &mut failover);
+            receiver.resolve_source(&element, 0);
+
+            let clock_id = {
+                let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                if queue.shutdown {
+                    gst_debug!(CAT, obj: &element, "Shutting down");
+                    break;
+                }
+
+                // If an error happened in the meantime, just go out of here
+                if queue.error.is_some() {
+                    gst_error!(CAT, obj: &element, "Error while waiting for connection");
+                    return;
+                }
+
+                // The timeline continues but the frames of the new source are not continuous
+                if queue.generation != generation {
+                    gst_debug!(CAT, obj: &element, "Source changed");
+                    generation = queue.generation;
+                    first_video_frame = true;
+                    first_audio_frame = true;
+                    first_metadata_frame = true;
+                    last_video_timecode = None;
+                    received = false;
+                    timer = time::Instant::now();
+                    reconnect_attempts = 0;
+                    connected = false;
+                    receiver.post_connection_state(&element, "connecting", None);
+                }
+
+                // Nothing to be done until flushing stopped or we have a clock
+                let (clock, base_time) = match clock {
+                    Some(clock) if !queue.flushing => clock,
+                    _ => {
+                        if queue.flushing {
+                            gst_debug!(CAT, obj: &element, "Flushing");
+                            queue.buffer_queue.clear();
+                        }
+                        ticks = None;
+                        let _ = (receiver.0.queue.0)
+                            .1
+                            .wait_timeout(queue, time::Duration::from_millis(50))
+                            .unwrap();
+                        timer = time::Instant::now();
+                        continue;
+                    }
+                };
+
+                let running_time = clock.time().map(|now| now.saturating_sub(base_time));
+                let (start, n) = match (ticks, running_time) {
+                    (Some(ticks), _) => ticks,
+                    (None, Some(running_time)) => (running_time, 0),
+                    (None, None) => continue,
+                };
+                ticks = Some((start, n));
+
+                let pts = start + Self::tick_offset(frame_rate, n);
+                let clock_id = clock.new_single_shot_id(base_time + pts);
+                queue.clock_id = Some(clock_id.clone());
+
+                clock_id
+            };
+
+            let (res, jitter) = clock_id.wait();
+            (receiver.0.queue.0).0.lock().unwrap().clock_id = None;
+
+            let (start, n) = ticks.unwrap();
+            let pts = start + Self::tick_offset(frame_rate, n);
+            let duration = start + Self::tick_offset(frame_rate, n + 1) - pts;
+
+            match res {
+                Err(gst::ClockError::Unscheduled) => {
+                    gst_debug!(CAT, obj: &element, "Clock wait unscheduled");
+                    continue;
+                }
+                Err(gst::ClockError::Early) if jitter > duration.nseconds() as i64 => {
+                    gst_debug!(
+                        CAT,
+                        obj: &element,
+                        "Late by {}, resyncing",
+                        gst::ClockTime::from_nseconds(jitter as u64)
+                    );
+                    ticks = None;
+                    first_video_frame = true;
+                    first_audio_frame = true;
+                    continue;
+                }
+                _ => (),
+            }
+            ticks = Some((start, n + 1));
+            let tick_frame_rate = frame_rate;
+
+            let mut buffers = Vec::new();
+            let mut res = Ok(());
+
+            if let Some(video_frame) = framesync
+                .capture_video(NDIlib_frame_format_type_e::NDIlib_frame_format_type_progressive)
+            {
+                received = true;
+
+                // Continue at the new frame rate after this frame
+                if video_frame.frame_rate() != frame_rate
+                    && video_frame.frame_rate().0 > 0
+                    && video_frame.frame_rate().1 > 0
+                {
+                    gst_debug!(
+                        CAT,
+                        obj: &element,
+                        "Frame rate changed to {}/{}",
+                        video_frame.frame_rate().0,
+                        video_frame.frame_rate().1
+                    );
+                    frame_rate = video_frame.frame_rate();
+                    ticks = Some((pts + duration, 0));
+                }
+
+                // Frames are repeated if no new frame was received, and their metadata should
+                // only be output once
+                let frame_metadata = if last_video_timecode != Some(video_frame.timecode()) {
+                    last_video_timecode = Some(video_frame.timecode());
+                    video_frame.metadata().map(String::from)
+                } else {
+                    None
+                };
+
+                match receiver.create_framesync_video_buffer_and_info(
+                    &element,
+                    video_frame,
+                    pts,
+                    duration,
+                ) {
+                    Ok(Buffer::Video(mut buffer, info)) => {
+                        if first_video_frame {
+                            buffer
+                                .get_mut()
+                                .unwrap()
+                                .set_flags(gst::BufferFlags::DISCONT);
+                            first_video_frame = false;
+                        }
+                        buffers.push(Buffer::Video(buffer, info));
+
+                        if let Some(buffer) = frame_metadata.and_then(|metadata| {
+                            Self::create_metadata_buffer_with_data(
+                                &element,
+                                &metadata,
+                                pts,
+                                Some(duration),
+                            )
+                        }) {
+                            buffers.push(Buffer::Metadata(buffer));
+                        }
+                    }
+                    Ok(_) => unreachable!(),
+                    Err(err) => res = Err(err),
+                }
+            }
+
+            if let (Ok(()), Some((sample_rate, no_channels))) = (&res, framesync.audio_format()) {
+                received = true;
+
+                let no_samples = Self::tick_samples(tick_frame_rate, sample_rate, n + 1)
+                    - Self::tick_samples(tick_frame_rate, sample_rate, n);
+
+                if let Some(audio_frame) =
+                    framesync.capture_audio(sample_rate, no_channels, no_samples as i32)
+                {
+                    match receiver.create_framesync_audio_buffer_and_info(
+                        &element,
+                        audio_frame,
+                        pts,
+                    ) {
+                        Ok(Buffer::Audio(mut buffer, info)) => {
+                            if first_audio_frame {
+                                buffer
+                                    .get_mut()
+                                    .unwrap()
+                                    .set_flags(gst::BufferFlags::DISCONT);
+                                first_audio_frame = false;
+                            }
+                            buffers.push(Buffer::Audio(buffer, info));
+                        }
+                        Ok(_) => unreachable!(),
+                        Err(err) => res = Err(err),
+                    }
+                }
+            }
+
+            // Metadata frames are still captured from the receiver directly
+            while res.is_ok() {
+                match recv.capture_metadata(0) {
+                    Err(_) => {
+                        gst::element_error!(
+                            element,
+                            gst::ResourceError::Read,
+                            ["Error receiving frame"]
                         );
+                        res = Err(gst::FlowError::Error);
+                    }
+                    Ok(None) => break,
+                    Ok(Some(Frame::StatusChange)) => {
+                        receiver.handle_status_change(&element, &mut connected)
+                    }
+                    Ok(Some(Frame::Metadata(frame))) => {
+                        if let Some(metadata) = frame.metadata() {
+                            receiver.handle_tally_echo(&element, metadata);
+                        }
+
+                        match receiver.create_metadata_buffer(&element, frame) {
+                            None => (),
+                            Some(Ok(Buffer::Metadata(mut buffer))) => {
+                                if first_metadata_frame {
+                                    buffer
+                                        .get_mut()
+                                        .unwrap()
+                                        .set_flags(gst::BufferFlags::DISCONT);
+                                    first_metadata_frame = false;
+                                }
+                                buffers.push(Buffer::Metadata(buffer));
+                            }
+                            Some(Ok(_)) => unreachable!(),
+                            Some(Err(err)) => res = Err(err),
+                        }
+                    }
+                    Ok(Some(_)) => unreachable!(),
+                }
+            }
+
+            // The frame synchronizer repeats the last frames once connected, so only the initial
+            // connection can time out
+            let timeout = if reconnect_attempts > 0 {
+                receiver.0.reconnect_interval
+            } else {
+                receiver.0.connect_timeout
+            };
+            if res.is_ok()
+                && !received
+                && timeout > 0
+                && timer.elapsed().as_millis() >= timeout as u128
+            {
+                match receiver.handle_timeout(
+                    &element,
+                    &recv,
+                    &mut reconnect_attempts,
+                    &mut connected,
+                ) {
+                    Ok(()) => timer = time::Instant::now(),
+                    Err(err) => res = Err(err),
+                }
+            } else if received && reconnect_attempts > 0 {
+                gst_info!(CAT, obj: &element, "Reconnected");
+                reconnect_attempts = 0;
+            }
+
+            // The frame synchronizer keeps repeating the last frames after the sender went away
+            if received && !connected && recv.get_no_connections() > 0 {
+                receiver.post_connection_state(&element, "connected", None);
+                connected = true;
+            }
+
+            match res {
+                Ok(()) => {
+                    if !buffers.is_empty() {
+                        receiver.queue_buffers(&element, generation, buffers);
                     }
+                }
+                Err(gst::FlowError::Flushing) => {
+                    // Flushing, nothing to be done here except for emptying our queue
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    queue.buffer_queue.clear();
+                    (receiver.0.queue.0).1.notify_one();
+                }
+                Err(gst::FlowError::Eos) => {
+                    gst_debug!(CAT, obj: &element, "Signalling EOS");
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    queue.timeout = true;
+                    (receiver.0.queue.0).1.notify_one();
+                    break;
+                }
+                Err(err) => {
+                    gst_error!(CAT, obj: &element, "Signalling error");
+                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
+                    if queue.error.is_none() {
+                        queue.error = Some(err);
+                    }
+                    (receiver.0.queue.0).1.notify_one();
+                    break;
+                }
+            }
+        }
+    }
+
+    // Offset of the n-th tick from the first one at the given frame rate
+    fn tick_offset(frame_rate: (i32, i32), n: u64) -> gst::ClockTime {
+        gst::ClockTime::SECOND
+            .mul_div_floor(n * frame_rate.1 as u64, frame_rate.0 as u64)
+            .unwrap_or(gst::ClockTime::ZERO)
+    }
+
+    // Number of audio samples from the first tick until the n-th tick at the given frame rate
+    fn tick_samples(frame_rate: (i32, i32), sample_rate: i32, n: u64) -> u64 {
+        (n * sample_rate as u64)
+            .mul_div_floor(frame_rate.1 as u64, frame_rate.0 as u64)
+            .unwrap_or(0)
+    }
+
+    // Handles a timeout according to the configured timeout action. Returns `Ok` if reconnecting
+    // to the source, or the flow return to signal otherwise.
+    fn handle_timeout(
+        &self,
+        element: &gst_base::BaseSrc,
+        recv: &RecvInstance,
+        reconnect_attempts: &mut u32,
+        connected: &mut bool,
+    ) -> Result<(), gst::FlowError> {
+        // Timing out on the initial connection is not a disconnection
+        if std::mem::replace(connected, false) {
+            self.post_connection_state(element, "disconnected", None);
+        }
+
+        match self.0.timeout_action {
+            TimeoutAction::Eos => {
+                gst_debug!(CAT, obj: element, "Timed out -- assuming EOS",);
+                Err(gst::FlowError::Eos)
+            }
+            TimeoutAction::Error => {
+                gst::element_error!(
+                    element,
+                    gst::ResourceError::Read,
+                    ["Timed out waiting for frames from NDI source"]
+                );
+                Err(gst::FlowError::Error)
+            }
+            TimeoutAction::Reconnect => {
+                let max_attempts = self.0.max_reconnect_attempts;
+                if max_attempts > 0 && *reconnect_attempts >= max_attempts {
+                    gst::element_error!(
+                        element,
+                        gst::ResourceError::NotFound,
+                        [
+                            "Failed to reconnect to NDI source after {} attempts",
+                            reconnect_attempts
+                        ]
+                    );
+                    return Err(gst::FlowError::Error);
+                }
+
+                *reconnect_attempts += 1;
+                gst_info!(
+                    CAT,
+                    obj: element,
+                    "Timed out -- reconnecting (attempt {})",
+                    reconnect_attempts
+                );
+
+                let mut queue = (self.0.queue.0).0.lock().unwrap();
+                if queue.finder.is_some() && !queue.on_backup {
+                    // The source might be available at a different URL/address by now
+                    queue.resolve = true;
+                } else {
+                    let source = queue.active_source();
+                    recv.connect(Some(&Source::new(source.0.as_deref(), source.1.as_deref())));
+                }
+                drop(queue);
+                *self.0.observations.0.lock().unwrap() = ObservationsInner::default();
+                self.post_connection_state(element, "reconnecting", Some(*reconnect_attempts));
+
+                Ok(())
+            }
+        }
+    }
+
+    // Connects to the primary source once the finder found it. Returns whether it is still being
+    // looked up, waiting at most `timeout` milliseconds for new sources.
+    fn resolve_source(&self, element: &gst_base::BaseSrc, timeout: u32) -> bool {
+        let (finder, generation) = {
+            let queue = (self.0.queue.0).0.lock().unwrap();
+            match queue.finder {
+                Some(ref finder) if queue.resolve && !queue.on_backup => {
+                    (finder.clone(), queue.generation)
+                }
+                _ => return false,
+            }
+        };
+
+        let (ndi_name, url_address) = match finder.find_source(timeout) {
+            None => return true,
+            Some(source) => source,
+        };
+
+        let mut queue = (self.0.queue.0).0.lock().unwrap();
+        // Switched to another source or failed over in the meantime
+        if queue.generation != generation || queue.on_backup {
+            return queue.resolve;
+        }
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Found source {} at {}",
+            ndi_name,
+            url_address
+        );
+
+        self.0
+            .recv
+            .connect(Some(&Source::new(Some(&ndi_name), Some(&url_address))));
+        queue.resolve = false;
+        queue.resolved_source = (Some(ndi_name), Some(url_address));
+        drop(queue);
+
+        // Only ndisrc uses the receiver
+        element.notify("resolved-ndi-name");
+        element.notify("resolved-url-address");
+
+        false
+    }
+
+    // Switches to the backup source if the primary source did not deliver any frames for the
+    // failover timeout, or did not deliver any at all within the connect timeout. Switches back
+    // once a probe receiver got frames from the primary source for the failover timeout again.
+    fn check_failover(&self, element: &gst_base::BaseSrc, failover: &mut FailoverState) {
+        let mut queue = (self.0.queue.0).0.lock().unwrap();
+        let backup_source = match queue.backup_source {
+            Some(ref backup_source) if self.0.failover_timeout > 0 => backup_source.clone(),
+            _ => return,
+        };
+
+        // Switched to another source in the meantime
+        if failover.generation != queue.generation {
+            *failover = FailoverState::new(queue.generation);
+        }
+
+        let (total, _) = self.0.recv.get_performance();
+        let frames = total.video_frames() + total.audio_frames();
+        if matches!(failover.frames, Some(prev_frames) if prev_frames != frames) {
+            failover.last_frame = Some(time::Instant::now());
+        }
+        failover.frames = Some(frames);
+
+        let source = if !queue.on_backup {
+            let (since, timeout) = match failover.last_frame {
+                Some(last_frame) => (last_frame, self.0.failover_timeout),
+                None if self.0.connect_timeout > 0 => (failover.connecting, self.0.connect_timeout),
+                None => return,
+            };
+            if since.elapsed().as_millis() < timeout as u128 {
+                return;
+            }
+
+            gst_warning!(
+                CAT,
+                obj: element,
+                "No frames received for {}ms, failing over to backup source",
+                since.elapsed().as_millis()
+            );
+
+            queue.on_backup = true;
+            backup_source
+        } else {
+            let source = match self.probe_primary_source(&queue, failover) {
+                Some(source) => source,
+                None => return,
+            };
+
+            gst_info!(
+                CAT,
+                obj: element,
+                "Primary source delivered frames for {}ms, switching back",
+                self.0.failover_timeout
+            );
+
+            failover.probe = None;
+            queue.on_backup = false;
+            if queue.finder.is_some() {
+                queue.resolve = false;
+                queue.resolved_source = source.clone();
+            }
+            source
+        };
+
+        self.0
+            .recv
+            .connect(Some(&Source::new(source.0.as_deref(), source.1.as_deref())));
+        queue.generation += 1;
+        *self.0.observations.0.lock().unwrap() = ObservationsInner::default();
+        *failover = FailoverState::new(queue.generation);
+        let on_backup = queue.on_backup;
+        let resolved = !on_backup && queue.finder.is_some();
+        drop(queue);
+
+        if resolved {
+            element.notify("resolved-ndi-name");
+            element.notify("resolved-url-address");
+        }
+
+        let _ = element.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("ndi-failover")
+                    .field("active", if on_backup { "backup" } else { "primary" })
+                    .field("ndi-name", &source.0)
+                    .field("url-address", &source.1)
+                    .build(),
+            )
+            .src(element)
+            .build(),
+        );
+    }
+
+    // Monitors the primary source with a lowest bandwidth receiver while the backup source is
+    // active. Returns the primary source once it delivered frames without interruption for the
+    // failover timeout.
+    fn probe_primary_source(
+        &self,
+        queue: &ReceiverQueueInner,
+        failover: &mut FailoverState,
+    ) -> Option<(Option<String>, Option<String>)> {
+        let hold_off = time::Duration::from_millis(self.0.failover_timeout as u64);
+
+        // A discovered source is looked up again while the probe gets no frames as it might be
+        // available at a different URL/address by now
+        let lookup = match failover.probe {
+            None => true,
+            Some(ref probe) => {
+                queue.finder.is_some()
+                    && probe.delivering.is_none()
+                    && probe.created.elapsed() >= hold_off
+            }
+        };
+        if lookup {
+            let source = match queue.finder {
+                Some(ref finder) => finder
+                    .find_source(0)
+                    .map(|(ndi_name, url_address)| (Some(ndi_name), Some(url_address))),
+                None => Some(queue.source.clone()),
+            };
+            failover.probe = source.and_then(|source| {
+                let recv = RecvInstance::builder(
+                    source.0.as_deref(),
+                    source.1.as_deref(),
+                    &self.0.receiver_ndi_name,
+                )
+                .bandwidth(NDIlib_recv_bandwidth_lowest)
+                .build()?;
+
+                Some(Probe {
+                    recv,
+                    source,
+                    created: time::Instant::now(),
+                    frames: 0,
+                    delivering: None,
+                })
+            });
+        }
 
-                    continue;
-                }
+        let probe = failover.probe.as_mut()?;
+
+        // The frames are only counted
+        while let Ok(Some(_)) = probe.recv.capture(0) {}
+
+        let (total, _) = probe.recv.get_performance();
+        let frames = total.video_frames() + total.audio_frames();
+        let now = time::Instant::now();
+        if frames != probe.frames {
+            probe.frames = frames;
+            probe.delivering = match probe.delivering {
+                Some((since, last_frame)) if now - last_frame < hold_off => Some((since, now)),
+                _ => Some((now, now)),
             };
+        }
 
-            match res {
-                Ok(item) => {
-                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
-                    while queue.buffer_queue.len() > receiver.0.max_queue_length {
-                        gst_warning!(
-                            CAT,
-                            obj: &element,
-                            "Dropping old buffer -- queue has {} items",
-                            queue.buffer_queue.len()
-                        );
-                        queue.buffer_queue.pop_front();
-                    }
-                    queue.buffer_queue.push_back(item);
-                    (receiver.0.queue.0).1.notify_one();
-                    timer = time::Instant::now();
-                }
-                Err(gst::FlowError::Eos) => {
-                    gst_debug!(CAT, obj: &element, "Signalling EOS");
-                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
-                    queue.timeout = true;
-                    (receiver.0.queue.0).1.notify_one();
-                    break;
-                }
-                Err(gst::FlowError::Flushing) => {
-                    // Flushing, nothing to be done here except for emptying our queue
-                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
-                    queue.buffer_queue.clear();
-                    (receiver.0.queue.0).1.notify_one();
-                    timer = time::Instant::now();
-                }
-                Err(err) => {
-                    gst_error!(CAT, obj: &element, "Signalling error");
-                    let mut queue = (receiver.0.queue.0).0.lock().unwrap();
-                    if queue.error.is_none() {
-                        queue.error = Some(err);
-                    }
-                    (receiver.0.queue.0).1.notify_one();
-                    break;
-                }
+        match probe.delivering {
+            Some((_, last_frame)) if now - last_frame >= hold_off => {
+                probe.delivering = None;
+                None
+            }
+            Some((since, _)) if now - since >= hold_off => Some(probe.source.clone()),
+            _ => None,
+        }
+    }
+
+    // Posts an `ndi-connection` element message with the new connection state and the source
+    fn post_connection_state(
+        &self,
+        element: &gst_base::BaseSrc,
+        state: &str,
+        reconnect_attempt: Option<u32>,
+    ) {
+        let mut s = {
+            let queue = (self.0.queue.0).0.lock().unwrap();
+            let source = queue.active_source();
+            gst::Structure::builder("ndi-connection")
+                .field("state", state)
+                .field("ndi-name", &source.0)
+                .field("url-address", &source.1)
+                .build()
+        };
+        if let Some(reconnect_attempt) = reconnect_attempt {
+            s.set("attempt", reconnect_attempt);
+        }
+
+        gst_debug!(CAT, obj: element, "Connection state {}", state);
+        let _ = element.post_message(gst::message::Element::builder(s).src(element).build());
+    }
+
+    // Posts an `ndi-status-change` element message with the current status of the connection
+    fn post_status_change(&self, element: &gst_base::BaseSrc) {
+        let mut s = gst::Structure::builder("ndi-status-change")
+            .field("connections", self.0.recv.get_no_connections())
+            .field("web-control", self.0.recv.get_web_control())
+            .build();
+        if let Some((on_program, on_preview)) = *self.0.tally_echo.lock().unwrap() {
+            s.set("on-program", on_program);
+            s.set("on-preview", on_preview);
+        }
+
+        gst_debug!(CAT, obj: element, "Status changed: {}", s);
+        let _ = element.post_message(gst::message::Element::builder(s).src(element).build());
+    }
+
+    // Posts the status change, and `disconnected` if the sender went away while connected
+    fn handle_status_change(&self, element: &gst_base::BaseSrc, connected: &mut bool) {
+        self.post_status_change(element);
+
+        if *connected && self.0.recv.get_no_connections() == 0 {
+            *connected = false;
+            self.post_connection_state(element, "disconnected", None);
+        }
+    }
+
+    // Senders echo the combined tally of all their receivers as `<ndi_tally_echo/>` metadata
+    fn handle_tally_echo(&self, element: &gst_base::BaseSrc, metadata: &str) {
+        let attributes = match metadata.trim_start().strip_prefix("<ndi_tally_echo") {
+            Some(attributes)
+                if attributes.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') =>
+            {
+                attributes
+            }
+            _ => return,
+        };
+
+        let (mut on_program, mut on_preview) = (false, false);
+        for (name, value) in xml_attributes(attributes) {
+            match name {
+                "on_program" => on_program = value == "true",
+                "on_preview" => on_preview = value == "true",
+                _ => (),
+            }
+        }
+        let tally = Some((on_program, on_preview));
+
+        let mut tally_echo = self.0.tally_echo.lock().unwrap();
+        if *tally_echo != tally {
+            *tally_echo = tally;
+            drop(tally_echo);
+            self.post_status_change(element);
+        }
+    }
+
+    // Returns a copy of the buffer with the current running time as PTS once the previous one
+    // has ended
+    fn repeat_video_buffer(
+        element: &gst_base::BaseSrc,
+        buffer: &gst::Buffer,
+    ) -> Option<gst::Buffer> {
+        let running_time = element.current_running_time()?;
+        let end = buffer.pts()? + buffer.duration()?;
+        if end > running_time {
+            return None;
+        }
+
+        let mut buffer = buffer.copy();
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(running_time);
+            buffer.unset_flags(gst::BufferFlags::DISCONT);
+        }
+
+        Some(buffer)
+    }
+
+    // Queues the buffers unless they were captured before the source changed
+    fn queue_buffers(
+        &self,
+        element: &gst_base::BaseSrc,
+        generation: u64,
+        buffers: impl IntoIterator<Item = Buffer>,
+    ) {
+        let mut queue = (self.0.queue.0).0.lock().unwrap();
+        if queue.generation != generation {
+            gst_debug!(CAT, obj: element, "Dropping buffers of the previous source");
+            return;
+        }
+
+        for buffer in buffers {
+            while queue.buffer_queue.len() > self.0.max_queue_length {
+                gst_warning!(
+                    CAT,
+                    obj: element,
+                    "Dropping old buffer -- queue has {} items",
+                    queue.buffer_queue.len()
+                );
+                queue.buffer_queue.pop_front();
+                queue.dropped_buffers += 1;
             }
+            queue.buffer_queue.push_back(buffer);
         }
+        (self.0.queue.0).1.notify_one();
     }
 
     fn calculate_timestamp(
@@ -797,6 +2108,7 @@ impl Receiver {
         timestamp: i64,
         timecode: i64,
         duration: Option<gst::ClockTime>,
+        observe: bool,
     ) -> Option<(gst::ClockTime, Option<gst::ClockTime>, bool)> {
         let receive_time = element.current_running_time()?;
 
@@ -820,11 +2132,21 @@ impl Receiver {
         );
 
         let (pts, duration, discont) = match self.0.timestamp_mode {
+            TimestampMode::ReceiveTimeTimecode if !observe => (
+                self.0.observations.map((Some(timecode), receive_time)),
+                duration,
+                false,
+            ),
             TimestampMode::ReceiveTimeTimecode => {
                 self.0
                     .observations
                     .process(element, (Some(timecode), receive_time), duration)
             }
+            TimestampMode::ReceiveTimeTimestamp if !observe => (
+                self.0.observations.map((timestamp, receive_time)),
+                duration,
+                false,
+            ),
             TimestampMode::ReceiveTimeTimestamp => {
                 self.0
                     .observations
@@ -861,6 +2183,89 @@ impl Receiver {
         Some((pts, duration, discont))
     }
 
+    fn create_metadata_buffer(
+        &self,
+        element: &gst_base::BaseSrc,
+        metadata_frame: MetadataFrame,
+    ) -> Option<Result<Buffer, gst::FlowError>> {
+        let metadata = metadata_frame.metadata()?;
+
+        gst_debug!(
+            CAT,
+            obj: element,
+            "Received metadata at timecode {}: {}",
+            gst::ClockTime::from_nseconds(metadata_frame.timecode() as u64 * 100),
+            metadata,
+        );
+
+        let pts = match self.calculate_metadata_timestamp(element, &metadata_frame) {
+            Some(pts) => pts,
+            None => {
+                gst_debug!(CAT, obj: element, "Flushing, dropping buffer");
+                return Some(Err(gst::FlowError::Flushing));
+            }
+        };
+
+        let buffer = Self::create_metadata_buffer_with_data(element, metadata, pts, None)?;
+
+        #[cfg(feature = "reference-timestamps")]
+        let buffer = {
+            let mut buffer = buffer;
+            gst::ReferenceTimestampMeta::add(
+                buffer.get_mut().unwrap(),
+                &*TIMECODE_CAPS,
+                gst::ClockTime::from_nseconds(metadata_frame.timecode() as u64 * 100),
+                gst::ClockTime::NONE,
+            );
+            buffer
+        };
+
+        gst_log!(CAT, obj: element, "Produced metadata buffer {:?}", buffer);
+
+        Some(Ok(Buffer::Metadata(buffer)))
+    }
+
+    fn calculate_metadata_timestamp(
+        &self,
+        element: &gst_base::BaseSrc,
+        metadata_frame: &MetadataFrame,
+    ) -> Option<gst::ClockTime> {
+        // Metadata frames only carry a timecode, and they are not necessarily sent regularly or
+        // with meaningful timecodes so they're only mapped with the skew estimation of the audio
+        // and video frames but not used for it
+        self.calculate_timestamp(
+            element,
+            ndisys::NDIlib_recv_timestamp_undefined,
+            metadata_frame.timecode(),
+            None,
+            false,
+        )
+        .map(|(pts, _, _)| pts)
+    }
+
+    fn create_metadata_buffer_with_data(
+        element: &gst_base::BaseSrc,
+        metadata: &str,
+        pts: impl Into<Option<gst::ClockTime>>,
+        duration: Option<gst::ClockTime>,
+    ) -> Option<gst::Buffer> {
+        // Strip the NUL terminator that might be included in the NDI metadata
+        let metadata = metadata.trim_end_matches('\0');
+        if metadata.is_empty() {
+            gst_trace!(CAT, obj: element, "Ignoring empty metadata");
+            return None;
+        }
+
+        let mut buffer = gst::Buffer::from_mut_slice(Vec::from(metadata.as_bytes()));
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(pts);
+            buffer.set_duration(duration);
+        }
+
+        Some(buffer)
+    }
+
     fn create_video_buffer_and_info(
         &self,
         element: &gst_base::BaseSrc,
@@ -890,6 +2295,23 @@ impl Receiver {
         Ok(Buffer::Video(buffer, info))
     }
 
+    fn create_framesync_video_buffer_and_info(
+        &self,
+        element: &gst_base::BaseSrc,
+        video_frame: VideoFrame,
+        pts: gst::ClockTime,
+        duration: gst::ClockTime,
+    ) -> Result<Buffer, gst::FlowError> {
+        gst_trace!(CAT, obj: element, "Captured video frame {:?}", video_frame);
+
+        let info = self.create_video_info(element, &video_frame)?;
+        let buffer = self.create_video_buffer(element, pts, Some(duration), &info, &video_frame)?;
+
+        gst_log!(CAT, obj: element, "Produced video buffer {:?}", buffer);
+
+        Ok(Buffer::Video(buffer, info))
+    }
+
     fn calculate_video_timestamp(
         &self,
         element: &gst_base::BaseSrc,
@@ -905,6 +2327,7 @@ impl Receiver {
             video_frame.timestamp(),
             video_frame.timecode(),
             duration,
+            true,
         )
     }
 
@@ -941,21 +2364,30 @@ impl Receiver {
         if [
             ndisys::NDIlib_FourCC_video_type_UYVY,
             ndisys::NDIlib_FourCC_video_type_UYVA,
+            ndisys::NDIlib_FourCC_video_type_P216,
+            ndisys::NDIlib_FourCC_video_type_PA16,
             ndisys::NDIlib_FourCC_video_type_YV12,
             ndisys::NDIlib_FourCC_video_type_NV12,
             ndisys::NDIlib_FourCC_video_type_I420,
             ndisys::NDIlib_FourCC_video_type_BGRA,
             ndisys::NDIlib_FourCC_video_type_BGRX,
             ndisys::NDIlib_FourCC_video_type_RGBA,
-            ndisys::NDIlib_FourCC_video_type_BGRX,
+            ndisys::NDIlib_FourCC_video_type_RGBX,
         ]
         .contains(&fourcc)
         {
             // YV12 and I420 are swapped in the NDI SDK compared to GStreamer
             let format = match video_frame.fourcc() {
                 ndisys::NDIlib_FourCC_video_type_UYVY => gst_video::VideoFormat::Uyvy,
-                // FIXME: This drops the alpha plane!
+                // The alpha plane is only kept if requested as this needs a conversion
+                ndisys::NDIlib_FourCC_video_type_UYVA if self.0.preserve_alpha => {
+                    gst_video::VideoFormat::Ayuv
+                }
                 ndisys::NDIlib_FourCC_video_type_UYVA => gst_video::VideoFormat::Uyvy,
+                // GStreamer has no 16 bit 4:2:2 semi-planar formats, so these are converted
+                ndisys::NDIlib_FourCC_video_type_P216 | ndisys::NDIlib_FourCC_video_type_PA16 => {
+                    gst_video::VideoFormat::Ayuv64
+                }
                 ndisys::NDIlib_FourCC_video_type_YV12 => gst_video::VideoFormat::I420,
                 ndisys::NDIlib_FourCC_video_type_NV12 => gst_video::VideoFormat::Nv12,
                 ndisys::NDIlib_FourCC_video_type_I420 => gst_video::VideoFormat::Yv12,
@@ -971,8 +2403,7 @@ impl Receiver {
                     );
 
                     return Err(gst::FlowError::NotNegotiated);
-                } // TODO: NDIlib_FourCC_video_type_P216 and NDIlib_FourCC_video_type_PA16 not
-                  // supported by GStreamer
+                }
             };
 
             #[cfg(feature = "interlaced-fields")]
@@ -1358,6 +2789,77 @@ impl Receiver {
                             }
                         }
                     }
+                    gst_video::VideoFormat::Ayuv => {
+                        // UYVA has the UYVY data followed by an alpha plane with one byte per
+                        // pixel. The chroma is duplicated for both pixels of each pair
+                        let width = vframe.width() as usize;
+                        let src_stride = video_frame.line_stride_or_data_size_in_bytes() as usize;
+                        let lines = src.len() / (src_stride + width);
+                        let (src_uyvy, src_a) = src.split_at(lines * src_stride);
+
+                        let dest_stride = vframe.plane_stride()[0] as usize;
+                        let dest = vframe.plane_data_mut(0).unwrap();
+
+                        for (dest, (uyvy, a)) in dest.chunks_exact_mut(dest_stride).zip(
+                            src_uyvy
+                                .chunks_exact(src_stride)
+                                .zip(src_a.chunks_exact(width)),
+                        ) {
+                            for (x, dest) in dest[..4 * width].chunks_exact_mut(4).enumerate() {
+                                let pair = &uyvy[4 * (x / 2)..][..4];
+                                dest[0] = a[x];
+                                dest[1] = pair[1 + 2 * (x % 2)];
+                                dest[2] = pair[0];
+                                dest[3] = pair[2];
+                            }
+                        }
+                    }
+                    gst_video::VideoFormat::Ayuv64 => {
+                        // P216 has a 16 bit luma plane followed by a plane of the same size with
+                        // interleaved 16 bit chroma for each pair of pixels, and PA16 additionally
+                        // a 16 bit alpha plane. The chroma is duplicated for both pixels and the
+                        // alpha is opaque for P216
+                        let has_alpha =
+                            video_frame.fourcc() == ndisys::NDIlib_FourCC_video_type_PA16;
+                        let plane_size = if has_alpha {
+                            src.len() / 3
+                        } else {
+                            src.len() / 2
+                        };
+                        let (src_y, src_uv) = src.split_at(plane_size);
+                        let src_a = if has_alpha {
+                            Some(&src_uv[plane_size..])
+                        } else {
+                            None
+                        };
+
+                        let width = vframe.width() as usize;
+                        let dest_stride = vframe.plane_stride()[0] as usize;
+                        let dest = vframe.plane_data_mut(0).unwrap();
+                        let src_stride = video_frame.line_stride_or_data_size_in_bytes() as usize;
+
+                        let le16 = |src: &[u8]| u16::from_le_bytes([src[0], src[1]]).to_ne_bytes();
+                        for (line, (dest, (y, uv))) in dest
+                            .chunks_exact_mut(dest_stride)
+                            .zip(
+                                src_y
+                                    .chunks_exact(src_stride)
+                                    .zip(src_uv.chunks_exact(src_stride)),
+                            )
+                            .enumerate()
+                        {
+                            let a = src_a.map(|a| &a[line * src_stride..][..src_stride]);
+                            for (x, dest) in dest[..8 * width].chunks_exact_mut(8).enumerate() {
+                                let a = a
+                                    .map(|a| le16(&a[2 * x..]))
+                                    .unwrap_or_else(|| u16::MAX.to_ne_bytes());
+                                dest[0..2].copy_from_slice(&a);
+                                dest[2..4].copy_from_slice(&le16(&y[2 * x..]));
+                                dest[4..6].copy_from_slice(&le16(&uv[4 * (x / 2)..]));
+                                dest[6..8].copy_from_slice(&le16(&uv[4 * (x / 2) + 2..]));
+                            }
+                        }
+                    }
                     _ => unreachable!(),
                 }
 
@@ -1440,6 +2942,27 @@ impl Receiver {
         Ok(Buffer::Audio(buffer, info))
     }
 
+    fn create_framesync_audio_buffer_and_info(
+        &self,
+        element: &gst_base::BaseSrc,
+        audio_frame: AudioFrame,
+        pts: gst::ClockTime,
+    ) -> Result<Buffer, gst::FlowError> {
+        gst_trace!(CAT, obj: element, "Captured audio frame {:?}", audio_frame);
+
+        let duration = gst::ClockTime::SECOND.mul_div_floor(
+            audio_frame.no_samples() as u64,
+            audio_frame.sample_rate() as u64,
+        );
+
+        let info = self.create_audio_info(element, &audio_frame)?;
+        let buffer = self.create_audio_buffer(element, pts, duration, &info, &audio_frame)?;
+
+        gst_log!(CAT, obj: element, "Produced audio buffer {:?}", buffer);
+
+        Ok(Buffer::Audio(buffer, info))
+    }
+
     fn calculate_audio_timestamp(
         &self,
         element: &gst_base::BaseSrc,
@@ -1455,6 +2978,7 @@ impl Receiver {
             audio_frame.timestamp(),
             audio_frame.timecode(),
             duration,
+            true,
         )
     }
 
@@ -1472,6 +2996,9 @@ impl Receiver {
                 audio_frame.no_channels() as u32,
             );
 
+            #[cfg(feature = "non-interleaved-audio")]
+            let builder = builder.layout(gst_audio::AudioLayout::NonInterleaved);
+
             let info = builder.build().map_err(|_| {
                 gst::element_error!(
                     element,
@@ -1519,7 +3046,56 @@ impl Receiver {
         }
 
         #[cfg(feature = "advanced-sdk")]
-        if [NDIlib_FourCC_audio_type_Opus].contains(&fourcc) {}
+        if [NDIlib_FourCC_audio_type_Opus].contains(&fourcc) {
+            let compressed_packet = audio_frame.compressed_packet().ok_or_else(|| {
+                gst_error!(
+                    CAT,
+                    obj: element,
+                    "Audio packet doesn't have compressed packet start"
+                );
+                gst::element_error!(element, gst::StreamError::Format, ["Invalid audio packet"]);
+
+                gst::FlowError::Error
+            })?;
+
+            if compressed_packet.fourcc != NDIlib_compressed_FourCC_type_Opus {
+                gst_error!(CAT, obj: element, "Non-Opus audio packet");
+                gst::element_error!(element, gst::StreamError::Format, ["Invalid audio packet"]);
+
+                return Err(gst::FlowError::Error);
+            }
+
+            // The channel mapping is taken from the OpusHead in the extra data if there is one,
+            // otherwise only mono and stereo can be described by mapping family 0
+            let (channel_mapping_family, stream_count, coupled_count, channel_mapping) =
+                match compressed_packet.extra_data.and_then(parse_opus_head) {
+                    Some(head) => head,
+                    None if audio_frame.no_channels() <= 2 => {
+                        (0, 1, audio_frame.no_channels() - 1, Vec::new())
+                    }
+                    None => {
+                        gst::element_error!(
+                            element,
+                            gst::StreamError::Format,
+                            [
+                                "No Opus channel mapping for {} channels",
+                                audio_frame.no_channels()
+                            ]
+                        );
+
+                        return Err(gst::FlowError::NotNegotiated);
+                    }
+                };
+
+            return Ok(AudioInfo::OpusInfo {
+                sample_rate: audio_frame.sample_rate(),
+                no_channels: audio_frame.no_channels(),
+                channel_mapping_family,
+                stream_count,
+                coupled_count,
+                channel_mapping,
+            });
+        }
 
         gst::element_error!(
             element,
@@ -1567,29 +3143,58 @@ impl Receiver {
                         }
                     }
 
-                    let mut dest = buffer.map_writable().unwrap();
-                    let dest = dest
-                        .as_mut_slice_of::<f32>()
+                    #[cfg(feature = "non-interleaved-audio")]
+                    {
+                        // The NDI planes are copied as is, only without the padding at their end
+                        let plane_size =
+                            audio_frame.no_samples() as usize * std::mem::size_of::<f32>();
+                        let mut dest = buffer.map_writable().unwrap();
+                        assert!(dest.len() == plane_size * audio_frame.no_channels() as usize);
+
+                        for (dest, src) in dest.chunks_exact_mut(plane_size).zip(src.chunks_exact(
+                            audio_frame.channel_stride_or_data_size_in_bytes() as usize,
+                        )) {
+                            dest.copy_from_slice(&src[..plane_size]);
+                        }
+                        drop(dest);
+
+                        gst_audio::AudioMeta::add(
+                            buffer,
+                            info,
+                            audio_frame.no_samples() as usize,
+                            &[],
+                        )
                         .map_err(|_| gst::FlowError::NotNegotiated)?;
-                    assert!(
-                        dest.len()
-                            == audio_frame.no_samples() as usize
-                                * audio_frame.no_channels() as usize
-                    );
+                    }
 
-                    for (channel, samples) in src
-                        .chunks_exact(audio_frame.channel_stride_or_data_size_in_bytes() as usize)
-                        .enumerate()
+                    #[cfg(not(feature = "non-interleaved-audio"))]
                     {
-                        let samples = samples
-                            .as_slice_of::<f32>()
+                        let mut dest = buffer.map_writable().unwrap();
+                        let dest = dest
+                            .as_mut_slice_of::<f32>()
                             .map_err(|_| gst::FlowError::NotNegotiated)?;
+                        assert!(
+                            dest.len()
+                                == audio_frame.no_samples() as usize
+                                    * audio_frame.no_channels() as usize
+                        );
 
-                        for (i, sample) in samples[..audio_frame.no_samples() as usize]
-                            .iter()
+                        for (channel, samples) in src
+                            .chunks_exact(
+                                audio_frame.channel_stride_or_data_size_in_bytes() as usize
+                            )
                             .enumerate()
                         {
-                            dest[i * (audio_frame.no_channels() as usize) + channel] = *sample;
+                            let samples = samples
+                                .as_slice_of::<f32>()
+                                .map_err(|_| gst::FlowError::NotNegotiated)?;
+
+                            for (i, sample) in samples[..audio_frame.no_samples() as usize]
+                                .iter()
+                                .enumerate()
+                            {
+                                dest[i * (audio_frame.no_channels() as usize) + channel] = *sample;
+                            }
                         }
                     }
                 }
@@ -1597,22 +3202,7 @@ impl Receiver {
                 Ok(buffer)
             }
             #[cfg(feature = "advanced-sdk")]
-            AudioInfo::OpusInfo { .. } => {
-                let data = audio_frame.data().ok_or_else(|| {
-                    gst_error!(CAT, obj: element, "Audio packet has no data");
-                    gst::element_error!(
-                        element,
-                        gst::StreamError::Format,
-                        ["Invalid audio packet"]
-                    );
-
-                    gst::FlowError::Error
-                })?;
-
-                Ok(gst::Buffer::from_mut_slice(Vec::from(data)))
-            }
-            #[cfg(feature = "advanced-sdk")]
-            AudioInfo::AacInfo { .. } => {
+            AudioInfo::OpusInfo { .. } | AudioInfo::AacInfo { .. } => {
                 let compressed_packet = audio_frame.compressed_packet().ok_or_else(|| {
                     gst_error!(
                         CAT,
@@ -1628,10 +3218,64 @@ impl Receiver {
                     gst::FlowError::Error
                 })?;
 
-                Ok(gst::Buffer::from_mut_slice(Vec::from(
-                    compressed_packet.data,
-                )))
+                let mut buffer = gst::Buffer::from_mut_slice(Vec::from(compressed_packet.data));
+                {
+                    let buffer = buffer.get_mut().unwrap();
+                    buffer.set_pts(pts);
+                    buffer.set_duration(duration);
+                }
+
+                Ok(buffer)
             }
         }
     }
 }
+
+// Iterates over the names and values of the attributes at the start of `s`, up to the end of the
+// XML start tag they belong to. Values can be in single or double quotes.
+fn xml_attributes(s: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = s;
+
+    std::iter::from_fn(move || {
+        let s = rest.trim_start();
+        let (name, s) = s.split_at(s.find('=')?);
+        let name = name.trim_end();
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            return None;
+        }
+
+        let s = s[1..].trim_start();
+        let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let s = &s[1..];
+        let end = s.find(quote)?;
+        rest = &s[end + 1..];
+
+        Some((name, &s[..end]))
+    })
+}
+
+/// Parses the channel mapping family, stream count, coupled stream count and channel mapping
+/// from an `OpusHead` header as defined in RFC 7845.
+#[cfg(feature = "advanced-sdk")]
+fn parse_opus_head(data: &[u8]) -> Option<(i32, i32, i32, Vec<u8>)> {
+    if data.len() < 19 || &data[..8] != b"OpusHead" {
+        return None;
+    }
+
+    let channels = data[9] as usize;
+    let channel_mapping_family = data[18];
+    if channel_mapping_family == 0 {
+        return Some((0, 1, channels as i32 - 1, Vec::new()));
+    }
+
+    let stream_count = *data.get(19)?;
+    let coupled_count = *data.get(20)?;
+    let channel_mapping = data.get(21..21 + channels)?;
+
+    Some((
+        channel_mapping_family as i32,
+        stream_count as i32,
+        coupled_count as i32,
+        channel_mapping.to_vec(),
+    ))
+}
@@ -0,0 +1,233 @@
+// Sends known audio through ndisink or directly through the backend and checks what ndisrc
+// receives
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+// Level of each channel in the test audio, exactly representable in all sample formats
+fn channel_level(channel: usize) -> f64 {
+    (channel + 1) as f64 / 8.0
+}
+
+fn encode_sample(format: gst_audio::AudioFormat, value: f64) -> Vec<u8> {
+    match format {
+        gst_audio::AUDIO_FORMAT_S16 => ((value * 32768.0) as i16).to_ne_bytes().to_vec(),
+        gst_audio::AUDIO_FORMAT_S24 => {
+            let bytes = ((value * 8388608.0) as i32).to_ne_bytes();
+            if cfg!(target_endian = "little") {
+                bytes[..3].to_vec()
+            } else {
+                bytes[1..].to_vec()
+            }
+        }
+        gst_audio::AUDIO_FORMAT_F32 => (value as f32).to_ne_bytes().to_vec(),
+        _ => unreachable!(),
+    }
+}
+
+// Calls `f` with the channel and the bytes of every sample of an audio buffer
+fn for_each_sample(
+    info: &gst_audio::AudioInfo,
+    buffer: &mut gst::BufferRef,
+    mut f: impl FnMut(usize, &mut [u8]),
+) {
+    let channels = info.channels() as usize;
+    let interleaved = info.layout() == gst_audio::AudioLayout::Interleaved;
+    let bps = info.bps() as usize;
+
+    let mut audio_buffer =
+        gst_audio::AudioBufferRef::from_buffer_ref_writable(buffer, info).unwrap();
+    for plane in 0..audio_buffer.n_planes() {
+        let data = audio_buffer.plane_data_mut(plane).unwrap();
+        for (i, sample) in data.chunks_exact_mut(bps).enumerate() {
+            let channel = if interleaved {
+                i % channels
+            } else {
+                plane as usize
+            };
+            f(channel, sample);
+        }
+    }
+}
+
+// Sends two channels of constant audio in the given format and layout, and checks the F32 audio
+// received in the given layout
+fn audio_roundtrip(name: &str, format: gst_audio::AudioFormat, layout: &str, output_layout: &str) {
+    init();
+
+    let sender = Pipeline::new(&format!(
+        "audiotestsrc name=src is-live=true samplesperbuffer=480 ! \
+         audio/x-raw,format={},layout={},rate=48000,channels=2 ! ndisink ndi-name={}",
+        format.to_str(),
+        layout,
+        name
+    ));
+    fill_buffers(&sender, "src", |caps, buffer| {
+        let info = gst_audio::AudioInfo::from_caps(caps).unwrap();
+        for_each_sample(&info, buffer, |channel, sample| {
+            sample.copy_from_slice(&encode_sample(info.format(), channel_level(channel)));
+        });
+    });
+    sender.play();
+
+    let receiver = Pipeline::new(&format!(
+        "ndisrc ndi-name=\"{}\" ! ndisrcdemux name=demux demux.audio ! \
+         audio/x-raw,layout={} ! fakesink name=sink",
+        ndi_name(name),
+        output_layout
+    ));
+    let buffers = Buffers::new(&receiver, "sink");
+    receiver.play();
+
+    let (caps, mut buffer) = buffers.next();
+    let info = gst_audio::AudioInfo::from_caps(&caps).unwrap();
+    assert_eq!(info.format(), gst_audio::AUDIO_FORMAT_F32);
+    assert_eq!(info.channels(), 2);
+    assert_eq!(info.rate(), 48000);
+    assert_eq!(
+        info.layout() == gst_audio::AudioLayout::Interleaved,
+        output_layout == "interleaved"
+    );
+
+    let mut samples = 0;
+    for_each_sample(&info, buffer.make_mut(), |channel, sample| {
+        let value = f32::from_ne_bytes([sample[0], sample[1], sample[2], sample[3]]);
+        assert!((value as f64 - channel_level(channel)).abs() < 1e-4);
+        samples += 1;
+    });
+    assert!(samples > 0);
+}
+
+#[test]
+fn test_f32_audio() {
+    audio_roundtrip(
+        "audio-f32",
+        gst_audio::AUDIO_FORMAT_F32,
+        "interleaved",
+        "interleaved",
+    );
+}
+
+#[test]
+fn test_s16_audio() {
+    audio_roundtrip(
+        "audio-s16",
+        gst_audio::AUDIO_FORMAT_S16,
+        "interleaved",
+        "interleaved",
+    );
+}
+
+#[test]
+fn test_s24_audio() {
+    audio_roundtrip(
+        "audio-s24",
+        gst_audio::AUDIO_FORMAT_S24,
+        "interleaved",
+        "interleaved",
+    );
+}
+
+#[test]
+fn test_non_interleaved_s16_audio() {
+    audio_roundtrip(
+        "audio-s16-planar",
+        gst_audio::AUDIO_FORMAT_S16,
+        "non-interleaved",
+        "interleaved",
+    );
+}
+
+#[test]
+fn test_non_interleaved_f32_audio() {
+    audio_roundtrip(
+        "audio-f32-planar",
+        gst_audio::AUDIO_FORMAT_F32,
+        "non-interleaved",
+        "non-interleaved",
+    );
+}
+
+// Sends Opus packets of the given number of channels with the OpusHead, if any, as extra data
+// of the compressed packets, and returns the caps and the first buffer received
+#[cfg(feature = "advanced-sdk")]
+fn opus_roundtrip(name: &str, channels: i32, opus_head: &[u8]) -> (gst::Caps, gst::Buffer) {
+    use gstndi::ndisys::*;
+
+    init();
+
+    let sender = RawSender::new(name);
+    let receiver = Pipeline::new(&format!(
+        "ndisrc ndi-name=\"{}\" ! ndisrcdemux name=demux demux.audio ! fakesink name=sink",
+        ndi_name(name),
+    ));
+    let buffers = Buffers::new(&receiver, "sink");
+    receiver.play();
+
+    let data = [0xfc, 0xff, 0xfe];
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&NDIlib_compressed_packet_version_0.to_le_bytes());
+    packet.extend_from_slice(&NDIlib_compressed_FourCC_type_Opus.to_le_bytes());
+    packet.extend_from_slice(&0i64.to_le_bytes());
+    packet.extend_from_slice(&0i64.to_le_bytes());
+    packet.extend_from_slice(&0u64.to_le_bytes());
+    packet.extend_from_slice(&NDIlib_compressed_packet_flags_keyframe.to_le_bytes());
+    packet.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    packet.extend_from_slice(&(opus_head.len() as u32).to_le_bytes());
+    packet.extend_from_slice(&data);
+    packet.extend_from_slice(opus_head);
+
+    let (caps, buffer) = buffers.next_while(|| {
+        sender.send_audio(
+            NDIlib_FourCC_audio_type_Opus,
+            (48000, channels, 960),
+            &packet,
+        )
+    });
+    assert_eq!(&*buffer.map_readable().unwrap(), &data);
+
+    (caps, buffer)
+}
+
+#[cfg(feature = "advanced-sdk")]
+#[test]
+fn test_opus_audio() {
+    let (caps, _buffer) = opus_roundtrip("audio-opus", 2, &[]);
+
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.name(), "audio/x-opus");
+    assert_eq!(s.get::<i32>("channels").unwrap(), 2);
+    assert_eq!(s.get::<i32>("rate").unwrap(), 48000);
+    assert_eq!(s.get::<i32>("channel-mapping-family").unwrap(), 0);
+}
+
+#[cfg(feature = "advanced-sdk")]
+#[test]
+fn test_opus_audio_with_head() {
+    // Three channels in one coupled and one mono stream, with the last two channels swapped
+    let mut opus_head = b"OpusHead".to_vec();
+    opus_head.extend_from_slice(&[1, 3]);
+    opus_head.extend_from_slice(&312u16.to_le_bytes());
+    opus_head.extend_from_slice(&48000u32.to_le_bytes());
+    opus_head.extend_from_slice(&0i16.to_le_bytes());
+    opus_head.extend_from_slice(&[1, 2, 1, 0, 2, 1]);
+
+    let (caps, _buffer) = opus_roundtrip("audio-opus-head", 3, &opus_head);
+
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.name(), "audio/x-opus");
+    assert_eq!(s.get::<i32>("channels").unwrap(), 3);
+    assert_eq!(s.get::<i32>("channel-mapping-family").unwrap(), 1);
+    assert_eq!(s.get::<i32>("stream-count").unwrap(), 2);
+    assert_eq!(s.get::<i32>("coupled-count").unwrap(), 1);
+    let channel_mapping = s
+        .get::<gst::Array>("channel-mapping")
+        .unwrap()
+        .as_slice()
+        .iter()
+        .map(|channel| channel.get::<i32>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(channel_mapping, [0, 2, 1]);
+}
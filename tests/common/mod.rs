@@ -0,0 +1,304 @@
+// Helpers shared by the integration tests, which run the elements on the loopback backend so
+// that neither the NDI SDK nor a network is needed
+#![allow(dead_code)]
+
+use gst::prelude::*;
+
+use gstndi::ndisys::*;
+
+use std::ffi::CString;
+use std::sync::{mpsc, Mutex, Once};
+use std::time::{Duration, Instant};
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn init() {
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        std::env::set_var("GST_NDI_BACKEND", "loopback");
+        // Like G_DEBUG=fatal-warnings, which GLib only reads before the tests start
+        glib::log_set_always_fatal(
+            glib::LogLevels::LEVEL_WARNING | glib::LogLevels::LEVEL_CRITICAL,
+        );
+        gst::init().unwrap();
+        gstndi::plugin_register_static().expect("Failed to register the ndi plugin");
+    });
+}
+
+// Full name of a loopback sender, which like the SDK prefixes the name with the machine name
+pub fn ndi_name(name: &str) -> String {
+    format!("{} ({})", glib::host_name().to_uppercase(), name)
+}
+
+// Pipeline that is shut down when dropped
+pub struct Pipeline(pub gst::Pipeline);
+
+impl Pipeline {
+    pub fn new(description: &str) -> Self {
+        let pipeline = gst::parse_launch(description)
+            .unwrap()
+            .downcast::<gst::Pipeline>()
+            .unwrap();
+        Pipeline(pipeline)
+    }
+
+    pub fn by_name(&self, name: &str) -> gst::Element {
+        self.0.by_name(name).unwrap()
+    }
+
+    pub fn play(&self) {
+        self.0.set_state(gst::State::Playing).unwrap();
+    }
+
+    // Waits for an element message with the given name for which `matches` returns true,
+    // failing on errors
+    pub fn wait_for_message(
+        &self,
+        name: &str,
+        matches: impl Fn(&gst::StructureRef) -> bool,
+    ) -> gst::Structure {
+        let bus = self.0.bus().unwrap();
+        let deadline = Instant::now() + TIMEOUT;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let msg = bus
+                .timed_pop_filtered(
+                    gst::ClockTime::from_nseconds(remaining.as_nanos() as u64),
+                    &[gst::MessageType::Element, gst::MessageType::Error],
+                )
+                .unwrap_or_else(|| panic!("Timed out waiting for {} message", name));
+
+            match msg.view() {
+                gst::MessageView::Error(err) => {
+                    panic!("Error from {:?}: {}", err.src(), err.error())
+                }
+                _ => match msg.structure() {
+                    Some(s) if s.name() == name && matches(s) => return s.to_owned(),
+                    _ => (),
+                },
+            }
+        }
+    }
+
+    // Waits for end of stream, failing on errors
+    pub fn wait_for_eos(&self) {
+        let bus = self.0.bus().unwrap();
+        let msg = bus
+            .timed_pop_filtered(
+                gst::ClockTime::from_nseconds(TIMEOUT.as_nanos() as u64),
+                &[gst::MessageType::Eos, gst::MessageType::Error],
+            )
+            .expect("Timed out waiting for EOS");
+
+        if let gst::MessageView::Error(err) = msg.view() {
+            panic!("Error from {:?}: {}", err.src(), err.error());
+        }
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        let _ = self.0.set_state(gst::State::Null);
+    }
+}
+
+// Overwrites every buffer produced by the element with the given name before it is sent on
+pub fn fill_buffers(
+    pipeline: &Pipeline,
+    name: &str,
+    fill: impl Fn(&gst::Caps, &mut gst::BufferRef) + Send + Sync + 'static,
+) {
+    let pad = pipeline.by_name(name).static_pad("src").unwrap();
+    pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+        if let Some(gst::PadProbeData::Buffer(ref mut buffer)) = info.data {
+            let caps = pad.current_caps().unwrap();
+            fill(&caps, buffer.make_mut());
+        }
+        gst::PadProbeReturn::Ok
+    });
+}
+
+// Waits until `condition` returns true
+pub fn wait_until(what: &str, condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + TIMEOUT;
+    while !condition() {
+        assert!(Instant::now() < deadline, "Timed out waiting for {}", what);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// Starts a live video sender whose frames are identified by their width
+pub fn video_sender(name: &str, width: i32) -> Pipeline {
+    let sender = Pipeline::new(&format!(
+        "videotestsrc is-live=true ! video/x-raw,format=UYVY,width={},height=16,framerate=30/1 ! \
+         ndisink name=sink ndi-name={}",
+        width, name
+    ));
+    sender.play();
+    sender
+}
+
+// Starts a receiver whose video buffers are returned
+pub fn video_receiver(name: &str, properties: &str) -> (Pipeline, Buffers) {
+    let receiver = Pipeline::new(&format!(
+        "ndisrc name=src ndi-name=\"{}\" {} ! ndisrcdemux name=demux \
+         demux.video ! fakesink name=sink",
+        ndi_name(name),
+        properties
+    ));
+    let buffers = Buffers::new(&receiver, "sink");
+    receiver.play();
+    (receiver, buffers)
+}
+
+pub fn width(caps: &gst::Caps) -> i32 {
+    caps.structure(0).unwrap().get::<i32>("width").unwrap()
+}
+
+// Discards buffers until one with the given width arrives and checks that the following ones
+// have the same width
+pub fn wait_for_width(buffers: &Buffers, expected: i32) {
+    buffers.clear();
+
+    let deadline = Instant::now() + TIMEOUT;
+    while width(&buffers.next().0) != expected {
+        assert!(
+            Instant::now() < deadline,
+            "No buffers of width {}",
+            expected
+        );
+    }
+
+    for _ in 0..5 {
+        assert_eq!(width(&buffers.next().0), expected);
+    }
+}
+
+// Buffers arriving at the sink pad of an element together with their caps
+pub struct Buffers(Mutex<mpsc::Receiver<(gst::Caps, gst::Buffer)>>);
+
+impl Buffers {
+    pub fn new(pipeline: &Pipeline, name: &str) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(16);
+        let sender = Mutex::new(sender);
+
+        let pad = pipeline.by_name(name).static_pad("sink").unwrap();
+        pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+            if let Some(gst::PadProbeData::Buffer(ref buffer)) = info.data {
+                let caps = pad.current_caps().unwrap();
+                // New buffers are dropped while nobody is taking the queued ones
+                let _ = sender.lock().unwrap().try_send((caps, buffer.clone()));
+            }
+            gst::PadProbeReturn::Ok
+        });
+
+        Buffers(Mutex::new(receiver))
+    }
+
+    pub fn next(&self) -> (gst::Caps, gst::Buffer) {
+        self.0
+            .lock()
+            .unwrap()
+            .recv_timeout(TIMEOUT)
+            .expect("Timed out waiting for a buffer")
+    }
+
+    // Calls `f` until a buffer arrives, e.g. to send frames until the receiver is connected
+    pub fn next_while(&self, mut f: impl FnMut()) -> (gst::Caps, gst::Buffer) {
+        let receiver = self.0.lock().unwrap();
+        let deadline = Instant::now() + TIMEOUT;
+
+        loop {
+            f();
+            match receiver.recv_timeout(Duration::from_millis(20)) {
+                Ok(item) => return item,
+                Err(_) => assert!(Instant::now() < deadline, "Timed out waiting for a buffer"),
+            }
+        }
+    }
+
+    // Discards the buffers received so far
+    pub fn clear(&self) {
+        let receiver = self.0.lock().unwrap();
+        while receiver.try_recv().is_ok() {}
+    }
+}
+
+// Sender created directly on the backend, for sending frames that ndisink doesn't produce
+pub struct RawSender(NDIlib_send_instance_t);
+
+impl RawSender {
+    pub fn new(name: &str) -> Self {
+        let name = CString::new(name).unwrap();
+        let create = NDIlib_send_create_t {
+            p_ndi_name: name.as_ptr(),
+            p_groups: std::ptr::null(),
+            clock_video: false,
+            clock_audio: false,
+        };
+
+        let instance = unsafe { gstndi::backend::get().send_create(&create) };
+        assert!(!instance.is_null());
+        RawSender(instance)
+    }
+
+    // Sends a progressive video frame with the given data, which has to match the FourCC
+    pub fn send_video(
+        &self,
+        fourcc: NDIlib_FourCC_video_type_e,
+        (xres, yres, stride): (i32, i32, i32),
+        data: &[u8],
+        metadata: Option<&str>,
+    ) {
+        let metadata = metadata.map(|metadata| CString::new(metadata).unwrap());
+        let frame = NDIlib_video_frame_v2_t {
+            xres,
+            yres,
+            FourCC: fourcc,
+            frame_rate_N: 30,
+            frame_rate_D: 1,
+            picture_aspect_ratio: 0.0,
+            frame_format_type: NDIlib_frame_format_type_e::NDIlib_frame_format_type_progressive,
+            timecode: NDIlib_send_timecode_synthesize,
+            p_data: data.as_ptr() as *const _,
+            line_stride_or_data_size_in_bytes: stride,
+            p_metadata: metadata
+                .as_ref()
+                .map_or(std::ptr::null(), |metadata| metadata.as_ptr()),
+            timestamp: 0,
+        };
+
+        unsafe { gstndi::backend::get().send_send_video_v2(self.0, &frame) };
+    }
+
+    // Sends an audio frame whose data size is given instead of a channel stride, as for
+    // compressed audio
+    pub fn send_audio(
+        &self,
+        fourcc: NDIlib_FourCC_audio_type_e,
+        (sample_rate, no_channels, no_samples): (i32, i32, i32),
+        data: &[u8],
+    ) {
+        let frame = NDIlib_audio_frame_v3_t {
+            sample_rate,
+            no_channels,
+            no_samples,
+            timecode: NDIlib_send_timecode_synthesize,
+            FourCC: fourcc,
+            p_data: data.as_ptr() as *const _,
+            channel_stride_or_data_size_in_bytes: data.len() as i32,
+            p_metadata: std::ptr::null(),
+            timestamp: 0,
+        };
+
+        unsafe { gstndi::backend::get().send_send_audio_v3(self.0, &frame) };
+    }
+}
+
+impl Drop for RawSender {
+    fn drop(&mut self) {
+        unsafe { gstndi::backend::get().send_destroy(self.0) };
+    }
+}
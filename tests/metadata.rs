@@ -0,0 +1,159 @@
+// Checks the metadata exchanged between ndisink and ndisrc in both directions
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+use gstndi::ndisys::*;
+
+use std::time::Instant;
+
+// Starts a receiver whose metadata buffers are returned
+fn metadata_receiver(name: &str, properties: &str) -> (Pipeline, Buffers) {
+    let receiver = Pipeline::new(&format!(
+        "ndisrc name=src ndi-name=\"{}\" {} ! ndisrcdemux name=demux \
+         demux.video ! fakesink demux.metadata ! fakesink name=sink",
+        ndi_name(name),
+        properties
+    ));
+    let buffers = Buffers::new(&receiver, "sink");
+    receiver.play();
+    (receiver, buffers)
+}
+
+fn metadata(caps: &gst::Caps, buffer: &gst::Buffer) -> String {
+    assert_eq!(
+        caps.structure(0).unwrap().name(),
+        "application/x-ndi-metadata"
+    );
+    let map = buffer.map_readable().unwrap();
+    String::from_utf8_lossy(&map).into_owned()
+}
+
+// Discards metadata buffers until one containing `expected` arrives
+fn wait_for_metadata(buffers: &Buffers, expected: &str) {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        let (caps, buffer) = buffers.next();
+        if metadata(&caps, &buffer).contains(expected) {
+            return;
+        }
+        assert!(Instant::now() < deadline, "No metadata {}", expected);
+    }
+}
+
+#[test]
+fn test_frame_metadata() {
+    init();
+
+    let sender = RawSender::new("metadata-frame");
+    let (_receiver, buffers) = metadata_receiver("metadata-frame", "");
+
+    // Frames are sent until the receiver is connected, and other metadata is skipped
+    let frame = [0x80, 0x10].repeat(16 * 16);
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        let (caps, buffer) = buffers.next_while(|| {
+            sender.send_video(
+                NDIlib_FourCC_video_type_UYVY,
+                (16, 16, 32),
+                &frame,
+                Some("<test_frame/>"),
+            )
+        });
+        if metadata(&caps, &buffer).contains("<test_frame/>") {
+            break;
+        }
+        assert!(Instant::now() < deadline, "No frame metadata");
+    }
+}
+
+#[test]
+fn test_frame_metadata_meta() {
+    init();
+
+    let sender = Pipeline::new(
+        "videotestsrc name=src is-live=true ! video/x-raw,format=UYVY,width=32,height=16 ! \
+         ndisink ndi-name=metadata-meta",
+    );
+    fill_buffers(&sender, "src", |_caps, buffer| {
+        gstndi::ndisinkmeta::NdiSinkFrameMetadataMeta::add(buffer, "<test_meta/>");
+    });
+    sender.play();
+    let (_receiver, buffers) = metadata_receiver("metadata-meta", "");
+
+    wait_for_metadata(&buffers, "<test_meta/>");
+}
+
+#[test]
+fn test_receiver_metadata() {
+    init();
+
+    let sender = video_sender("metadata-receiver", 32);
+    let recv =
+        gstndi::ndi::RecvInstance::builder(Some(&ndi_name("metadata-receiver")), None, "test")
+            .build()
+            .unwrap();
+
+    // Timecodes are in units of 100ns
+    assert!(recv.send_metadata(&gstndi::ndi::MetadataFrame::new(
+        1_000_000,
+        Some("<test_receiver/>")
+    )));
+
+    let s = sender.wait_for_message("ndi-receiver-metadata", |s| {
+        s.get::<&str>("metadata").unwrap() == "<test_receiver/>"
+    });
+    assert_eq!(
+        s.get::<gst::ClockTime>("timecode").unwrap(),
+        gst::ClockTime::from_mseconds(100)
+    );
+}
+
+#[test]
+fn test_receiver_metadata_without_timecode() {
+    init();
+
+    let sender = video_sender("metadata-no-timecode", 32);
+    let recv =
+        gstndi::ndi::RecvInstance::builder(Some(&ndi_name("metadata-no-timecode")), None, "test")
+            .build()
+            .unwrap();
+
+    // Negative timecodes don't correspond to a time
+    assert!(recv.send_metadata(&gstndi::ndi::MetadataFrame::new(
+        -1,
+        Some("<test_receiver/>")
+    )));
+
+    let s = sender.wait_for_message("ndi-receiver-metadata", |s| {
+        s.get::<&str>("metadata").unwrap() == "<test_receiver/>"
+    });
+    assert!(!s.has_field("timecode"));
+}
+
+#[test]
+fn test_connection_metadata() {
+    init();
+
+    let sender = Pipeline::new(
+        "videotestsrc is-live=true ! video/x-raw,format=UYVY,width=32,height=16 ! \
+         ndisink ndi-name=metadata-connection connection-metadata=\"<test_sender/>\"",
+    );
+    sender.play();
+    let (_receiver, buffers) = metadata_receiver(
+        "metadata-connection",
+        "receiver-metadata=\"<test_receiver/>\"",
+    );
+
+    // Connection metadata of the sender arrives on the metadata pad of the receiver
+    wait_for_metadata(&buffers, "<test_sender/>");
+
+    // and the connection metadata of the receiver is posted by the sender
+    sender.wait_for_message("ndi-receiver-metadata", |s| {
+        s.get::<&str>("metadata")
+            .unwrap()
+            .contains("<test_receiver/>")
+    });
+}
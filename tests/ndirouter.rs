@@ -0,0 +1,34 @@
+// Checks that ndirouter forwards the selected source to its receivers
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+use gst::prelude::*;
+
+#[test]
+fn test_route() {
+    init();
+
+    let _sender_a = video_sender("route-a", 32);
+    let _sender_b = video_sender("route-b", 48);
+
+    // The router only needs to be in the READY state
+    let router = Pipeline(gst::Pipeline::new(None));
+    let element = gst::ElementFactory::make("ndirouter", None).unwrap();
+    element.set_property("ndi-name", "router").unwrap();
+    element
+        .set_property("source-ndi-name", ndi_name("route-a"))
+        .unwrap();
+    router.0.add(&element).unwrap();
+    router.0.set_state(gst::State::Ready).unwrap();
+
+    let (_receiver, buffers) = video_receiver("router", "");
+    wait_for_width(&buffers, 32);
+
+    element
+        .set_property("source-ndi-name", ndi_name("route-b"))
+        .unwrap();
+    wait_for_width(&buffers, 48);
+}
@@ -0,0 +1,59 @@
+// Checks what ndisink reports about the receivers connected to it
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+use gst::prelude::*;
+
+fn has_tally(on_program: bool, on_preview: bool) -> impl Fn(&gst::StructureRef) -> bool {
+    move |s| {
+        s.get::<bool>("on-program").unwrap() == on_program
+            && s.get::<bool>("on-preview").unwrap() == on_preview
+    }
+}
+
+#[test]
+fn test_tally() {
+    init();
+
+    let sender = video_sender("tally", 32);
+    let (receiver, buffers) = video_receiver("tally", "on-program=true on-preview=true");
+    buffers.next();
+
+    sender.wait_for_message("tally-changed", has_tally(true, true));
+    let sink = sender.by_name("sink");
+    assert!(sink.property("on-program").unwrap().get::<bool>().unwrap());
+    assert!(sink.property("on-preview").unwrap().get::<bool>().unwrap());
+
+    receiver
+        .by_name("src")
+        .set_property("on-program", false)
+        .unwrap();
+
+    sender.wait_for_message("tally-changed", has_tally(false, true));
+    assert!(!sink.property("on-program").unwrap().get::<bool>().unwrap());
+}
+
+#[test]
+fn test_connections() {
+    init();
+
+    let sender = Pipeline::new(
+        "videotestsrc is-live=true ! video/x-raw,format=UYVY,width=32,height=16 ! \
+         ndisink name=sink ndi-name=connections wait-for-connection=true",
+    );
+    sender.play();
+    let sink = sender.by_name("sink");
+    let connections = || sink.property("connections").unwrap().get::<u32>().unwrap();
+    assert_eq!(connections(), 0);
+
+    // Frames are sent once a receiver is connected
+    let (receiver, buffers) = video_receiver("connections", "");
+    buffers.next();
+    wait_until("a connection", || connections() == 1);
+
+    drop(receiver);
+    wait_until("no connections", || connections() == 0);
+}
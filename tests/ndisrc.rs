@@ -0,0 +1,395 @@
+// Checks how ndisrc follows senders that appear, disappear or are switched at runtime, and what
+// it reports about them
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+use gst::prelude::*;
+
+fn has_state(state: &'static str) -> impl Fn(&gst::StructureRef) -> bool {
+    move |s| s.get::<&str>("state").unwrap() == state
+}
+
+#[test]
+fn test_stats() {
+    init();
+
+    let _sender = video_sender("stats", 32);
+    let (receiver, buffers) = video_receiver("stats", "");
+    for _ in 0..5 {
+        buffers.next();
+    }
+
+    let stats = receiver
+        .by_name("src")
+        .property("stats")
+        .unwrap()
+        .get::<gst::Structure>()
+        .unwrap();
+    assert_eq!(stats.name(), "application/x-ndi-receiver-stats");
+    assert!(stats.get::<i64>("video-frames").unwrap() >= 5);
+    assert_eq!(stats.get::<i64>("audio-frames").unwrap(), 0);
+}
+
+#[test]
+fn test_ptz() {
+    init();
+
+    let sender = video_sender("ptz", 32);
+    let (receiver, buffers) = video_receiver("ptz", "");
+    buffers.next();
+
+    let src = receiver.by_name("src");
+    assert!(!src
+        .property("ptz-supported")
+        .unwrap()
+        .get::<bool>()
+        .unwrap());
+
+    // Commands are sent to the sender as metadata
+    let res = src.emit_by_name("ptz-zoom", &[&0.5f32]).unwrap().unwrap();
+    assert!(res.get::<bool>().unwrap());
+    sender.wait_for_message("ndi-receiver-metadata", |s| {
+        s.get::<&str>("metadata").unwrap() == "<ntk_ptz_zoom zoom=\"0.5\"/>"
+    });
+
+    let res = src
+        .emit_by_name("ptz-pan-tilt", &[&0.25f32, &-0.25f32])
+        .unwrap()
+        .unwrap();
+    assert!(res.get::<bool>().unwrap());
+    sender.wait_for_message("ndi-receiver-metadata", |s| {
+        s.get::<&str>("metadata").unwrap() == "<ntk_ptz_pan_tilt pan=\"0.25\" tilt=\"-0.25\"/>"
+    });
+}
+
+#[test]
+fn test_framesync() {
+    init();
+
+    let _sender = video_sender("framesync", 32);
+    let (_receiver, buffers) = video_receiver("framesync", "receive-mode=framesync");
+
+    let (caps, first) = buffers.next();
+    assert_eq!(width(&caps), 32);
+    let (_caps, second) = buffers.next();
+    assert!(second.pts().unwrap() > first.pts().unwrap());
+}
+
+#[test]
+fn test_source_switch() {
+    init();
+
+    let _sender_a = video_sender("switch-a", 32);
+    let _sender_b = video_sender("switch-b", 48);
+    let (receiver, buffers) = video_receiver("switch-a", "");
+
+    assert_eq!(width(&buffers.next().0), 32);
+
+    receiver
+        .by_name("src")
+        .set_property("ndi-name", ndi_name("switch-b"))
+        .unwrap();
+
+    receiver.wait_for_message("ndi-source-changed", |s| {
+        s.get::<&str>("ndi-name").unwrap() == ndi_name("switch-b")
+    });
+    wait_for_width(&buffers, 48);
+}
+
+// URL/address of the sender the receiver connected to
+fn url_address(receiver: &Pipeline) -> String {
+    receiver
+        .wait_for_message("ndi-connection", has_state("connected"))
+        .get::<String>("url-address")
+        .unwrap()
+}
+
+#[test]
+fn test_source_switch_single() {
+    init();
+
+    let _sender_a = video_sender("switch-single-a", 32);
+    let _sender_b = video_sender("switch-single-b", 48);
+    let (receiver, buffers) = video_receiver("switch-single-b", "");
+    let src = receiver.by_name("src");
+
+    let url_address_b = url_address(&receiver);
+    wait_for_width(&buffers, 48);
+
+    src.set_property("ndi-name", ndi_name("switch-single-a"))
+        .unwrap();
+    wait_for_width(&buffers, 32);
+
+    // Like g_object_set(), notifications are only sent once both properties are set
+    {
+        let _guard = src.freeze_notify();
+        src.set_property("ndi-name", ndi_name("switch-single-b"))
+            .unwrap();
+        src.set_property("url-address", &url_address_b).unwrap();
+    }
+    wait_for_width(&buffers, 48);
+
+    // Both switches posted exactly one message
+    let bus = receiver.0.bus().unwrap();
+    let changes = std::iter::from_fn(|| bus.pop_filtered(&[gst::MessageType::Element]))
+        .filter(|msg| msg.structure().unwrap().name() == "ndi-source-changed")
+        .count();
+    assert_eq!(changes, 2);
+}
+
+#[test]
+fn test_source_switch_clears_url_address() {
+    init();
+
+    let _sender_a = video_sender("switch-url-a", 32);
+    let _sender_b = video_sender("switch-url-b", 48);
+    let (receiver, buffers) = video_receiver("switch-url-a", "");
+    let src = receiver.by_name("src");
+
+    let url_address_a = url_address(&receiver);
+    wait_for_width(&buffers, 32);
+    src.set_property("url-address", &url_address_a).unwrap();
+
+    src.set_property("ndi-name", ndi_name("switch-url-b"))
+        .unwrap();
+    wait_for_width(&buffers, 48);
+    assert_eq!(
+        src.property("url-address")
+            .unwrap()
+            .get::<Option<String>>()
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_timeout_reconnect() {
+    init();
+
+    let first_sender = video_sender("reconnect", 32);
+    let (receiver, buffers) = video_receiver(
+        "reconnect",
+        "timeout=500 timeout-action=reconnect reconnect-interval=200",
+    );
+
+    receiver.wait_for_message("ndi-connection", has_state("connected"));
+    buffers.next();
+
+    drop(first_sender);
+
+    receiver.wait_for_message("ndi-connection", |s| {
+        s.get::<&str>("state").unwrap() == "reconnecting" && s.get::<u32>("attempt").unwrap() >= 1
+    });
+
+    let _sender = video_sender("reconnect", 32);
+
+    receiver.wait_for_message("ndi-connection", has_state("connected"));
+    wait_for_width(&buffers, 32);
+}
+
+#[test]
+fn test_timeout_eos() {
+    init();
+
+    let sender = video_sender("timeout-eos", 32);
+    let (receiver, buffers) = video_receiver("timeout-eos", "timeout=500 timeout-action=eos");
+
+    buffers.next();
+    drop(sender);
+
+    receiver.wait_for_eos();
+}
+
+#[test]
+fn test_connection_messages() {
+    init();
+
+    let sender = video_sender("messages", 32);
+    let (receiver, buffers) = video_receiver("messages", "timeout=10000");
+
+    let s = receiver.wait_for_message("ndi-connection", has_state("connecting"));
+    assert_eq!(s.get::<&str>("ndi-name").unwrap(), ndi_name("messages"));
+    let s = receiver.wait_for_message("ndi-connection", has_state("connected"));
+    assert_eq!(s.get::<&str>("ndi-name").unwrap(), ndi_name("messages"));
+    assert!(s
+        .get::<&str>("url-address")
+        .unwrap()
+        .starts_with("loopback:"));
+    buffers.next();
+
+    drop(sender);
+
+    receiver.wait_for_message("ndi-status-change", |s| {
+        s.get::<i32>("connections").unwrap() == 0
+    });
+    receiver.wait_for_message("ndi-connection", has_state("disconnected"));
+}
+
+#[test]
+fn test_tally_echo() {
+    init();
+
+    let _sender = video_sender("tally-echo", 32);
+    let (receiver, buffers) = video_receiver("tally-echo", "on-program=true on-preview=false");
+    buffers.next();
+
+    receiver.wait_for_message("ndi-status-change", |s| {
+        s.get::<bool>("on-program") == Ok(true) && s.get::<bool>("on-preview") == Ok(false)
+    });
+
+    receiver
+        .by_name("src")
+        .set_property("on-preview", true)
+        .unwrap();
+
+    receiver.wait_for_message("ndi-status-change", |s| {
+        s.get::<bool>("on-program") == Ok(true) && s.get::<bool>("on-preview") == Ok(true)
+    });
+}
+
+#[test]
+fn test_tally_echo_quotes() {
+    init();
+
+    // Echoes sent by other senders may use single quotes and whitespace around the `=`
+    let sender = Pipeline::new(
+        "videotestsrc is-live=true ! video/x-raw,format=UYVY,width=32,height=16 ! \
+         ndisink ndi-name=tally-echo-quotes \
+         connection-metadata=\"<ndi_tally_echo on_program = 'true' on_preview='true'/>\"",
+    );
+    sender.play();
+    let (receiver, buffers) =
+        video_receiver("tally-echo-quotes", "on-program=false on-preview=false");
+    buffers.next();
+
+    receiver.wait_for_message("ndi-status-change", |s| {
+        s.get::<bool>("on-program") == Ok(true) && s.get::<bool>("on-preview") == Ok(true)
+    });
+}
+
+// Starts a receiver whose ndisrc is configured by `configure` instead of for a specific sender
+fn receiver_with(configure: impl FnOnce(&gst::Element)) -> (Pipeline, Buffers) {
+    let receiver =
+        Pipeline::new("ndisrc name=src ! ndisrcdemux name=demux demux.video ! fakesink name=sink");
+    configure(&receiver.by_name("src"));
+    let buffers = Buffers::new(&receiver, "sink");
+    receiver.play();
+    (receiver, buffers)
+}
+
+fn resolved_ndi_name(receiver: &Pipeline) -> String {
+    receiver
+        .by_name("src")
+        .property("resolved-ndi-name")
+        .unwrap()
+        .get::<String>()
+        .unwrap()
+}
+
+#[test]
+fn test_glob_name() {
+    init();
+
+    let _sender_1 = video_sender("glob-1", 32);
+    let _sender_2 = video_sender("glob-2", 48);
+    let (receiver, buffers) = video_receiver("glob-?", "ndi-name-match=glob");
+
+    // The smallest matching NDI name is used
+    wait_for_width(&buffers, 32);
+    assert_eq!(resolved_ndi_name(&receiver), ndi_name("glob-1"));
+}
+
+#[test]
+fn test_regex_name() {
+    init();
+
+    let _sender = video_sender("regex-48", 48);
+    let (receiver, buffers) = receiver_with(|src| {
+        src.set_property("ndi-name", r".* \(regex-[0-9]+\)")
+            .unwrap();
+        src.set_property_from_str("ndi-name-match", "regex");
+    });
+
+    wait_for_width(&buffers, 48);
+    assert_eq!(resolved_ndi_name(&receiver), ndi_name("regex-48"));
+}
+
+#[test]
+fn test_regex_name_is_anchored() {
+    init();
+
+    // Only the second sender matches the whole pattern
+    let _sender_a = video_sender("anchored-a-x", 32);
+    let _sender_b = video_sender("anchored-b", 48);
+    let (receiver, buffers) = receiver_with(|src| {
+        src.set_property("ndi-name", r".* \(anchored-[ab]\)?")
+            .unwrap();
+        src.set_property_from_str("ndi-name-match", "regex");
+    });
+
+    wait_for_width(&buffers, 48);
+    assert_eq!(resolved_ndi_name(&receiver), ndi_name("anchored-b"));
+}
+
+#[test]
+fn test_failover() {
+    init();
+
+    let primary = video_sender("failover-primary", 32);
+    let _backup = video_sender("failover-backup", 48);
+    let (receiver, buffers) = video_receiver(
+        "failover-primary",
+        &format!(
+            "backup-ndi-name=\"{}\" failover-timeout=300 timeout-action=reconnect",
+            ndi_name("failover-backup")
+        ),
+    );
+
+    wait_for_width(&buffers, 32);
+
+    drop(primary);
+
+    let s = receiver.wait_for_message("ndi-failover", |s| {
+        s.get::<&str>("active").unwrap() == "backup"
+    });
+    assert_eq!(
+        s.get::<&str>("ndi-name").unwrap(),
+        ndi_name("failover-backup")
+    );
+    wait_for_width(&buffers, 48);
+
+    // Fails back once the primary source delivers frames again
+    let _primary = video_sender("failover-primary", 32);
+
+    let s = receiver.wait_for_message("ndi-failover", |s| {
+        s.get::<&str>("active").unwrap() == "primary"
+    });
+    assert_eq!(
+        s.get::<&str>("ndi-name").unwrap(),
+        ndi_name("failover-primary")
+    );
+    wait_for_width(&buffers, 32);
+}
+
+#[test]
+fn test_failover_without_primary() {
+    init();
+
+    let _backup = video_sender("failover-only-backup", 48);
+    let (receiver, buffers) = video_receiver(
+        "failover-missing-primary",
+        &format!(
+            "backup-ndi-name=\"{}\" connect-timeout=500 failover-timeout=300 \
+             timeout-action=reconnect",
+            ndi_name("failover-only-backup")
+        ),
+    );
+
+    receiver.wait_for_message("ndi-failover", |s| {
+        s.get::<&str>("active").unwrap() == "backup"
+    });
+    wait_for_width(&buffers, 48);
+}
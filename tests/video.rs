@@ -0,0 +1,214 @@
+// Sends known video through ndisink or directly through the backend and checks what ndisrc
+// receives
+#![cfg(feature = "sink")]
+
+mod common;
+
+use common::*;
+
+use gstndi::ndisys::*;
+
+// Repeats `pattern` over all of `data`
+fn fill_pattern(data: &mut [u8], pattern: &[u8]) {
+    for (dest, src) in data.iter_mut().zip(pattern.iter().cycle()) {
+        *dest = *src;
+    }
+}
+
+// Fills each component of a planar little-endian 16 bit video format with the given value, in
+// the order Y, U, V, A
+fn fill_planar16(values: [u16; 4]) -> impl Fn(&gst::Caps, &mut gst::BufferRef) {
+    move |caps, buffer| {
+        let info = gst_video::VideoInfo::from_caps(caps).unwrap();
+        let format_info = info.format_info();
+        let mut frame = gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info).unwrap();
+        for (component, plane) in format_info.plane().iter().enumerate() {
+            let data = frame.plane_data_mut(*plane).unwrap();
+            fill_pattern(data, &values[component].to_le_bytes());
+        }
+    }
+}
+
+// Calls `check` with the first `width` pixels of every line of the first plane
+fn for_each_line(caps: &gst::Caps, buffer: &gst::Buffer, mut check: impl FnMut(&[u8])) {
+    let info = gst_video::VideoInfo::from_caps(caps).unwrap();
+    let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, &info).unwrap();
+    let stride = frame.plane_stride()[0] as usize;
+    let line_size = info.format_info().pixel_stride()[0] as usize * info.width() as usize;
+
+    for line in frame.plane_data(0).unwrap().chunks(stride) {
+        check(&line[..line_size]);
+    }
+}
+
+// Starts a live video sender with the given caps whose buffers are overwritten by `fill`
+fn filled_video_sender(
+    name: &str,
+    caps: &str,
+    fill: impl Fn(&gst::Caps, &mut gst::BufferRef) + Send + Sync + 'static,
+) -> Pipeline {
+    let sender = Pipeline::new(&format!(
+        "videotestsrc name=src is-live=true ! {} ! ndisink ndi-name={}",
+        caps, name
+    ));
+    fill_buffers(&sender, "src", fill);
+    sender.play();
+    sender
+}
+
+#[test]
+fn test_uyvy() {
+    init();
+
+    let _sender = filled_video_sender(
+        "video-uyvy",
+        "video/x-raw,format=UYVY,width=32,height=16,framerate=30/1",
+        |caps, buffer| {
+            let info = gst_video::VideoInfo::from_caps(caps).unwrap();
+            let mut frame =
+                gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info).unwrap();
+            fill_pattern(frame.plane_data_mut(0).unwrap(), &[0x10, 0x20, 0x30, 0x40]);
+        },
+    );
+    let (_receiver, buffers) = video_receiver("video-uyvy", "");
+
+    let (caps, buffer) = buffers.next();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "UYVY");
+    assert_eq!(s.get::<i32>("width").unwrap(), 32);
+    assert_eq!(s.get::<i32>("height").unwrap(), 16);
+    for_each_line(&caps, &buffer, |line| {
+        for pixel in line.chunks_exact(4) {
+            assert_eq!(pixel, [0x10, 0x20, 0x30, 0x40]);
+        }
+    });
+}
+
+// Checks that every pixel of AYUV64 video has the given A, Y, U and V values
+fn check_ayuv64(caps: &gst::Caps, buffer: &gst::Buffer, expected: [u16; 4]) {
+    for_each_line(caps, buffer, |line| {
+        for pixel in line.chunks_exact(8) {
+            let pixel = [
+                u16::from_ne_bytes([pixel[0], pixel[1]]),
+                u16::from_ne_bytes([pixel[2], pixel[3]]),
+                u16::from_ne_bytes([pixel[4], pixel[5]]),
+                u16::from_ne_bytes([pixel[6], pixel[7]]),
+            ];
+            assert_eq!(pixel, expected);
+        }
+    });
+}
+
+// Builds a P216 frame with the given Y, U and V values, followed by an alpha plane for PA16
+fn p216_frame(width: usize, height: usize, [y, u, v]: [u16; 3], alpha: Option<u16>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for _ in 0..width * height {
+        data.extend_from_slice(&y.to_le_bytes());
+    }
+    for _ in 0..width * height / 2 {
+        data.extend_from_slice(&u.to_le_bytes());
+        data.extend_from_slice(&v.to_le_bytes());
+    }
+    if let Some(alpha) = alpha {
+        for _ in 0..width * height {
+            data.extend_from_slice(&alpha.to_le_bytes());
+        }
+    }
+    data
+}
+
+#[test]
+fn test_receive_p216() {
+    init();
+
+    let sender = RawSender::new("video-receive-p216");
+    let (_receiver, buffers) = video_receiver("video-receive-p216", "");
+
+    let frame = p216_frame(32, 16, [0x4000, 0x8000, 0xc000], None);
+    let (caps, buffer) = buffers.next_while(|| {
+        sender.send_video(NDIlib_FourCC_video_type_P216, (32, 16, 64), &frame, None)
+    });
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "AYUV64");
+    assert_eq!(s.get::<i32>("width").unwrap(), 32);
+    check_ayuv64(&caps, &buffer, [0xffff, 0x4000, 0x8000, 0xc000]);
+}
+
+#[test]
+fn test_receive_pa16() {
+    init();
+
+    let sender = RawSender::new("video-receive-pa16");
+    let (_receiver, buffers) = video_receiver("video-receive-pa16", "");
+
+    let frame = p216_frame(32, 16, [0x4000, 0x8000, 0xc000], Some(0x2000));
+    let (caps, buffer) = buffers.next_while(|| {
+        sender.send_video(NDIlib_FourCC_video_type_PA16, (32, 16, 64), &frame, None)
+    });
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "AYUV64");
+    check_ayuv64(&caps, &buffer, [0x2000, 0x4000, 0x8000, 0xc000]);
+}
+
+#[test]
+fn test_uyva() {
+    init();
+
+    let _sender = filled_video_sender(
+        "video-uyva",
+        "video/x-raw,format=AYUV,width=32,height=16,framerate=30/1",
+        |caps, buffer| {
+            let info = gst_video::VideoInfo::from_caps(caps).unwrap();
+            let mut frame =
+                gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info).unwrap();
+            fill_pattern(frame.plane_data_mut(0).unwrap(), &[0x80, 0x50, 0x60, 0x70]);
+        },
+    );
+    let (_receiver, buffers) = video_receiver("video-uyva", "preserve-alpha=true");
+
+    let (caps, buffer) = buffers.next();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "AYUV");
+    assert_eq!(s.get::<i32>("width").unwrap(), 32);
+    for_each_line(&caps, &buffer, |line| {
+        for pixel in line.chunks_exact(4) {
+            assert_eq!(pixel, [0x80, 0x50, 0x60, 0x70]);
+        }
+    });
+}
+
+#[test]
+fn test_p216() {
+    init();
+
+    // An odd width checks that the chroma of the last pixel is kept
+    let _sender = filled_video_sender(
+        "video-p216",
+        "video/x-raw,format=I422_10LE,width=33,height=16,framerate=30/1",
+        fill_planar16([0x100, 0x200, 0x300, 0]),
+    );
+    let (_receiver, buffers) = video_receiver("video-p216", "");
+
+    let (caps, buffer) = buffers.next();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "AYUV64");
+    assert_eq!(s.get::<i32>("width").unwrap(), 33);
+    check_ayuv64(&caps, &buffer, [0xffff, 0x4010, 0x8020, 0xc030]);
+}
+
+#[test]
+fn test_pa16() {
+    init();
+
+    let _sender = filled_video_sender(
+        "video-pa16",
+        "video/x-raw,format=A444_10LE,width=32,height=16,framerate=30/1",
+        fill_planar16([0x100, 0x200, 0x300, 0x200]),
+    );
+    let (_receiver, buffers) = video_receiver("video-pa16", "");
+
+    let (caps, buffer) = buffers.next();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<&str>("format").unwrap(), "AYUV64");
+    check_ayuv64(&caps, &buffer, [0x8020, 0x4010, 0x8020, 0xc030]);
+}